@@ -0,0 +1,38 @@
+//! Expands path tails that keep repeating the same few column names, with and without a
+//! `MemberInterner`. Without one, every string member allocates its own name.
+//!
+//! Run with `cargo bench --bench member_interning`.
+
+mod support;
+
+use nu::bench::LexedSource;
+use nu::MemberInterner;
+use support::measure;
+
+const COLUMNS: &[&str] = &["name", "size", "modified", "type"];
+const TAILS: usize = 1000;
+
+fn main() {
+    let source = (0..TAILS)
+        .map(|i| {
+            format!(
+                ".{}.{}",
+                COLUMNS[i % COLUMNS.len()],
+                COLUMNS[(i + 1) % COLUMNS.len()]
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    let lexed = LexedSource::new(&source);
+    let interner = MemberInterner::new();
+
+    println!("{} path tails of two members each", TAILS);
+    println!(
+        "  without an interner: {}",
+        measure(|| lexed.expand_path_tails(None))
+    );
+    println!(
+        "  with an interner:    {}",
+        measure(|| lexed.expand_path_tails(Some(&interner)))
+    );
+}
@@ -0,0 +1,25 @@
+//! Expands column paths of one to six members, keeping each path's members inline as
+//! the parser does and moving them into a `Vec` as it used to. Paths of up to four
+//! members fit inline, so they don't need a heap allocation for their members at all.
+//!
+//! Run with `cargo bench --bench path_members`.
+
+mod support;
+
+use nu::bench::LexedSource;
+use support::measure;
+
+const COLUMNS: &[&str] = &["name", "size", "modified", "type", "owner", "group"];
+const PATHS: usize = 1000;
+
+fn main() {
+    for len in 1..=COLUMNS.len() {
+        let path = COLUMNS[..len].join(".");
+        let source = vec![path; PATHS].join(" ");
+        let lexed = LexedSource::new(&source);
+
+        println!("{} column paths of {} members each", PATHS, len);
+        println!("  inline: {}", measure(|| lexed.expand_column_paths(false)));
+        println!("  vec:    {}", measure(|| lexed.expand_column_paths(true)));
+    }
+}
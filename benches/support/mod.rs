@@ -0,0 +1,65 @@
+//! Allocation counting shared by the benchmarks. Each benchmark is a plain binary that
+//! installs this allocator and prints what it measured.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+const RUNS: u32 = 100;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// The allocations and time of one run, averaged over several
+pub struct Measurement {
+    allocations: usize,
+    elapsed: Duration,
+}
+
+impl fmt::Display for Measurement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} allocations, {:?} per run",
+            self.allocations, self.elapsed
+        )
+    }
+}
+
+pub fn measure<T>(mut run: impl FnMut() -> T) -> Measurement {
+    let before = ALLOCATIONS.load(Ordering::SeqCst);
+    let start = Instant::now();
+
+    for _ in 0..RUNS {
+        drop(run());
+    }
+
+    let elapsed = start.elapsed();
+    let allocations = ALLOCATIONS.load(Ordering::SeqCst) - before;
+
+    Measurement {
+        allocations: allocations / RUNS as usize,
+        elapsed: elapsed / RUNS,
+    }
+}
@@ -74,7 +74,7 @@ impl ClassifiedInputStream {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub(crate) struct ClassifiedPipeline {
     pub(crate) commands: Spanned<Vec<ClassifiedCommand>>,
 }
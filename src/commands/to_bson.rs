@@ -55,10 +55,24 @@ pub fn value_to_bson_value(v: &Tagged<Value>) -> Result<Bson, ShellError> {
         Value::Primitive(Primitive::ColumnPath(path)) => Bson::Array(
             path.iter()
                 .map(|x| match &x.item {
-                    RawPathMember::String(string) => Ok(Bson::String(string.clone())),
+                    RawPathMember::String(string) => Ok(Bson::String(string.to_string())),
+                    RawPathMember::Entries
+                    | RawPathMember::Wildcard
+                    | RawPathMember::Glob(_)
+                    | RawPathMember::ColumnNumber(_)
+                    | RawPathMember::Splat(_)
+                    | RawPathMember::Computed(_)
+                    | RawPathMember::Filter(_)
+                    | RawPathMember::Spread(_)
+                    | RawPathMember::Alternatives(_)
+                    | RawPathMember::ComputedRange(_)
+                    | RawPathMember::Conditional(..)
+                    | RawPathMember::Cast(..)
+                    | RawPathMember::Range { .. } => Ok(Bson::String(x.item.to_string())),
                     RawPathMember::Int(int) => Ok(Bson::I64(
                         int.tagged(&v.tag).coerce_into("converting to BSON")?,
                     )),
+                    RawPathMember::SmallInt(int) => Ok(Bson::I64(*int)),
                 })
                 .collect::<Result<Vec<Bson>, ShellError>>()?,
         ),
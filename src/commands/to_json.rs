@@ -54,13 +54,33 @@ pub fn value_to_json_value(v: &Tagged<Value>) -> Result<serde_json::Value, Shell
         Value::Primitive(Primitive::ColumnPath(path)) => serde_json::Value::Array(
             path.iter()
                 .map(|x| match &x.item {
-                    RawPathMember::String(string) => Ok(serde_json::Value::String(string.clone())),
+                    RawPathMember::String(string) => {
+                        Ok(serde_json::Value::String(string.to_string()))
+                    }
+                    RawPathMember::Entries
+                    | RawPathMember::Wildcard
+                    | RawPathMember::Glob(_)
+                    | RawPathMember::ColumnNumber(_)
+                    | RawPathMember::Splat(_)
+                    | RawPathMember::Computed(_)
+                    | RawPathMember::Filter(_)
+                    | RawPathMember::Spread(_)
+                    | RawPathMember::Alternatives(_)
+                    | RawPathMember::ComputedRange(_)
+                    | RawPathMember::Conditional(..)
+                    | RawPathMember::Cast(..)
+                    | RawPathMember::Range { .. } => {
+                        Ok(serde_json::Value::String(x.item.to_string()))
+                    }
                     RawPathMember::Int(int) => Ok(serde_json::Value::Number(
                         serde_json::Number::from(CoerceInto::<i64>::coerce_into(
                             int.tagged(&v.tag),
                             "converting to JSON number",
                         )?),
                     )),
+                    RawPathMember::SmallInt(int) => {
+                        Ok(serde_json::Value::Number(serde_json::Number::from(*int)))
+                    }
                 })
                 .collect::<Result<Vec<serde_json::Value>, ShellError>>()?,
         ),
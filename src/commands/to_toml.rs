@@ -52,11 +52,25 @@ pub fn value_to_toml_value(v: &Tagged<Value>) -> Result<toml::Value, ShellError>
         Value::Primitive(Primitive::ColumnPath(path)) => toml::Value::Array(
             path.iter()
                 .map(|x| match &x.item {
-                    RawPathMember::String(string) => Ok(toml::Value::String(string.clone())),
+                    RawPathMember::String(string) => Ok(toml::Value::String(string.to_string())),
+                    RawPathMember::Entries
+                    | RawPathMember::Wildcard
+                    | RawPathMember::Glob(_)
+                    | RawPathMember::ColumnNumber(_)
+                    | RawPathMember::Splat(_)
+                    | RawPathMember::Computed(_)
+                    | RawPathMember::Filter(_)
+                    | RawPathMember::Spread(_)
+                    | RawPathMember::Alternatives(_)
+                    | RawPathMember::ComputedRange(_)
+                    | RawPathMember::Conditional(..)
+                    | RawPathMember::Cast(..)
+                    | RawPathMember::Range { .. } => Ok(toml::Value::String(x.item.to_string())),
                     RawPathMember::Int(int) => Ok(toml::Value::Integer(
                         int.tagged(&v.tag)
                             .coerce_into("converting to TOML integer")?,
                     )),
+                    RawPathMember::SmallInt(int) => Ok(toml::Value::Integer(*int)),
                 })
                 .collect::<Result<Vec<toml::Value>, ShellError>>()?,
         ),
@@ -54,7 +54,22 @@ pub fn value_to_yaml_value(v: &Tagged<Value>) -> Result<serde_yaml::Value, Shell
             for member in path.iter() {
                 match &member.item {
                     RawPathMember::String(string) => {
-                        out.push(serde_yaml::Value::String(string.clone()))
+                        out.push(serde_yaml::Value::String(string.to_string()))
+                    }
+                    RawPathMember::Entries
+                    | RawPathMember::Wildcard
+                    | RawPathMember::Glob(_)
+                    | RawPathMember::ColumnNumber(_)
+                    | RawPathMember::Splat(_)
+                    | RawPathMember::Computed(_)
+                    | RawPathMember::Filter(_)
+                    | RawPathMember::Spread(_)
+                    | RawPathMember::Alternatives(_)
+                    | RawPathMember::ComputedRange(_)
+                    | RawPathMember::Conditional(..)
+                    | RawPathMember::Cast(..)
+                    | RawPathMember::Range { .. } => {
+                        out.push(serde_yaml::Value::String(member.item.to_string()))
                     }
                     RawPathMember::Int(int) => out.push(serde_yaml::Value::Number(
                         serde_yaml::Number::from(CoerceInto::<i64>::coerce_into(
@@ -62,6 +77,9 @@ pub fn value_to_yaml_value(v: &Tagged<Value>) -> Result<serde_yaml::Value, Shell
                             "converting to YAML number",
                         )?),
                     )),
+                    RawPathMember::SmallInt(int) => {
+                        out.push(serde_yaml::Value::Number(serde_yaml::Number::from(*int)))
+                    }
                 }
             }
 
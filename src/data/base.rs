@@ -72,6 +72,10 @@ mod serde_bigdecimal {
     }
 }
 
+/// The most bits the result of `**` may have, so that `2 ** 4000000000` is an error rather
+/// than a hang
+pub const MAX_POW_BITS: u64 = 1 << 20;
+
 #[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq, Deserialize, Serialize)]
 pub enum Primitive {
     Nothing,
@@ -490,6 +494,122 @@ impl Value {
         }
     }
 
+    /// `self <=> other`: -1 if `self` is smaller, 0 if they're equal and 1 if `self` is
+    /// larger, coercing values the same way as `compare`
+    pub(crate) fn compare_ordering(
+        &self,
+        other: &Value,
+    ) -> Result<Value, (&'static str, &'static str)> {
+        use std::cmp::Ordering;
+
+        let ordering = match coerce_compare(self, other)?.compare() {
+            Ordering::Less => -1,
+            Ordering::Equal => 0,
+            Ordering::Greater => 1,
+        };
+
+        Ok(Value::int(ordering))
+    }
+
+    /// `self + other`, `self - other` or `self * other`, coercing numbers the same way as `compare`
+    pub(crate) fn arithmetic(
+        &self,
+        operator: &Operator,
+        other: &Value,
+    ) -> Result<Value, (&'static str, &'static str)> {
+        match (operator, coerce_compare(self, other)?) {
+            (Operator::Plus, CompareValues::Ints(left, right)) => Ok(Value::int(left + right)),
+            (Operator::Plus, CompareValues::Decimals(left, right)) => {
+                Ok(Value::decimal(left + right))
+            }
+            (Operator::Minus, CompareValues::Ints(left, right)) => Ok(Value::int(left - right)),
+            (Operator::Minus, CompareValues::Decimals(left, right)) => {
+                Ok(Value::decimal(left - right))
+            }
+            (Operator::Multiply, CompareValues::Ints(left, right)) => Ok(Value::int(left * right)),
+            (Operator::Multiply, CompareValues::Decimals(left, right)) => {
+                Ok(Value::decimal(left * right))
+            }
+            _ => Err((self.type_name(), other.type_name())),
+        }
+    }
+
+    /// The remainder of `self % other`, coercing numbers the same way as `compare`.
+    /// Returns `Ok(None)` if `other` is zero.
+    pub(crate) fn modulo(
+        &self,
+        other: &Value,
+    ) -> Result<Option<Value>, (&'static str, &'static str)> {
+        match coerce_compare(self, other)? {
+            CompareValues::Ints(left, right) => {
+                if right.is_zero() {
+                    Ok(None)
+                } else {
+                    Ok(Some(Value::int(left % right)))
+                }
+            }
+            CompareValues::Decimals(left, right) => {
+                if right.is_zero() {
+                    Ok(None)
+                } else {
+                    Ok(Some(Value::decimal(left % right)))
+                }
+            }
+            _ => Err((self.type_name(), other.type_name())),
+        }
+    }
+
+    /// `self ++ other`, which joins two tables or two strings
+    pub(crate) fn append(&self, other: &Value) -> Result<Value, (&'static str, &'static str)> {
+        match (self, other) {
+            (Value::Table(left), Value::Table(right)) => {
+                let mut items = left.clone();
+                items.extend(right.iter().cloned());
+                Ok(Value::Table(items))
+            }
+            (
+                Value::Primitive(Primitive::String(left)),
+                Value::Primitive(Primitive::String(right)),
+            ) => Ok(Value::string(format!("{}{}", left, right))),
+            _ => Err((self.type_name(), other.type_name())),
+        }
+    }
+
+    /// `self ** other` for integers. Returns `Ok(None)` if the exponent is negative, or if
+    /// the result would have more than `MAX_POW_BITS` bits.
+    pub(crate) fn pow(&self, other: &Value) -> Result<Option<Value>, (&'static str, &'static str)> {
+        use num_traits::pow::Pow;
+
+        match coerce_compare(self, other)? {
+            CompareValues::Ints(left, right) => {
+                let exponent = match right.to_u32() {
+                    Some(exponent) => exponent,
+                    None => return Ok(None),
+                };
+
+                // the result has at least this many bits, so a huge exponent is rejected
+                // before any work is done
+                let bits = (left.bits() as u64).saturating_sub(1) * u64::from(exponent);
+
+                if bits > MAX_POW_BITS {
+                    return Ok(None);
+                }
+
+                Ok(Some(Value::int((&left).pow(exponent))))
+            }
+            _ => Err((self.type_name(), other.type_name())),
+        }
+    }
+
+    /// `-self` for numbers. Returns `None` for any other kind of value.
+    pub(crate) fn negate(&self) -> Option<Value> {
+        match self {
+            Value::Primitive(Primitive::Int(int)) => Some(Value::int(-int.clone())),
+            Value::Primitive(Primitive::Decimal(decimal)) => Some(Value::decimal(-decimal.clone())),
+            _ => None,
+        }
+    }
+
     pub(crate) fn is_true(&self) -> bool {
         match self {
             Value::Primitive(Primitive::Boolean(true)) => true,
@@ -845,6 +965,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn column_path_that_contains_a_negative_number_gets_a_row_from_the_end_of_a_table() {
+        let field_path = column_path(&vec![string("package"), string("authors"), int(-1)]);
+
+        let (_, tag) = string("Yehuda Katz").into_parts();
+
+        let value = Value::row(indexmap! {
+            "package".into() => row(indexmap! {
+                "name".into() => string("nu"),
+                "version".into() => string("0.4.0"),
+                "authors".into() => table(&vec![
+                    row(indexmap!{"name".into() => string("Andrés N. Robalino")}),
+                    row(indexmap!{"name".into() => string("Jonathan Turner")}),
+                    row(indexmap!{"name".into() => string("Yehuda Katz")})
+                ])
+            })
+        });
+
+        assert_eq!(
+            *value
+                .tagged(tag)
+                .get_data_by_column_path(
+                    &field_path,
+                    Box::new(error_callback("package.authors.-1"))
+                )
+                .unwrap(),
+            Value::row(indexmap! {
+                "name".into() => string("Yehuda Katz")
+            })
+        );
+    }
+
     #[test]
     fn column_path_that_contains_just_a_number_gets_a_row_from_a_row() {
         let field_path = column_path(&vec![string("package"), string("authors"), string("0")]);
@@ -877,6 +1029,89 @@ mod tests {
         );
     }
 
+    #[test]
+    fn gets_columns_matching_a_glob() {
+        let path = ColumnPathValue::build(vec![PathMember::glob("user_*", Span::unknown())]);
+
+        let value = row(indexmap! {
+            "user_name".into() => string("andres"),
+            "user_id".into() => int(7),
+            "email".into() => string("andres@example.com")
+        });
+
+        let expected = Value::row(indexmap! {
+            "user_name".into() => string("andres"),
+            "user_id".into() => int(7)
+        });
+
+        assert_eq!(
+            *value
+                .get_data_by_column_path(&path, Box::new(error_callback("user_*")))
+                .unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn gets_a_column_by_its_number() {
+        let path = ColumnPathValue::build(vec![PathMember::column_number(2, Span::unknown())]);
+
+        let value = row(indexmap! {
+            "name".into() => string("andres"),
+            "id".into() => int(7),
+            "email".into() => string("andres@example.com")
+        });
+
+        assert_eq!(
+            *value
+                .get_data_by_column_path(&path, Box::new(error_callback("#2")))
+                .unwrap(),
+            Value::string("andres@example.com")
+        );
+
+        let table = table(&vec![
+            value,
+            row(indexmap! {
+                "name".into() => string("jonathan"),
+                "id".into() => int(8),
+                "email".into() => string("jonathan@example.com")
+            }),
+        ]);
+
+        assert_eq!(
+            *table
+                .get_data_by_column_path(&path, Box::new(error_callback("#2")))
+                .unwrap(),
+            Value::table(&vec![
+                string("andres@example.com"),
+                string("jonathan@example.com")
+            ])
+        );
+    }
+
+    #[test]
+    fn splat_flattens_nested_tables() {
+        let path = ColumnPathValue::build(vec![PathMember::splat(
+            PathMember::bare_from("data"),
+            Span::unknown(),
+        )]);
+
+        let value = row(indexmap! {
+            "data".into() => table(&vec![
+                table(&vec![int(1), int(2)]),
+                int(3),
+                table(&vec![int(4)])
+            ])
+        });
+
+        assert_eq!(
+            *value
+                .get_data_by_column_path(&path, Box::new(error_callback("data...")))
+                .unwrap(),
+            Value::table(&vec![int(1), int(2), int(3), int(4)])
+        );
+    }
+
     #[test]
     fn replaces_matching_field_from_a_row() {
         let field_path = column_path(&vec![string("amigos")]);
@@ -999,4 +1234,29 @@ mod tests {
             }).tagged(&tag)
         );
     }
+
+    #[test]
+    fn appends_strings_and_tables() {
+        assert_eq!(
+            string("foo").append(&string("bar")),
+            Ok(Value::string("foobar"))
+        );
+
+        assert_eq!(
+            table(&vec![int(1)]).append(&table(&vec![int(2), int(3)])),
+            Ok(Value::Table(vec![int(1), int(2), int(3)]))
+        );
+
+        assert_eq!(string("foo").append(&int(1)), Err(("string", "integer")));
+    }
+
+    #[test]
+    fn pow_rejects_results_that_are_too_large() {
+        assert_eq!(int(2).pow(&int(8)), Ok(Some(Value::int(256))));
+        assert_eq!(int(2).pow(&int(-1)), Ok(None));
+        assert_eq!(int(2).pow(&int(4_000_000_000u32)), Ok(None));
+
+        // the result stays small no matter the exponent
+        assert_eq!(int(1).pow(&int(4_000_000_000u32)), Ok(Some(Value::int(1))));
+    }
 }
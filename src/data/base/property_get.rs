@@ -1,3 +1,4 @@
+use crate::data::{Dictionary, TaggedDictBuilder};
 use crate::errors::ExpectedRange;
 use crate::parser::hir::path::{PathMember, RawPathMember};
 use crate::prelude::*;
@@ -22,11 +23,71 @@ impl Tagged<Value> {
                         )
                     }),
 
-                // If the member is a number, it's an error
-                RawPathMember::Int(_) => Err(ShellError::invalid_integer_index(
+                // If the member is a number or a range, it's an error
+                RawPathMember::Int(_)
+                | RawPathMember::SmallInt(_)
+                | RawPathMember::Range { .. } => Err(ShellError::invalid_integer_index(
                     "row".spanned(self.tag.span),
                     name.span,
                 )),
+
+                // If the member is `~`, get the key-value pairs as a table
+                RawPathMember::Entries => {
+                    let tag = Tag::new(self.anchor(), name.span);
+
+                    let entries = o
+                        .entries
+                        .iter()
+                        .map(|(key, value)| {
+                            let mut entry = TaggedDictBuilder::new(&tag);
+                            entry.insert("key", Value::string(key));
+                            entry.insert_tagged("value", value.clone());
+                            entry.into_tagged_value()
+                        })
+                        .collect();
+
+                    Ok(Value::Table(entries).tagged(tag))
+                }
+
+                // Only tables have rows to select
+                RawPathMember::Wildcard => Err(ShellError::type_error(
+                    "table",
+                    "row".spanned(self.tag.span),
+                )),
+
+                // If the member is a splat, get its inner member and flatten it
+                RawPathMember::Splat(member) => self.get_data_by_member(member)?.flatten(name.span),
+
+                // Only the evaluator can compute a member
+                RawPathMember::Computed(_)
+                | RawPathMember::Filter(_)
+                | RawPathMember::Spread(_)
+                | RawPathMember::Alternatives(_)
+                | RawPathMember::ComputedRange(_)
+                | RawPathMember::Conditional(..)
+                | RawPathMember::Cast(..) => Err(unevaluated_member(name)),
+
+                // If the member is a column number, get the column at that position
+                RawPathMember::ColumnNumber(number) => nth_column(o, number).ok_or_else(|| {
+                    ShellError::range_error(
+                        0..(o.entries.len()),
+                        &number.tagged(name.span),
+                        "selecting a column",
+                    )
+                }),
+
+                // If the member is a glob, get a row of the columns it matches
+                RawPathMember::Glob(pattern) => {
+                    let tag = Tag::new(self.anchor(), name.span);
+
+                    match select_matching_columns(o, pattern, &tag, name.span)? {
+                        Some(row) => Ok(row),
+                        None => Err(ShellError::missing_property(
+                            "row".spanned(self.tag.span),
+                            pattern.spanned(name.span),
+                        )),
+                    }
+                }
             },
 
             // If the value is a table
@@ -57,6 +118,26 @@ impl Tagged<Value> {
                         Ok(Value::Table(out).tagged(Tag::new(self.anchor(), name.span)))
                     }
                 }
+                // If the member is a small number, index without big integer arithmetic
+                RawPathMember::SmallInt(int) => match small_index(l.len(), *int) {
+                    Some(index) => Ok(l[index].clone()),
+                    None => Err(ShellError::range_error(
+                        0..(l.len()),
+                        &int.tagged(name.span),
+                        "indexing",
+                    )),
+                },
+                // If the member is a negative number, count back from the end of the table
+                RawPathMember::Int(int) if *int < BigInt::zero() => {
+                    match (BigInt::from(l.len()) + int).to_usize() {
+                        Some(index) => Ok(l[index].clone()),
+                        None => Err(ShellError::range_error(
+                            0..(l.len()),
+                            &int.tagged(name.span),
+                            "indexing",
+                        )),
+                    }
+                }
                 RawPathMember::Int(int) => {
                     let index = int.to_usize().ok_or_else(|| {
                         ShellError::range_error(
@@ -75,6 +156,101 @@ impl Tagged<Value> {
                         )),
                     }
                 }
+
+                // If the member is a range, slice the table
+                RawPathMember::Range {
+                    from,
+                    to,
+                    inclusive,
+                } => {
+                    let len = l.len();
+                    let start = range_bound(from, len);
+                    let end = match to {
+                        Some(to) if *inclusive => range_bound(to, len).map(|end| end + 1),
+                        Some(to) => range_bound(to, len),
+                        None => Some(len),
+                    };
+
+                    match (start, end) {
+                        (Some(start), Some(end)) if start <= end && end <= len => {
+                            Ok(Value::Table(l[start..end].to_vec())
+                                .tagged(Tag::new(self.anchor(), name.span)))
+                        }
+                        _ => Err(ShellError::range_error(
+                            0..len,
+                            &format!("{}", name.item).tagged(name.span),
+                            "slicing",
+                        )),
+                    }
+                }
+
+                // Only rows have entries
+                RawPathMember::Entries => Err(ShellError::type_error(
+                    "row",
+                    "table".spanned(self.tag.span),
+                )),
+
+                // If the member is `*`, select every row, so the next member applies to each
+                RawPathMember::Wildcard => {
+                    Ok(Value::Table(l.clone()).tagged(Tag::new(self.anchor(), name.span)))
+                }
+
+                // If the member is a splat, get its inner member and flatten it
+                RawPathMember::Splat(member) => self.get_data_by_member(member)?.flatten(name.span),
+
+                // Only the evaluator can compute a member
+                RawPathMember::Computed(_)
+                | RawPathMember::Filter(_)
+                | RawPathMember::Spread(_)
+                | RawPathMember::Alternatives(_)
+                | RawPathMember::ComputedRange(_)
+                | RawPathMember::Conditional(..)
+                | RawPathMember::Cast(..) => Err(unevaluated_member(name)),
+
+                // If the member is a column number, map over the column at that position
+                RawPathMember::ColumnNumber(number) => {
+                    let out: Vec<_> = l
+                        .iter()
+                        .filter_map(|item| match &item.item {
+                            Value::Row(o) => nth_column(o, number),
+                            _ => None,
+                        })
+                        .collect();
+
+                    if out.len() == 0 {
+                        Err(ShellError::missing_property(
+                            "table".spanned(self.tag.span),
+                            name.item.to_string().spanned(name.span),
+                        ))
+                    } else {
+                        Ok(Value::Table(out).tagged(Tag::new(self.anchor(), name.span)))
+                    }
+                }
+
+                // If the member is a glob, map over the columns it matches
+                RawPathMember::Glob(pattern) => {
+                    let tag = Tag::new(self.anchor(), name.span);
+                    let mut out = vec![];
+
+                    for item in l {
+                        match item {
+                            Tagged {
+                                item: Value::Row(o),
+                                ..
+                            } => out.extend(select_matching_columns(o, pattern, &tag, name.span)?),
+                            _ => {}
+                        }
+                    }
+
+                    if out.len() == 0 {
+                        Err(ShellError::missing_property(
+                            "table".spanned(self.tag.span),
+                            pattern.spanned(name.span),
+                        ))
+                    } else {
+                        Ok(Value::Table(out).tagged(tag))
+                    }
+                }
             },
             other => Err(ShellError::type_error(
                 "row or table",
@@ -160,16 +336,122 @@ impl Tagged<Value> {
                 RawPathMember::String(key) => Ok({
                     dict.insert_data_at_key(key, new_value);
                 }),
-                RawPathMember::Int(_) => Err(ShellError::type_error(
+                RawPathMember::Int(_) | RawPathMember::SmallInt(_) => Err(ShellError::type_error(
                     "column name",
                     "integer".spanned(member.span),
                 )),
+                RawPathMember::Entries => Err(ShellError::type_error(
+                    "column name",
+                    "entries".spanned(member.span),
+                )),
+                RawPathMember::Range { .. } | RawPathMember::ComputedRange(_) => Err(
+                    ShellError::type_error("column name", "range".spanned(member.span)),
+                ),
+                RawPathMember::Wildcard => Err(ShellError::type_error(
+                    "column name",
+                    "wildcard".spanned(member.span),
+                )),
+                RawPathMember::Glob(_) => Err(ShellError::type_error(
+                    "column name",
+                    "glob".spanned(member.span),
+                )),
+                RawPathMember::Splat(_) => Err(ShellError::type_error(
+                    "column name",
+                    "splat".spanned(member.span),
+                )),
+                RawPathMember::Computed(_) => Err(ShellError::type_error(
+                    "column name",
+                    "computed member".spanned(member.span),
+                )),
+                RawPathMember::Conditional(..) => Err(ShellError::type_error(
+                    "column name",
+                    "conditional member".spanned(member.span),
+                )),
+                RawPathMember::Cast(..) => Err(ShellError::type_error(
+                    "column name",
+                    "cast member".spanned(member.span),
+                )),
+                RawPathMember::Filter(_) => Err(ShellError::type_error(
+                    "column name",
+                    "filter".spanned(member.span),
+                )),
+                RawPathMember::Spread(_) => Err(ShellError::type_error(
+                    "column name",
+                    "spread".spanned(member.span),
+                )),
+                RawPathMember::Alternatives(_) => Err(ShellError::type_error(
+                    "column name",
+                    "alternatives".spanned(member.span),
+                )),
+                RawPathMember::ColumnNumber(number) => {
+                    let len = dict.entries.len();
+
+                    match range_bound(number, len)
+                        .and_then(|index| dict.entries.get_index_mut(index))
+                    {
+                        Some((_, value)) => Ok({
+                            *value = new_value;
+                        }),
+                        None => Err(ShellError::range_error(
+                            0..len,
+                            &number.tagged(member.span),
+                            "inserting into a column",
+                        )),
+                    }
+                }
             },
             Value::Table(array) => match &member.item {
                 RawPathMember::String(_) => Err(ShellError::type_error(
                     "list index",
                     "string".spanned(member.span),
                 )),
+                RawPathMember::Entries => Err(ShellError::type_error(
+                    "list index",
+                    "entries".spanned(member.span),
+                )),
+                RawPathMember::Range { .. } | RawPathMember::ComputedRange(_) => Err(
+                    ShellError::type_error("list index", "range".spanned(member.span)),
+                ),
+                RawPathMember::Wildcard => Err(ShellError::type_error(
+                    "list index",
+                    "wildcard".spanned(member.span),
+                )),
+                RawPathMember::Glob(_) => Err(ShellError::type_error(
+                    "list index",
+                    "glob".spanned(member.span),
+                )),
+                RawPathMember::ColumnNumber(_) => Err(ShellError::type_error(
+                    "list index",
+                    "column number".spanned(member.span),
+                )),
+                RawPathMember::Splat(_) => Err(ShellError::type_error(
+                    "list index",
+                    "splat".spanned(member.span),
+                )),
+                RawPathMember::Computed(_) => Err(ShellError::type_error(
+                    "list index",
+                    "computed member".spanned(member.span),
+                )),
+                RawPathMember::Conditional(..) => Err(ShellError::type_error(
+                    "list index",
+                    "conditional member".spanned(member.span),
+                )),
+                RawPathMember::Cast(..) => Err(ShellError::type_error(
+                    "list index",
+                    "cast member".spanned(member.span),
+                )),
+                RawPathMember::Filter(_) => Err(ShellError::type_error(
+                    "list index",
+                    "filter".spanned(member.span),
+                )),
+                RawPathMember::Spread(_) => Err(ShellError::type_error(
+                    "list index",
+                    "spread".spanned(member.span),
+                )),
+                RawPathMember::Alternatives(_) => Err(ShellError::type_error(
+                    "list index",
+                    "alternatives".spanned(member.span),
+                )),
                 RawPathMember::Int(int) => Ok({
                     let int = int.to_usize().ok_or_else(|| {
                         ShellError::range_error(
@@ -179,15 +461,40 @@ impl Tagged<Value> {
                         )
                     })?;
 
+                    insert_data_at_index(array, int.tagged(member.span), new_value.clone())?;
+                }),
+                RawPathMember::SmallInt(int) => Ok({
+                    let int = int.to_usize().ok_or_else(|| {
+                        ShellError::range_error(
+                            ExpectedRange::Usize,
+                            &"negative number".tagged(member.span),
+                            "inserting into a list",
+                        )
+                    })?;
+
                     insert_data_at_index(array, int.tagged(member.span), new_value.clone())?;
                 }),
             },
             other => match &member.item {
-                RawPathMember::String(_) => Err(ShellError::type_error(
+                RawPathMember::String(_)
+                | RawPathMember::Entries
+                | RawPathMember::Glob(_)
+                | RawPathMember::ColumnNumber(_) => Err(ShellError::type_error(
                     "row",
                     other.type_name().spanned(self.span()),
                 )),
-                RawPathMember::Int(_) => Err(ShellError::type_error(
+                RawPathMember::Int(_)
+                | RawPathMember::SmallInt(_)
+                | RawPathMember::Range { .. }
+                | RawPathMember::ComputedRange(_)
+                | RawPathMember::Conditional(..)
+                | RawPathMember::Cast(..)
+                | RawPathMember::Wildcard
+                | RawPathMember::Splat(_)
+                | RawPathMember::Computed(_)
+                | RawPathMember::Filter(_)
+                | RawPathMember::Spread(_)
+                | RawPathMember::Alternatives(_) => Err(ShellError::type_error(
                     "table",
                     other.type_name().spanned(self.span()),
                 )),
@@ -252,6 +559,33 @@ impl Tagged<Value> {
         None
     }
 
+    /// The value with its nested tables flattened into it, like the result of a splat
+    /// member. The rows of a table that are themselves tables are spliced into it, and a
+    /// row is flattened into a table of its values.
+    pub(crate) fn flatten(&self, span: Span) -> Result<Tagged<Value>, ShellError> {
+        let tag = Tag::new(self.anchor(), span);
+
+        match &self.item {
+            Value::Table(l) => {
+                let mut out = vec![];
+
+                for item in l {
+                    match &item.item {
+                        Value::Table(nested) => out.extend(nested.iter().cloned()),
+                        _ => out.push(item.clone()),
+                    }
+                }
+
+                Ok(Value::Table(out).tagged(tag))
+            }
+            Value::Row(o) => Ok(Value::Table(o.entries.values().cloned().collect()).tagged(tag)),
+            other => Err(ShellError::type_error(
+                "row or table",
+                other.type_name().spanned(self.span()),
+            )),
+        }
+    }
+
     pub fn as_column_path(&self) -> Result<Tagged<ColumnPath>, ShellError> {
         match &self.item {
             Value::Table(table) => {
@@ -378,7 +712,24 @@ impl Value {
         match self {
             Value::Row(o) => match &name.item {
                 RawPathMember::String(string) => o.get_mut_data_by_key(&string),
-                RawPathMember::Int(_) => None,
+                RawPathMember::ColumnNumber(number) => {
+                    let index = range_bound(number, o.entries.len())?;
+                    o.entries.get_index_mut(index).map(|(_, value)| value)
+                }
+                RawPathMember::Int(_)
+                | RawPathMember::SmallInt(_)
+                | RawPathMember::Entries
+                | RawPathMember::Wildcard
+                | RawPathMember::Glob(_)
+                | RawPathMember::Splat(_)
+                | RawPathMember::Computed(_)
+                | RawPathMember::Filter(_)
+                | RawPathMember::Spread(_)
+                | RawPathMember::Alternatives(_)
+                | RawPathMember::Range { .. }
+                | RawPathMember::ComputedRange(_)
+                | RawPathMember::Conditional(..)
+                | RawPathMember::Cast(..) => None,
             },
             Value::Table(l) => match &name.item {
                 RawPathMember::String(string) => {
@@ -396,12 +747,100 @@ impl Value {
                     }
                     None
                 }
+                RawPathMember::SmallInt(int) => l.get_mut(small_index(l.len(), *int)?),
+                RawPathMember::Int(int) if *int < BigInt::zero() => {
+                    let index = (BigInt::from(l.len()) + int).to_usize()?;
+                    l.get_mut(index)
+                }
                 RawPathMember::Int(int) => {
                     let index = int.to_usize()?;
                     l.get_mut(index)
                 }
+                RawPathMember::Entries
+                | RawPathMember::Wildcard
+                | RawPathMember::Glob(_)
+                | RawPathMember::ColumnNumber(_)
+                | RawPathMember::Splat(_)
+                | RawPathMember::Computed(_)
+                | RawPathMember::Filter(_)
+                | RawPathMember::Spread(_)
+                | RawPathMember::Alternatives(_)
+                | RawPathMember::Range { .. }
+                | RawPathMember::ComputedRange(_)
+                | RawPathMember::Conditional(..)
+                | RawPathMember::Cast(..) => None,
             },
             _ => None,
         }
     }
 }
+
+/// The position of index `int` in a table of `len` rows, counting back from the end if
+/// it's negative, or `None` if it's out of range
+fn small_index(len: usize, int: i64) -> Option<usize> {
+    let index = if int < 0 { len as i64 + int } else { int };
+
+    if index >= 0 && (index as usize) < len {
+        Some(index as usize)
+    } else {
+        None
+    }
+}
+
+/// The error for a member that has to be evaluated before it can be looked up, like a
+/// computed member in a column path passed to a command
+fn unevaluated_member(member: &PathMember) -> ShellError {
+    ShellError::labeled_error(
+        "Unevaluated member",
+        "this member can only be used in a variable path",
+        member.span,
+    )
+}
+
+/// The value of the column of `dict` at position `number`, counting back from the end if
+/// it's negative, or `None` if there's no column there
+fn nth_column(dict: &Dictionary, number: &BigInt) -> Option<Tagged<Value>> {
+    let index = range_bound(number, dict.entries.len())?;
+
+    dict.entries
+        .get_index(index)
+        .map(|(_, value)| value.clone())
+}
+
+/// A row of the columns of `dict` whose names match `pattern`, or `None` if none of them
+/// do
+fn select_matching_columns(
+    dict: &Dictionary,
+    pattern: &str,
+    tag: &Tag,
+    span: Span,
+) -> Result<Option<Tagged<Value>>, ShellError> {
+    let pattern = glob::Pattern::new(pattern)
+        .map_err(|err| ShellError::labeled_error("Invalid glob member", err.msg, span))?;
+
+    let mut row = TaggedDictBuilder::new(tag);
+    let mut matched = false;
+
+    for (key, value) in dict.entries.iter() {
+        if pattern.matches(key) {
+            row.insert_tagged(key, value.clone());
+            matched = true;
+        }
+    }
+
+    if matched {
+        Ok(Some(row.into_tagged_value()))
+    } else {
+        Ok(None)
+    }
+}
+
+/// The index of a range bound into a table of `len` rows. Negative bounds count back from
+/// the end of the table.
+fn range_bound(bound: &BigInt, len: usize) -> Option<usize> {
+    if *bound < BigInt::zero() {
+        (BigInt::from(len) + bound).to_usize()
+    } else {
+        bound.to_usize()
+    }
+}
@@ -43,6 +43,34 @@ pub enum ParseErrorReason {
         command: Spanned<String>,
         error: ArgumentError,
     },
+    TooDeep {
+        span: Span,
+    },
+    AmbiguousMarker {
+        span: Span,
+    },
+    /// Something that isn't a column where a column was expected, but that's close to one
+    /// of the known columns
+    ColumnMismatch {
+        actual: Spanned<String>,
+        suggestion: String,
+    },
+    /// Two dots in a row in a path, like `$x..y`
+    RepeatedDot {
+        previous: Span,
+        dot: Span,
+    },
+    /// An infix operator directly followed by its right side, like the `>` in `$a >$b`.
+    /// `span` is the character right after the operator.
+    InfixWithoutWhitespace {
+        operator: Span,
+        span: Span,
+    },
+    /// A duration with a unit smaller than a second, like `500ms`. Durations are whole
+    /// seconds.
+    SubSecondDuration {
+        span: Span,
+    },
 }
 
 #[derive(Debug, Clone)]
@@ -76,6 +104,49 @@ impl ParseError {
             },
         }
     }
+
+    pub fn too_deep(span: Span) -> ParseError {
+        ParseError {
+            reason: ParseErrorReason::TooDeep { span },
+        }
+    }
+
+    pub fn ambiguous_marker(span: Span) -> ParseError {
+        ParseError {
+            reason: ParseErrorReason::AmbiguousMarker { span },
+        }
+    }
+
+    pub fn column_mismatch(actual: Spanned<impl Into<String>>, suggestion: String) -> ParseError {
+        ParseError {
+            reason: ParseErrorReason::ColumnMismatch {
+                actual: actual.item.into().spanned(actual.span),
+                suggestion,
+            },
+        }
+    }
+
+    pub fn repeated_dot(previous: Span, dot: Span) -> ParseError {
+        ParseError {
+            reason: ParseErrorReason::RepeatedDot { previous, dot },
+        }
+    }
+
+    pub fn infix_without_whitespace(operator: Span, span: Span) -> ParseError {
+        ParseError {
+            reason: ParseErrorReason::InfixWithoutWhitespace { operator, span },
+        }
+    }
+
+    pub fn sub_second_duration(span: Span) -> ParseError {
+        ParseError {
+            reason: ParseErrorReason::SubSecondDuration { span },
+        }
+    }
+
+    pub fn reason(&self) -> &ParseErrorReason {
+        &self.reason
+    }
 }
 
 impl From<ParseError> for ShellError {
@@ -83,15 +154,103 @@ impl From<ParseError> for ShellError {
         match error.reason {
             ParseErrorReason::Eof { expected, span } => ShellError::unexpected_eof(expected, span),
             ParseErrorReason::Mismatch { actual, expected } => {
-                ShellError::type_error(expected, actual.clone())
+                ShellError::type_error(expected, actual)
             }
             ParseErrorReason::ArgumentError { command, error } => {
                 ShellError::argument_error(command, error)
             }
+            ParseErrorReason::TooDeep { span } => ShellError::labeled_error(
+                "Expression nested too deeply",
+                "expression nested too deeply",
+                span,
+            ),
+            ParseErrorReason::AmbiguousMarker { span } => ShellError::labeled_error(
+                "Ambiguous member marker",
+                "unclear whether this marks the member or its index; put it after the index, like `a[0]?`, or index with a dot, like `a?.0`",
+                span,
+            ),
+            ParseErrorReason::ColumnMismatch { actual, suggestion } => ShellError::labeled_error(
+                "Expected a column",
+                format!("expected a column, did you mean '{}'?", suggestion),
+                actual.span,
+            ),
+            ParseErrorReason::RepeatedDot { previous, dot } => {
+                ShellError::labeled_error_with_secondary(
+                    "Expected a column name",
+                    "expected a column name after '.', found another '.'",
+                    dot,
+                    "after this '.'",
+                    previous,
+                )
+            }
+            ParseErrorReason::InfixWithoutWhitespace { span, .. } => ShellError::labeled_error(
+                "Missing whitespace",
+                "infix operator must be followed by whitespace",
+                span,
+            ),
+            ParseErrorReason::SubSecondDuration { span } => ShellError::labeled_error(
+                "Sub-second durations are not supported",
+                "durations are whole seconds; use sec or a larger unit",
+                span,
+            ),
         }
     }
 }
 
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ParseWarningReason {
+    UnknownColumn {
+        name: Spanned<String>,
+        suggestion: Option<String>,
+    },
+    ReservedMember {
+        name: Spanned<String>,
+    },
+    /// A member written in a form that's on its way out, like `column number`
+    DeprecatedMember {
+        kind: Spanned<&'static str>,
+    },
+}
+
+/// A recoverable problem found while parsing. Warnings never fail the parse; they are
+/// collected on the `ExpandContext` for the caller to report.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ParseWarning {
+    reason: ParseWarningReason,
+}
+
+impl ParseWarning {
+    pub fn unknown_column(
+        name: Spanned<impl Into<String>>,
+        suggestion: Option<String>,
+    ) -> ParseWarning {
+        ParseWarning {
+            reason: ParseWarningReason::UnknownColumn {
+                name: name.item.into().spanned(name.span),
+                suggestion,
+            },
+        }
+    }
+
+    pub fn reserved_member(name: Spanned<impl Into<String>>) -> ParseWarning {
+        ParseWarning {
+            reason: ParseWarningReason::ReservedMember {
+                name: name.item.into().spanned(name.span),
+            },
+        }
+    }
+
+    pub fn deprecated_member(kind: Spanned<&'static str>) -> ParseWarning {
+        ParseWarning {
+            reason: ParseWarningReason::DeprecatedMember { kind },
+        }
+    }
+
+    pub fn reason(&self) -> &ParseWarningReason {
+        &self.reason
+    }
+}
+
 #[derive(Debug, Eq, PartialEq, Clone, Ord, Hash, PartialOrd, Serialize, Deserialize)]
 pub enum ArgumentError {
     MissingMandatoryFlag(String),
@@ -1,19 +1,28 @@
-use crate::data::base::Block;
+use crate::data::base::{Block, MAX_POW_BITS};
 use crate::errors::ArgumentError;
-use crate::parser::hir::path::{ColumnPath, RawPathMember};
+use crate::parser::hir::path::{ColumnPath, PathMember, RawPathMember};
+use crate::parser::hir::syntax_shape::TypeName;
 use crate::parser::{
     hir::{self, Expression, RawExpression},
-    CommandRegistry, Text,
+    CommandRegistry, Operator, Text,
 };
 use crate::prelude::*;
+use crate::SpannedTypeName;
 use crate::TaggedDictBuilder;
 use indexmap::IndexMap;
 use log::trace;
 use std::fmt;
 
+/// The most items a range in expression position, like `1..10`, is evaluated into. Ranges
+/// are materialized into a table, so a huge range would otherwise exhaust memory.
+pub const MAX_RANGE_LENGTH: usize = 1_000_000;
+
 pub struct Scope {
     it: Tagged<Value>,
     vars: IndexMap<String, Tagged<Value>>,
+    /// Whether a bare word is a column of `$it`, like the `a` in `$it.(a ?? b)`, rather
+    /// than a string
+    bare_columns: bool,
 }
 
 impl Scope {
@@ -21,6 +30,7 @@ impl Scope {
         Scope {
             it,
             vars: IndexMap::new(),
+            bare_columns: false,
         }
     }
 }
@@ -39,6 +49,7 @@ impl Scope {
         Scope {
             it: Value::nothing().tagged_unknown(),
             vars: IndexMap::new(),
+            bare_columns: false,
         }
     }
 
@@ -46,6 +57,17 @@ impl Scope {
         Scope {
             it: value,
             vars: IndexMap::new(),
+            bare_columns: false,
+        }
+    }
+
+    /// The scope of an expression inside a member, where `$it` is the value the member is
+    /// taken from, and bare words are its columns
+    fn member(&self, it: &Tagged<Value>) -> Scope {
+        Scope {
+            it: it.clone(),
+            vars: self.vars.clone(),
+            bare_columns: true,
         }
     }
 }
@@ -61,6 +83,9 @@ pub(crate) fn evaluate_baseline_expr(
         anchor: None,
     };
     match &expr.item {
+        RawExpression::Literal(hir::Literal::Bare) if scope.bare_columns => {
+            Ok(evaluate_bare_column(tag.slice(source), scope, tag))
+        }
         RawExpression::Literal(literal) => Ok(evaluate_literal(literal.tagged(tag), source)),
         RawExpression::ExternalWord => Err(ShellError::argument_error(
             "Invalid external word".spanned(tag.span),
@@ -72,19 +97,58 @@ pub(crate) fn evaluate_baseline_expr(
         }
         RawExpression::Variable(var) => evaluate_reference(var, scope, source, tag),
         RawExpression::Command(_) => evaluate_command(tag, scope, source),
+        RawExpression::Pipe(_) => Err(ShellError::syntax_error(
+            "Unexpected pipeline".spanned(tag.span),
+        )),
         RawExpression::ExternalCommand(external) => evaluate_external(external, scope, source),
         RawExpression::Binary(binary) => {
             let left = evaluate_baseline_expr(binary.left(), registry, scope, source)?;
-            let right = evaluate_baseline_expr(binary.right(), registry, scope, source)?;
 
-            trace!("left={:?} right={:?}", left.item, right.item);
+            match binary.op().item {
+                // `??` only evaluates its right side if the left side is nothing
+                Operator::Coalesce => match left.item {
+                    Value::Primitive(Primitive::Nothing) => {
+                        evaluate_baseline_expr(binary.right(), registry, scope, source)
+                    }
+                    _ => Ok(left),
+                },
+                // `&&` and `||` only evaluate their right side if the left side doesn't
+                // decide the result
+                Operator::And | Operator::Or => {
+                    let left_true = left.is_true();
+
+                    if left_true == (binary.op().item == Operator::Or) {
+                        return Ok(Value::boolean(left_true).tagged(tag));
+                    }
+
+                    let right = evaluate_baseline_expr(binary.right(), registry, scope, source)?;
 
-            match left.compare(binary.op(), &*right) {
-                Ok(result) => Ok(Value::boolean(result).tagged(tag)),
-                Err((left_type, right_type)) => Err(ShellError::coerce_error(
-                    left_type.spanned(binary.left().span),
-                    right_type.spanned(binary.right().span),
-                )),
+                    Ok(Value::boolean(right.is_true()).tagged(tag))
+                }
+                op => {
+                    let right = evaluate_baseline_expr(binary.right(), registry, scope, source)?;
+
+                    trace!("left={:?} right={:?}", left.item, right.item);
+
+                    evaluate_binary(binary, op, &left, &right, tag)
+                }
+            }
+        }
+        RawExpression::Unary(unary) => {
+            let value = evaluate_baseline_expr(unary.expr(), registry, scope, source)?;
+
+            if unary.op().item == Operator::Not {
+                return match value.item {
+                    Value::Primitive(Primitive::Boolean(boolean)) => {
+                        Ok(Value::boolean(!boolean).tagged(tag))
+                    }
+                    _ => Err(ShellError::type_error("boolean", value.spanned_type_name())),
+                };
+            }
+
+            match value.negate() {
+                Some(result) => Ok(result.tagged(tag)),
+                None => Err(ShellError::type_error("number", value.spanned_type_name())),
             }
         }
         RawExpression::List(list) => {
@@ -104,10 +168,14 @@ pub(crate) fn evaluate_baseline_expr(
             let value = evaluate_baseline_expr(path.head(), registry, scope, source)?;
             let mut item = value;
 
-            for member in path.tail() {
-                let next = item.get_data_by_member(member);
+            for (index, member) in path.tail().iter().enumerate() {
+                let next = evaluate_member(&item, member, registry, scope, source);
 
                 match next {
+                    // a missing member marked with `?` skips the rest of the path
+                    Err(_) if path.short_circuits_at(index) => {
+                        return Ok(Value::nothing().tagged(tag))
+                    }
                     Err(err) => {
                         let possibilities = item.data_descriptors();
 
@@ -129,6 +197,8 @@ pub(crate) fn evaluate_baseline_expr(
                                 return Err(err);
                             }
                         }
+
+                        return Err(err);
                     }
                     Ok(next) => {
                         item = next.clone().item.tagged(&tag);
@@ -138,10 +208,404 @@ pub(crate) fn evaluate_baseline_expr(
 
             Ok(item.item().clone().tagged(tag))
         }
+        RawExpression::Range(range) => {
+            // a range without a lower bound starts at zero
+            let from = match range.from() {
+                Some(from) => evaluate_range_bound(from, registry, scope, source)?,
+                None => BigInt::from(0),
+            };
+
+            // an open-ended range only makes sense as a member, where the end is known
+            let to = match range.to() {
+                Some(to) => evaluate_range_bound(to, registry, scope, source)?,
+                None => {
+                    return Err(ShellError::labeled_error(
+                        "Unbounded range",
+                        "this range has no end",
+                        tag.span,
+                    ))
+                }
+            };
+
+            let length = if *range.inclusive() {
+                &to - &from + BigInt::from(1)
+            } else {
+                &to - &from
+            };
+
+            if length > BigInt::from(MAX_RANGE_LENGTH) {
+                return Err(ShellError::labeled_error(
+                    "Range too long",
+                    format!("this range has more than {} items", MAX_RANGE_LENGTH),
+                    tag.span,
+                ));
+            }
+
+            let mut current = from;
+            let mut items = vec![];
+
+            while current < to || (*range.inclusive() && current == to) {
+                items.push(Value::int(current.clone()).tagged(&tag));
+                current = current + BigInt::from(1);
+            }
+
+            Ok(Value::Table(items).tagged(tag))
+        }
+        RawExpression::TypeTest(test) => {
+            let value = evaluate_baseline_expr(test.expr(), registry, scope, source)?;
+
+            Ok(Value::boolean(test.type_name().matches(&value.item)).tagged(tag))
+        }
+        RawExpression::Interpolation(parts) => {
+            let mut output = String::new();
+
+            for part in parts {
+                let value = evaluate_baseline_expr(part, registry, scope, source)?;
+                output.push_str(&value.as_string()?);
+            }
+
+            Ok(Value::string(output).tagged(tag))
+        }
         RawExpression::Boolean(_boolean) => unimplemented!(),
     }
 }
 
+/// The value of `member` in `item`. A member with an expression in it, like the `(a ?? b)`
+/// in `$it.(a ?? b)`, is evaluated against `item`.
+fn evaluate_member(
+    item: &Tagged<Value>,
+    member: &PathMember,
+    registry: &CommandRegistry,
+    scope: &Scope,
+    source: &Text,
+) -> Result<Tagged<Value>, ShellError> {
+    match &member.item {
+        RawPathMember::Computed(expr) => {
+            evaluate_baseline_expr(expr, registry, &scope.member(item), source)
+        }
+        RawPathMember::Filter(predicate) => {
+            let rows = match &item.item {
+                Value::Table(rows) => rows,
+                other => {
+                    return Err(ShellError::type_error(
+                        "table",
+                        other.type_name().spanned(item.tag.span),
+                    ))
+                }
+            };
+
+            let mut out = vec![];
+
+            for row in rows {
+                let keep = evaluate_baseline_expr(predicate, registry, &scope.member(row), source)?;
+
+                if keep.is_true() {
+                    out.push(row.clone());
+                }
+            }
+
+            Ok(Value::Table(out).tagged(Tag::new(item.anchor(), member.span)))
+        }
+        RawPathMember::Spread(expr) => {
+            let spread = evaluate_baseline_expr(expr, registry, &scope.member(item), source)?;
+
+            match &item.item {
+                Value::Table(rows) => {
+                    let mut out = vec![];
+
+                    for row in rows {
+                        out.push(spread_row(row, &spread, member.span)?);
+                    }
+
+                    Ok(Value::Table(out).tagged(Tag::new(item.anchor(), member.span)))
+                }
+                _ => spread_row(item, &spread, member.span),
+            }
+        }
+        RawPathMember::Alternatives(exprs) => {
+            let scope = scope.member(item);
+
+            for expr in exprs {
+                let value = evaluate_baseline_expr(expr, registry, &scope, source)?;
+
+                match value.item {
+                    Value::Primitive(Primitive::Nothing) => continue,
+                    _ => return Ok(value),
+                }
+            }
+
+            Err(ShellError::labeled_error(
+                "No matching alternative",
+                "none of these alternatives has a value",
+                member.span,
+            ))
+        }
+        RawPathMember::Splat(inner) => {
+            evaluate_member(item, inner, registry, scope, source)?.flatten(member.span)
+        }
+        RawPathMember::Conditional(inner, predicate) => {
+            let holds = evaluate_baseline_expr(predicate, registry, &scope.member(item), source)?;
+
+            if holds.is_true() {
+                evaluate_member(item, inner, registry, scope, source)
+            } else {
+                Ok(Value::nothing().tagged(Tag::new(item.anchor(), member.span)))
+            }
+        }
+        RawPathMember::Cast(inner, type_name) => {
+            let value = evaluate_member(item, inner, registry, scope, source)?;
+
+            cast(value, *type_name, member.span)
+        }
+        RawPathMember::ComputedRange(range) => {
+            // the bounds come from the surrounding scope, not from the item being sliced
+            let from = match range.from() {
+                Some(from) => evaluate_range_bound(from, registry, scope, source)?,
+                None => BigInt::from(0),
+            };
+
+            let to = match range.to() {
+                Some(to) => Some(evaluate_range_bound(to, registry, scope, source)?),
+                None => None,
+            };
+
+            item.get_data_by_member(&PathMember::range(
+                from,
+                to,
+                *range.inclusive(),
+                member.span,
+            ))
+        }
+        _ => item.get_data_by_member(member),
+    }
+}
+
+/// `value` converted to `type_name`, like the `age` in `$it.age as int`. A value that
+/// already has the type is left alone.
+fn cast(
+    value: Tagged<Value>,
+    type_name: TypeName,
+    span: Span,
+) -> Result<Tagged<Value>, ShellError> {
+    if type_name.matches(&value.item) {
+        return Ok(value);
+    }
+
+    let cast = match (type_name, &value.item) {
+        (TypeName::Int, Value::Primitive(Primitive::Decimal(decimal))) => {
+            decimal.to_i64().map(Value::int)
+        }
+        (TypeName::Int, Value::Primitive(Primitive::Bytes(bytes))) => Some(Value::int(*bytes)),
+        (TypeName::Int, Value::Primitive(Primitive::String(s))) => {
+            s.trim().parse::<BigInt>().ok().map(Value::int)
+        }
+        (TypeName::Decimal, Value::Primitive(Primitive::Int(int))) => {
+            Some(Value::decimal(BigDecimal::new(int.clone(), 0)))
+        }
+        (TypeName::Decimal, Value::Primitive(Primitive::String(s))) => {
+            s.trim().parse::<BigDecimal>().ok().map(Value::decimal)
+        }
+        (TypeName::String, _) => value.as_string().ok().map(Value::string),
+        (TypeName::Bool, Value::Primitive(Primitive::String(s))) => match s.trim() {
+            "true" => Some(Value::boolean(true)),
+            "false" => Some(Value::boolean(false)),
+            _ => None,
+        },
+        (TypeName::Date, Value::Primitive(Primitive::String(s))) => {
+            Value::date_from_str(s.trim().tagged(&value.tag)).ok()
+        }
+        (TypeName::Path, Value::Primitive(Primitive::String(s))) => Some(Value::path(s)),
+        (TypeName::Bytes, Value::Primitive(Primitive::Int(int))) => int.to_u64().map(Value::bytes),
+        _ => None,
+    };
+
+    match cast {
+        Some(cast) => Ok(cast.tagged(value.tag)),
+        None => Err(ShellError::labeled_error(
+            "Invalid cast",
+            format!(
+                "can't convert {} to {}",
+                value.item.type_name(),
+                type_name.as_str()
+            ),
+            span,
+        )),
+    }
+}
+
+/// The integer value of one end of a range
+fn evaluate_range_bound(
+    bound: &Expression,
+    registry: &CommandRegistry,
+    scope: &Scope,
+    source: &Text,
+) -> Result<BigInt, ShellError> {
+    let value = evaluate_baseline_expr(bound, registry, scope, source)?;
+
+    match &value.item {
+        Value::Primitive(Primitive::Int(int)) => Ok(int.clone()),
+        _ => Err(ShellError::type_error("integer", value.spanned_type_name())),
+    }
+}
+
+/// `row` with the columns of `spread` that it doesn't have yet, like the result of
+/// `$it.(...$defaults)`
+fn spread_row(
+    row: &Tagged<Value>,
+    spread: &Tagged<Value>,
+    span: Span,
+) -> Result<Tagged<Value>, ShellError> {
+    let (row_entries, spread_entries) = match (&row.item, &spread.item) {
+        (Value::Row(row), Value::Row(spread)) => (&row.entries, &spread.entries),
+        (Value::Row(_), _) => {
+            return Err(ShellError::type_error("row", spread.spanned_type_name()))
+        }
+        _ => return Err(ShellError::type_error("row", row.spanned_type_name())),
+    };
+
+    let mut out = TaggedDictBuilder::new(Tag::new(row.anchor(), span));
+
+    for (key, value) in row_entries {
+        out.insert_tagged(key, value.clone());
+    }
+
+    for (key, value) in spread_entries {
+        if !row_entries.contains_key(key) {
+            out.insert_tagged(key, value.clone());
+        }
+    }
+
+    Ok(out.into_tagged_value())
+}
+
+/// The value of a bare word inside a member, like the `a` in `$it.(a ?? b)` or the
+/// `left.name` in `$it.(left.name | right.name)`, which is a path of columns of `$it`.
+/// A missing column is nothing, so that it can fall back to something else.
+fn evaluate_bare_column(text: &str, scope: &Scope, tag: Tag) -> Tagged<Value> {
+    let mut current = scope.it.clone();
+
+    for column in text.split('.').filter(|column| !column.is_empty()) {
+        match current.get_data_by_member(&PathMember::string(column, tag.span)) {
+            Ok(next) => current = next,
+            Err(_) => return Value::nothing().tagged(tag),
+        }
+    }
+
+    current.item.tagged(tag)
+}
+
+/// Apply a binary operator whose operands are both evaluated, like the `+` in `1 + 2`
+fn evaluate_binary(
+    binary: &hir::Binary,
+    op: Operator,
+    left: &Tagged<Value>,
+    right: &Tagged<Value>,
+    tag: Tag,
+) -> Result<Tagged<Value>, ShellError> {
+    let coerce_error = |(left_type, right_type): (&'static str, &'static str)| {
+        ShellError::coerce_error(
+            left_type.spanned(binary.left().span),
+            right_type.spanned(binary.right().span),
+        )
+    };
+
+    match op {
+        Operator::In | Operator::NotIn => {
+            let contained = match &right.item {
+                Value::Table(items) => items
+                    .iter()
+                    .any(|item| left.compare(&Operator::Equal, &item.item).unwrap_or(false)),
+                other => {
+                    return Err(ShellError::type_error(
+                        "table",
+                        other.type_name().spanned(binary.right().span),
+                    ))
+                }
+            };
+
+            Ok(Value::boolean(contained == (op == Operator::In)).tagged(tag))
+        }
+        Operator::RegexMatch | Operator::NotRegexMatch => {
+            evaluate_regex_match(binary, left, right, tag)
+        }
+        Operator::Plus | Operator::Minus | Operator::Multiply => left
+            .arithmetic(binary.op(), right)
+            .map(|result| result.tagged(tag))
+            .map_err(coerce_error),
+        Operator::Append => left
+            .append(right)
+            .map(|result| result.tagged(tag))
+            .map_err(coerce_error),
+        Operator::Compare => left
+            .compare_ordering(right)
+            .map(|result| result.tagged(tag))
+            .map_err(coerce_error),
+        Operator::Pow => match left.pow(right).map_err(coerce_error)? {
+            Some(result) => Ok(result.tagged(tag)),
+            None => Err(ShellError::labeled_error(
+                "Invalid exponent",
+                format!(
+                    "expected a non-negative integer, with a result of at most {} bits",
+                    MAX_POW_BITS
+                ),
+                binary.right().span,
+            )),
+        },
+        Operator::Mod => match left.modulo(right).map_err(coerce_error)? {
+            Some(result) => Ok(result.tagged(tag)),
+            None => Err(ShellError::labeled_error(
+                "Modulo by zero",
+                "this is zero",
+                binary.right().span,
+            )),
+        },
+        _ => left
+            .compare(binary.op(), right)
+            .map(|result| Value::boolean(result).tagged(tag))
+            .map_err(coerce_error),
+    }
+}
+
+fn evaluate_regex_match(
+    binary: &hir::Binary,
+    left: &Tagged<Value>,
+    right: &Tagged<Value>,
+    tag: Tag,
+) -> Result<Tagged<Value>, ShellError> {
+    let (text, pattern) = match (&left.item, &right.item) {
+        (
+            Value::Primitive(Primitive::String(text)),
+            Value::Primitive(Primitive::String(pattern)),
+        ) => (text, pattern),
+        _ => {
+            return Err(ShellError::coerce_error(
+                left.item.type_name().spanned(binary.left().span),
+                right.item.type_name().spanned(binary.right().span),
+            ))
+        }
+    };
+
+    let regex = match regex::Regex::new(pattern) {
+        Ok(regex) => regex,
+        Err(err) => {
+            return Err(ShellError::labeled_error(
+                "Invalid regular expression",
+                err.to_string(),
+                binary.right().span,
+            ))
+        }
+    };
+
+    let matched = regex.is_match(text);
+    let result = if binary.op().item == Operator::RegexMatch {
+        matched
+    } else {
+        !matched
+    };
+
+    Ok(Value::boolean(result).tagged(tag))
+}
+
 fn evaluate_literal(literal: Tagged<&hir::Literal>, source: &Text) -> Tagged<Value> {
     let result = match literal.item {
         hir::Literal::ColumnPath(path) => {
@@ -157,6 +621,7 @@ fn evaluate_literal(literal: Tagged<&hir::Literal>, source: &Text) -> Tagged<Val
         hir::Literal::String(tag) => Value::string(tag.slice(source)),
         hir::Literal::GlobPattern(pattern) => Value::pattern(pattern),
         hir::Literal::Bare => Value::string(literal.tag().slice(source)),
+        hir::Literal::Nothing => Value::nothing(),
     };
 
     literal.map(|_| result)
@@ -171,6 +636,25 @@ fn evaluate_reference(
     trace!("Evaluating {} with Scope {}", name, scope);
     match name {
         hir::Variable::It(_) => Ok(scope.it.item.clone().tagged(tag)),
+        hir::Variable::Env(_) => Ok(env_vars(&tag)),
+        hir::Variable::Nu(_) => {
+            let mut dict = TaggedDictBuilder::new(&tag);
+            dict.insert_tagged("env", env_vars(&tag));
+
+            let config = crate::data::config::read(tag.clone(), &None)?;
+            dict.insert("config", Value::row(config));
+            dict.insert_tagged("path", path_table(&tag));
+
+            if let Some(home) = dirs::home_dir() {
+                dict.insert("home-dir", Value::path(home));
+            }
+
+            if let Ok(cwd) = std::env::current_dir() {
+                dict.insert("cwd", Value::path(cwd));
+            }
+
+            Ok(dict.into_tagged_value())
+        }
         hir::Variable::Other(inner) => match inner.slice(source) {
             x if x == "nu:env" => {
                 let mut dict = TaggedDictBuilder::new(&tag);
@@ -185,18 +669,7 @@ fn evaluate_reference(
                 let config = crate::data::config::read(tag.clone(), &None)?;
                 Ok(Value::row(config).tagged(tag))
             }
-            x if x == "nu:path" => {
-                let mut table = vec![];
-                match std::env::var_os("PATH") {
-                    Some(paths) => {
-                        for path in std::env::split_paths(&paths) {
-                            table.push(Value::path(path).tagged(&tag));
-                        }
-                    }
-                    _ => {}
-                }
-                Ok(Value::table(&table).tagged(tag))
-            }
+            x if x == "nu:path" => Ok(path_table(&tag)),
             x => Ok(scope
                 .vars
                 .get(x)
@@ -206,6 +679,29 @@ fn evaluate_reference(
     }
 }
 
+/// Every environment variable, as a row
+fn env_vars(tag: &Tag) -> Tagged<Value> {
+    let mut dict = TaggedDictBuilder::new(tag);
+    for (key, value) in std::env::vars() {
+        dict.insert(key, Value::string(value));
+    }
+    dict.into_tagged_value()
+}
+
+/// The directories in `PATH`, as a table of paths
+fn path_table(tag: &Tag) -> Tagged<Value> {
+    let mut table = vec![];
+    match std::env::var_os("PATH") {
+        Some(paths) => {
+            for path in std::env::split_paths(&paths) {
+                table.push(Value::path(path).tagged(tag));
+            }
+        }
+        _ => {}
+    }
+    Value::table(&table).tagged(tag)
+}
+
 fn evaluate_external(
     external: &hir::ExternalCommand,
     _scope: &Scope,
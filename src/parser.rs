@@ -1,3 +1,5 @@
+#[doc(hidden)]
+pub mod bench;
 pub(crate) mod debug;
 pub(crate) mod deserializer;
 pub(crate) mod hir;
@@ -13,7 +15,7 @@ pub(crate) use hir::TokensIterator;
 pub(crate) use parse::call_node::CallNode;
 pub(crate) use parse::files::Files;
 pub(crate) use parse::flag::{Flag, FlagKind};
-pub(crate) use parse::operator::Operator;
+pub(crate) use parse::operator::{Associativity, Operator};
 pub(crate) use parse::parser::{nom_input, pipeline};
 pub(crate) use parse::text::Text;
 pub(crate) use parse::token_tree::{DelimitedNode, Delimiter, TokenNode};
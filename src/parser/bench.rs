@@ -0,0 +1,77 @@
+//! Hooks for the benchmarks in `benches/`, which can't reach the parser's internals
+//! otherwise. This isn't a stable API.
+
+use crate::parser::hir::path::MemberInterner;
+use crate::parser::hir::syntax_shape::{
+    expand_syntax, ColumnPathShape, ExpandContext, PathTailShape, WhitespaceShape,
+};
+use crate::parser::parse::parser::{nom_input, token_list};
+use crate::parser::{CommandRegistry, Text, TokenNode, TokensIterator};
+use crate::prelude::*;
+
+/// Source that has already been lexed, so that a benchmark only measures expansion
+pub struct LexedSource {
+    source: Text,
+    tokens: Spanned<Vec<TokenNode>>,
+    registry: CommandRegistry,
+}
+
+impl LexedSource {
+    pub fn new(source: &str) -> LexedSource {
+        let (_, tokens) = token_list(nom_input(source)).expect("benchmark source should lex");
+
+        LexedSource {
+            source: Text::from(source),
+            tokens,
+            registry: CommandRegistry::new(),
+        }
+    }
+
+    /// Expand the source's whitespace-separated column paths, like `a.b c.d`, returning
+    /// the number of members. With `into_vecs`, each path's members are moved into a
+    /// `Vec`, which is how they were stored before they were kept inline.
+    pub fn expand_column_paths(&self, into_vecs: bool) -> usize {
+        let context = ExpandContext::new(&self.registry, &self.source, None);
+        let mut tokens = TokensIterator::all(&self.tokens.item, self.tokens.span);
+        let mut members = 0;
+
+        while !tokens.at_end_possible_ws() {
+            let path = expand_syntax(&ColumnPathShape, &mut tokens, &context)
+                .expect("benchmark source should be column paths");
+
+            members += if into_vecs {
+                path.item.into_vec().len()
+            } else {
+                path.item.len()
+            };
+
+            let _ = expand_syntax(&WhitespaceShape, &mut tokens, &context);
+        }
+
+        members
+    }
+
+    /// Expand the source's whitespace-separated path tails, like `.a.b .c.d`, returning
+    /// the number of members. Repeated member names share storage if there's an
+    /// `interner`.
+    pub fn expand_path_tails(&self, interner: Option<&MemberInterner>) -> usize {
+        let context = ExpandContext::new(&self.registry, &self.source, None);
+        let context = match interner {
+            Some(interner) => context.with_interner(interner),
+            None => context,
+        };
+        let mut tokens = TokensIterator::all(&self.tokens.item, self.tokens.span);
+        let mut members = 0;
+
+        while !tokens.at_end_possible_ws() {
+            let tail = expand_syntax(&PathTailShape, &mut tokens, &context)
+                .expect("benchmark source should be path tails");
+
+            members += tail.item.len();
+
+            let _ = expand_syntax(&WhitespaceShape, &mut tokens, &context);
+        }
+
+        members
+    }
+}
@@ -4,12 +4,17 @@ pub(crate) mod expand_external_tokens;
 pub(crate) mod external_command;
 pub(crate) mod named;
 pub(crate) mod path;
+pub(crate) mod pipe;
+pub(crate) mod range;
 pub(crate) mod syntax_shape;
 pub(crate) mod tokens_iterator;
+pub(crate) mod type_test;
+pub(crate) mod unary;
 
-use crate::parser::hir::path::PathMember;
-use crate::parser::hir::syntax_shape::Member;
-use crate::parser::{registry, Operator, Unit};
+use crate::commands::classified::ClassifiedPipeline;
+use crate::parser::hir::path::{MemberMarker, PathMember};
+use crate::parser::hir::syntax_shape::{Member, TypeName};
+use crate::parser::{registry, Associativity, Operator, Unit};
 use crate::prelude::*;
 use derive_new::new;
 use getset::Getters;
@@ -25,8 +30,12 @@ pub(crate) use self::binary::Binary;
 pub(crate) use self::external_command::ExternalCommand;
 pub(crate) use self::named::NamedArguments;
 pub(crate) use self::path::Path;
+pub(crate) use self::pipe::Pipe;
+pub(crate) use self::range::Range;
 pub(crate) use self::syntax_shape::ExpandContext;
 pub(crate) use self::tokens_iterator::TokensIterator;
+pub(crate) use self::type_test::TypeTest;
+pub(crate) use self::unary::Unary;
 
 pub use self::syntax_shape::SyntaxShape;
 
@@ -81,9 +90,15 @@ pub enum RawExpression {
     Synthetic(Synthetic),
     Variable(Variable),
     Binary(Box<Binary>),
+    Unary(Box<Unary>),
     Block(Vec<Expression>),
     List(Vec<Expression>),
     Path(Box<Path>),
+    Pipe(Box<Pipe>),
+    Range(Box<Range>),
+    TypeTest(Box<TypeTest>),
+    /// The parts of an interpolated string, which are concatenated as strings
+    Interpolation(Vec<Expression>),
 
     FilePath(PathBuf),
     ExternalCommand(ExternalCommand),
@@ -116,8 +131,13 @@ impl RawExpression {
             RawExpression::Variable(..) => "variable",
             RawExpression::List(..) => "list",
             RawExpression::Binary(..) => "binary",
+            RawExpression::Unary(..) => "unary",
             RawExpression::Block(..) => "block",
             RawExpression::Path(..) => "variable path",
+            RawExpression::Pipe(..) => "pipeline",
+            RawExpression::Range(..) => "range",
+            RawExpression::TypeTest(..) => "type test",
+            RawExpression::Interpolation(..) => "string interpolation",
             RawExpression::Boolean(..) => "boolean",
             RawExpression::ExternalCommand(..) => "external",
         }
@@ -144,6 +164,7 @@ impl std::fmt::Display for Expression {
                 .entries(list.iter().map(|e| format!("{}", e)))
                 .finish(),
             RawExpression::Binary(binary) => write!(f, "{}", binary),
+            RawExpression::Unary(unary) => write!(f, "{}", unary),
             RawExpression::Block(items) => {
                 write!(f, "Block")?;
                 f.debug_set()
@@ -151,6 +172,15 @@ impl std::fmt::Display for Expression {
                     .finish()
             }
             RawExpression::Path(path) => write!(f, "{}", path),
+            RawExpression::Pipe(pipe) => write!(f, "{}", pipe),
+            RawExpression::Range(range) => write!(f, "{}", range),
+            RawExpression::TypeTest(test) => write!(f, "{}", test),
+            RawExpression::Interpolation(parts) => {
+                write!(f, "Interpolation")?;
+                f.debug_list()
+                    .entries(parts.iter().map(|p| format!("{}", p)))
+                    .finish()
+            }
             RawExpression::Boolean(b) => write!(f, "${}", b),
             RawExpression::ExternalCommand(..) => {
                 write!(f, "ExternalComment{{ {}..{} }}", span.start(), span.end())
@@ -193,7 +223,41 @@ impl Expression {
         RawExpression::Path(Box::new(Path::new(head, tail))).spanned(span.into())
     }
 
+    pub(crate) fn lenient_path(
+        head: Expression,
+        tail: Vec<impl Into<PathMember>>,
+        span: impl Into<Span>,
+    ) -> Expression {
+        let tail = tail.into_iter().map(|t| t.into()).collect();
+        RawExpression::Path(Box::new(Path::new(head, tail).into_lenient())).spanned(span.into())
+    }
+
+    /// Apply `f` to the path, if this is a path
+    pub(crate) fn map_path(self, f: impl FnOnce(Path) -> Path) -> Expression {
+        let Spanned { item, span } = self;
+
+        match item {
+            RawExpression::Path(path) => RawExpression::Path(Box::new(f(*path))).spanned(span),
+            other => other.spanned(span),
+        }
+    }
+
+    /// Attach a marker to each member of the tail, if this is a path
+    pub(crate) fn with_markers(self, markers: Vec<Option<MemberMarker>>) -> Expression {
+        self.map_path(|path| path.with_markers(markers))
+    }
+
     pub(crate) fn dot_member(head: Expression, next: impl Into<PathMember>) -> Expression {
+        Expression::marked_dot_member(head, next, None)
+    }
+
+    /// Like `dot_member`, but the new member can have a marker, like the `?` in
+    /// `$record.name?`. The markers of the members already in the path are kept.
+    pub(crate) fn marked_dot_member(
+        head: Expression,
+        next: impl Into<PathMember>,
+        marker: Option<MemberMarker>,
+    ) -> Expression {
         let Spanned { item, span } = head;
         let next = next.into();
 
@@ -201,13 +265,11 @@ impl Expression {
 
         match item {
             RawExpression::Path(path) => {
-                let (head, mut tail) = path.parts();
-
-                tail.push(next);
-                Expression::path(head, tail, new_span)
+                RawExpression::Path(Box::new(path.push_member(next, marker))).spanned(new_span)
             }
 
-            other => Expression::path(other.spanned(span), vec![next], new_span),
+            other => Expression::path(other.spanned(span), vec![next], new_span)
+                .with_markers(vec![marker]),
         }
     }
 
@@ -222,6 +284,89 @@ impl Expression {
             .spanned(new_span)
     }
 
+    pub(crate) fn unary(op: Spanned<impl Into<Operator>>, expr: Expression) -> Expression {
+        let new_span = op.span.until(expr.span);
+
+        RawExpression::Unary(Box::new(Unary::new(op.map(|o| o.into()), expr))).spanned(new_span)
+    }
+
+    /// An expression written in the parentheses at `span`, like the `(1 + 2)` in
+    /// `not (1 + 2) * 3`
+    pub(crate) fn parenthesized(expr: Expression, span: Span) -> Expression {
+        match expr.item {
+            RawExpression::Binary(binary) => {
+                RawExpression::Binary(Box::new((*binary).into_parenthesized())).spanned(span)
+            }
+            other => other.spanned(span),
+        }
+    }
+
+    /// Add `op` and `right` to the end of a chain of infix operators. If `left` is an
+    /// infix expression whose operator binds more loosely than `op`, or just as tightly
+    /// when `op` is right-associative, `right` is folded into its right side instead.
+    /// Only infix expressions built by the chain itself are folded into; a closed one,
+    /// like a parenthesized expression, is always kept whole.
+    pub(crate) fn infix_by_precedence(
+        left: Expression,
+        op: Spanned<Operator>,
+        right: Expression,
+    ) -> Expression {
+        if let RawExpression::Binary(binary) = &left.item {
+            if *binary.parenthesized() {
+                return Expression::infix(left, op, right);
+            }
+
+            let left_precedence = binary.op().precedence();
+            let folds_right = left_precedence < op.precedence()
+                || (left_precedence == op.precedence()
+                    && op.associativity() == Associativity::Right);
+
+            if folds_right {
+                let right = Expression::infix_by_precedence(binary.right().clone(), op, right);
+                return Expression::infix(binary.left().clone(), *binary.op(), right);
+            }
+        }
+
+        Expression::infix(left, op, right)
+    }
+
+    pub(crate) fn range(
+        from: Expression,
+        dotdot: Span,
+        to: Expression,
+        span: impl Into<Span>,
+    ) -> Expression {
+        Expression::range_with_bounds(Some(from), dotdot, Some(to), false, span)
+    }
+
+    /// A range that may be open-ended, like `2..` or `..5`, or include its upper bound,
+    /// like `2..=5`
+    pub(crate) fn range_with_bounds(
+        from: Option<Expression>,
+        dotdot: Span,
+        to: Option<Expression>,
+        inclusive: bool,
+        span: impl Into<Span>,
+    ) -> Expression {
+        RawExpression::Range(Box::new(Range::new(from, dotdot, to, inclusive))).spanned(span.into())
+    }
+
+    pub(crate) fn pipe(head: Expression, pipe: Span, pipeline: ClassifiedPipeline) -> Expression {
+        let span = head.span.until(pipeline.span());
+
+        RawExpression::Pipe(Box::new(Pipe::new(head, pipe, pipeline))).spanned(span)
+    }
+
+    pub(crate) fn type_test(expr: Expression, type_name: Spanned<TypeName>) -> Expression {
+        let span = expr.span.until(type_name.span);
+
+        RawExpression::TypeTest(Box::new(TypeTest::new(expr, type_name))).spanned(span)
+    }
+
+    pub(crate) fn interpolation(parts: Vec<Expression>, span: impl Into<Span>) -> Expression {
+        RawExpression::Interpolation(parts).spanned(span)
+    }
+
     pub(crate) fn file_path(path: impl Into<PathBuf>, outer: impl Into<Span>) -> Expression {
         RawExpression::FilePath(path.into()).spanned(outer)
     }
@@ -234,6 +379,10 @@ impl Expression {
         RawExpression::Literal(Literal::Bare).spanned(span)
     }
 
+    pub(crate) fn nothing(span: impl Into<Span>) -> Expression {
+        RawExpression::Literal(Literal::Nothing).spanned(span)
+    }
+
     pub(crate) fn pattern(inner: impl Into<String>, outer: impl Into<Span>) -> Expression {
         RawExpression::Literal(Literal::GlobPattern(inner.into())).spanned(outer.into())
     }
@@ -249,6 +398,76 @@ impl Expression {
     pub(crate) fn it_variable(inner: impl Into<Span>, outer: impl Into<Span>) -> Expression {
         RawExpression::Variable(Variable::It(inner.into())).spanned(outer)
     }
+
+    pub(crate) fn nu_variable(inner: impl Into<Span>, outer: impl Into<Span>) -> Expression {
+        RawExpression::Variable(Variable::Nu(inner.into())).spanned(outer)
+    }
+
+    pub(crate) fn env_variable(inner: impl Into<Span>, outer: impl Into<Span>) -> Expression {
+        RawExpression::Variable(Variable::Env(inner.into())).spanned(outer)
+    }
+
+    /// Every infix operator used in the expression, in source order, including the ones
+    /// nested inside computed members and other subexpressions
+    pub fn operators_used(&self) -> Vec<Operator> {
+        let mut operators = vec![];
+        self.collect_operators(&mut operators);
+        operators
+    }
+
+    fn collect_operators(&self, operators: &mut Vec<Operator>) {
+        match &self.item {
+            RawExpression::Binary(binary) => {
+                binary.left().collect_operators(operators);
+                operators.push(binary.op().item);
+                binary.right().collect_operators(operators);
+            }
+            RawExpression::Block(items)
+            | RawExpression::List(items)
+            | RawExpression::Interpolation(items) => {
+                for item in items {
+                    item.collect_operators(operators);
+                }
+            }
+            RawExpression::Path(path) => {
+                path.head().collect_operators(operators);
+
+                for member in path.tail() {
+                    for expr in member.item.expressions() {
+                        expr.collect_operators(operators);
+                    }
+                }
+            }
+            // the pipeline's commands aren't expressions, so only the head is searched
+            RawExpression::Pipe(pipe) => pipe.head().collect_operators(operators),
+            RawExpression::Range(range) => {
+                if let Some(from) = range.from() {
+                    from.collect_operators(operators);
+                }
+
+                if let Some(to) = range.to() {
+                    to.collect_operators(operators);
+                }
+            }
+            RawExpression::TypeTest(test) => test.expr().collect_operators(operators),
+            RawExpression::Unary(unary) => unary.expr().collect_operators(operators),
+            RawExpression::Literal(Literal::ColumnPath(members)) => {
+                for member in members {
+                    for expr in member.expressions() {
+                        expr.collect_operators(operators);
+                    }
+                }
+            }
+            RawExpression::Literal(..)
+            | RawExpression::ExternalWord
+            | RawExpression::Synthetic(..)
+            | RawExpression::Variable(..)
+            | RawExpression::FilePath(..)
+            | RawExpression::ExternalCommand(..)
+            | RawExpression::Command(..)
+            | RawExpression::Boolean(..) => {}
+        }
+    }
 }
 
 impl FormatDebug for Spanned<RawExpression> {
@@ -260,8 +479,11 @@ impl FormatDebug for Spanned<RawExpression> {
             RawExpression::Command(tag) => write!(f, "{}", tag.slice(source)),
             RawExpression::Synthetic(Synthetic::String(s)) => write!(f, "{:?}", s),
             RawExpression::Variable(Variable::It(_)) => write!(f, "$it"),
+            RawExpression::Variable(Variable::Nu(_)) => write!(f, "$nu"),
+            RawExpression::Variable(Variable::Env(_)) => write!(f, "$env"),
             RawExpression::Variable(Variable::Other(s)) => write!(f, "${}", s.slice(source)),
             RawExpression::Binary(b) => write!(f, "{}", b.debug(source)),
+            RawExpression::Unary(u) => write!(f, "{}", u.debug(source)),
             RawExpression::ExternalCommand(c) => write!(f, "^{}", c.name().slice(source)),
             RawExpression::Block(exprs) => f.say_block("block", |f| {
                 write!(f, "{{ ")?;
@@ -282,6 +504,18 @@ impl FormatDebug for Spanned<RawExpression> {
                 write!(f, "]")
             }),
             RawExpression::Path(p) => write!(f, "{}", p.debug(source)),
+            RawExpression::Pipe(p) => write!(f, "{}", p.debug(source)),
+            RawExpression::Range(r) => write!(f, "{}", r.debug(source)),
+            RawExpression::TypeTest(t) => write!(f, "{}", t.debug(source)),
+            RawExpression::Interpolation(parts) => f.say_block("interpolation", |f| {
+                write!(f, "$\" ")?;
+
+                for part in parts {
+                    write!(f, "{} ", part.debug(source))?;
+                }
+
+                write!(f, "\"")
+            }),
             RawExpression::Boolean(true) => write!(f, "$yes"),
             RawExpression::Boolean(false) => write!(f, "$no"),
         }
@@ -307,6 +541,8 @@ pub enum Literal {
     GlobPattern(String),
     ColumnPath(Vec<Member>),
     Bare,
+    /// `$nothing`, an explicit null
+    Nothing,
 }
 
 impl std::fmt::Display for Tagged<Literal> {
@@ -326,6 +562,7 @@ impl std::fmt::Display for Tagged<&Literal> {
             Literal::ColumnPath(_) => write!(f, "ColumnPath"),
             Literal::GlobPattern(_) => write!(f, "Glob{{ {}..{} }}", span.start(), span.end()),
             Literal::Bare => write!(f, "Bare{{ {}..{} }}", span.start(), span.end()),
+            Literal::Nothing => write!(f, "$nothing"),
         }
     }
 }
@@ -347,6 +584,7 @@ impl FormatDebug for Spanned<&Literal> {
             }),
             Literal::GlobPattern(..) => f.say_str("glob", self.span.slice(source)),
             Literal::Bare => f.say_str("word", self.span.slice(source)),
+            Literal::Nothing => write!(f, "$nothing"),
         }
     }
 }
@@ -360,6 +598,7 @@ impl Literal {
             Literal::ColumnPath(..) => "column path",
             Literal::Bare => "string",
             Literal::GlobPattern(_) => "pattern",
+            Literal::Nothing => "nothing",
         }
     }
 }
@@ -367,6 +606,10 @@ impl Literal {
 #[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
 pub enum Variable {
     It(Span),
+    /// `$nu`, the shell's own scope, like its config and current directory
+    Nu(Span),
+    /// `$env`, the environment variables
+    Env(Span),
     Other(Span),
 }
 
@@ -374,6 +617,8 @@ impl std::fmt::Display for Variable {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Variable::It(_) => write!(f, "$it"),
+            Variable::Nu(_) => write!(f, "$nu"),
+            Variable::Env(_) => write!(f, "$env"),
             Variable::Other(span) => write!(f, "${{ {}..{} }}", span.start(), span.end()),
         }
     }
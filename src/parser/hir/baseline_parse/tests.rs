@@ -1,16 +1,30 @@
 use crate::commands::classified::InternalCommand;
 use crate::commands::ClassifiedCommand;
 use crate::env::host::BasicHost;
+use crate::errors::{ParseError, ParseErrorReason, ParseWarning, ParseWarningReason, ShellError};
+use crate::evaluate::{evaluate_baseline_expr, Scope};
 use crate::parser::hir::TokensIterator;
 use crate::parser::hir::{
-    self, named::NamedValue, path::PathMember, syntax_shape::*, NamedArguments,
+    self,
+    named::NamedValue,
+    path::{ColumnPath, MemberInterner, MemberMarker, MemberName, PathMember, RawPathMember},
+    syntax_shape::*,
+    NamedArguments,
 };
+use crate::parser::parse::parser::{nom_input, token_list, Number};
 use crate::parser::parse::token_tree_builder::{CurriedToken, TokenTreeBuilder as b};
-use crate::parser::TokenNode;
-use crate::{HasSpan, Span, SpannedItem, Tag, Text};
+use crate::parser::{
+    Associativity, CommandRegistry, Operator, RawNumber, RawToken, TokenNode, Unit,
+};
+use crate::{HasSpan, Span, Spanned, SpannedItem, Tag, Tagged, TaggedItem, Text, ToDebug, Value};
+use bigdecimal::BigDecimal;
 use indexmap::IndexMap;
+use num_bigint::BigInt;
 use pretty_assertions::assert_eq;
+use std::cell::RefCell;
+use std::collections::HashSet;
 use std::fmt::Debug;
+use std::str::FromStr;
 
 #[test]
 fn test_parse_string() {
@@ -19,6 +33,57 @@ fn test_parse_string() {
     });
 }
 
+#[test]
+fn test_parse_nothing() {
+    parse_tokens(VariableShape, vec![b::var("nothing")], |tokens| {
+        hir::Expression::nothing(tokens[0].span())
+    });
+
+    parse_tokens(
+        VariablePathShape,
+        vec![b::var("nothing"), b::op("."), b::bare("foo")],
+        |tokens| {
+            let bare = tokens[2].expect_bare();
+            hir::Expression::path(
+                hir::Expression::nothing(tokens[0].span()),
+                vec![PathMember::string("foo", bare)],
+                tokens[0].span().until(bare),
+            )
+        },
+    );
+}
+
+#[test]
+fn test_parse_scope_variables() {
+    parse_tokens(
+        VariablePathShape,
+        vec![b::var("env"), b::op("."), b::bare("FOO")],
+        |tokens| {
+            let (outer_var, inner_var) = tokens[0].expect_var();
+            let bare = tokens[2].expect_bare();
+            hir::Expression::path(
+                hir::Expression::env_variable(inner_var, outer_var),
+                vec![PathMember::string("FOO", bare)],
+                outer_var.until(bare),
+            )
+        },
+    );
+
+    parse_tokens(
+        VariablePathShape,
+        vec![b::var("nu"), b::op("."), b::bare("cwd")],
+        |tokens| {
+            let (outer_var, inner_var) = tokens[0].expect_var();
+            let bare = tokens[2].expect_bare();
+            hir::Expression::path(
+                hir::Expression::nu_variable(inner_var, outer_var),
+                vec![PathMember::string("cwd", bare)],
+                outer_var.until(bare),
+            )
+        },
+    );
+}
+
 #[test]
 fn test_parse_path() {
     parse_tokens(
@@ -62,65 +127,4792 @@ fn test_parse_path() {
 }
 
 #[test]
-fn test_parse_command() {
+fn test_parse_negative_int_member() {
+    for index in vec![-1, -2] {
+        parse_tokens(
+            VariablePathShape,
+            vec![b::var("list"), b::op("."), b::int(index)],
+            |tokens| {
+                let (outer_var, inner_var) = tokens[0].expect_var();
+                let int = tokens[2].span();
+
+                hir::Expression::path(
+                    hir::Expression::variable(inner_var, outer_var),
+                    vec![PathMember::int(index, int)],
+                    outer_var.until(int),
+                )
+            },
+        );
+    }
+}
+
+#[test]
+fn test_parse_quoted_int_member_is_a_string() {
+    // `$record."0"` addresses the string key "0"
     parse_tokens(
-        ClassifiedCommandShape,
-        vec![b::bare("ls"), b::sp(), b::pattern("*.txt")],
+        VariablePathShape,
+        vec![b::var("record"), b::op("."), b::string("0")],
         |tokens| {
-            let bare = tokens[0].expect_bare();
-            let pat = tokens[2].expect_pattern();
+            let (outer_var, inner_var) = tokens[0].expect_var();
+            let string = tokens[2].span();
 
-            let mut map = IndexMap::new();
-            map.insert("full".to_string(), NamedValue::AbsentSwitch);
+            hir::Expression::path(
+                hir::Expression::variable(inner_var, outer_var),
+                vec![PathMember::string("0", string)],
+                outer_var.until(string),
+            )
+        },
+    );
 
-            ClassifiedCommand::Internal(InternalCommand::new(
-                "ls".to_string(),
-                Tag {
-                    span: bare,
-                    anchor: None,
-                },
-                hir::Call {
-                    head: Box::new(hir::RawExpression::Command(bare).spanned(bare)),
-                    positional: Some(vec![hir::Expression::pattern("*.txt", pat)]),
-                    named: Some(NamedArguments { named: map }),
+    parse_tokens(MemberShape, vec![b::string("0")], |tokens| {
+        let outer = tokens[0].span();
+        Member::String(outer, inner_string_span(outer))
+    });
+
+    // while `$record.0` is the zeroth element
+    parse_tokens(
+        VariablePathShape,
+        vec![b::var("record"), b::op("."), b::int(0)],
+        |tokens| {
+            let (outer_var, inner_var) = tokens[0].expect_var();
+            let int = tokens[2].span();
+
+            hir::Expression::path(
+                hir::Expression::variable(inner_var, outer_var),
+                vec![PathMember::int(0, int)],
+                outer_var.until(int),
+            )
+        },
+    );
+
+    parse_tokens(MemberShape, vec![b::int(0)], |tokens| {
+        Member::Int(BigInt::from(0), tokens[0].span())
+    });
+}
+
+#[test]
+fn test_parse_int_member_in_strict_record_mode() {
+    let parse_index = |strict: bool| {
+        with_tokens(
+            vec![b::var("record"), b::op("."), b::int(0)],
+            |iterator, context, tokens| {
+                let context = if strict {
+                    context.with_strict_records()
+                } else {
+                    context
+                };
+                let int = tokens[2].span();
+
+                let expr = expand_expr(&VariablePathShape, iterator, &context).unwrap();
+
+                match &expr.item {
+                    hir::RawExpression::Path(path) => (path.tail().clone(), int),
+                    other => panic!("expected a path, got {:?}", other),
                 }
-                .spanned(bare.until(pat)),
-            ))
-            // hir::Expression::path(
-            //     hir::Expression::variable(inner_var, outer_var),
-            //     vec!["cpu".tagged(bare)],
-            //     outer_var.until(bare),
-            // )
+            },
+        )
+    };
+
+    // by default, `$record.0` is the zeroth element
+    let (tail, int) = parse_index(false);
+    assert_eq!(tail, vec![PathMember::int(0, int)]);
+
+    // in strict-record mode, it's the column named "0"
+    let (tail, int) = parse_index(true);
+    assert_eq!(tail, vec![PathMember::string("0", int)]);
+}
+
+#[test]
+fn test_parse_range_path_member() {
+    parse_tokens(
+        VariablePathShape,
+        vec![
+            b::var("rows"),
+            b::op("."),
+            b::bare("2"),
+            b::op("."),
+            b::op("."),
+            b::bare("5"),
+        ],
+        |tokens| {
+            let (outer_var, inner_var) = tokens[0].expect_var();
+            let from = tokens[2].span();
+            let to = tokens[5].span();
+
+            hir::Expression::path(
+                hir::Expression::variable(inner_var, outer_var),
+                vec![PathMember::range(
+                    2,
+                    Some(BigInt::from(5)),
+                    false,
+                    from.until(to),
+                )],
+                outer_var.until(to),
+            )
+        },
+    );
+
+    parse_tokens(
+        VariablePathShape,
+        vec![
+            b::var("rows"),
+            b::op("."),
+            b::bare("2"),
+            b::op("."),
+            b::op("."),
+            b::bare("="),
+            b::bare("5"),
+        ],
+        |tokens| {
+            let (outer_var, inner_var) = tokens[0].expect_var();
+            let from = tokens[2].span();
+            let to = tokens[6].span();
+
+            hir::Expression::path(
+                hir::Expression::variable(inner_var, outer_var),
+                vec![PathMember::range(
+                    2,
+                    Some(BigInt::from(5)),
+                    true,
+                    from.until(to),
+                )],
+                outer_var.until(to),
+            )
+        },
+    );
+
+    parse_tokens(
+        VariablePathShape,
+        vec![
+            b::var("rows"),
+            b::op("."),
+            b::bare("2"),
+            b::op("."),
+            b::op("."),
+        ],
+        |tokens| {
+            let (outer_var, inner_var) = tokens[0].expect_var();
+            let from = tokens[2].span();
+            let dotdot = tokens[4].span();
+
+            hir::Expression::path(
+                hir::Expression::variable(inner_var, outer_var),
+                vec![PathMember::range(2, None, false, from.until(dotdot))],
+                outer_var.until(dotdot),
+            )
         },
     );
 }
 
-fn parse_tokens<T: Eq + HasSpan + Clone + Debug + 'static>(
-    shape: impl ExpandSyntax<Output = T>,
-    tokens: Vec<CurriedToken>,
-    expected: impl FnOnce(&[TokenNode]) -> T,
-) {
-    let tokens = b::token_list(tokens);
-    let (tokens, source) = b::build(tokens);
+#[test]
+fn test_parse_malformed_int_member() {
+    // the lexer never produces an integer like this, but it shouldn't panic if it did
+    let source = Text::from("1x3");
+    let span = Span::new(0, 3);
+    let tokens = vec![TokenNode::Token(
+        RawToken::Number(RawNumber::Int(span)).spanned(span),
+    )];
 
-    ExpandContext::with_empty(&Text::from(source), |context| {
-        let tokens = tokens.expect_list();
-        let mut iterator = TokensIterator::all(tokens.item, tokens.span);
+    ExpandContext::with_empty(&source, |context| {
+        let mut iterator = TokensIterator::all(&tokens, span);
 
-        let expr = expand_syntax(&shape, &mut iterator, &context);
+        match expand_syntax(&IntMemberShape, &mut iterator, &context) {
+            Err(err) => match err.reason() {
+                ParseErrorReason::Mismatch { expected, .. } => {
+                    assert_eq!(*expected, "integer member")
+                }
+                other => panic!("expected a mismatch error, got {:?}", other),
+            },
+            Ok(member) => panic!("expected a parse error, got {:?}", member),
+        }
+    });
+}
 
-        let expr = match expr {
-            Ok(expr) => expr,
-            Err(err) => {
-                crate::cli::print_err(err.into(), &BasicHost, context.source().clone());
-                panic!("Parse failed");
-            }
-        };
+#[test]
+fn test_parse_decimal_member() {
+    // `.1.5` is a single column named "1.5", not the integer members 1 and 5
+    parse_tokens(
+        VariablePathShape,
+        vec![
+            b::var("data"),
+            b::op("."),
+            b::decimal(BigDecimal::from_str("1.5").unwrap()),
+        ],
+        |tokens| {
+            let (outer_var, inner_var) = tokens[0].expect_var();
+            let decimal = tokens[2].span();
 
-        assert_eq!(expr, expected(tokens.item));
-    })
+            hir::Expression::path(
+                hir::Expression::variable(inner_var, outer_var),
+                vec![PathMember::string("1.5", decimal)],
+                outer_var.until(decimal),
+            )
+        },
+    );
+
+    assert_eq!(
+        expand_member(vec![b::decimal(BigDecimal::from_str("1.5").unwrap())]),
+        Member::Bare(Span::new(0, 3))
+    );
 }
 
-fn inner_string_span(span: Span) -> Span {
-    Span::new(span.start() + 1, span.end() - 1)
+#[test]
+fn test_variable_path_span() {
+    let cases = vec![
+        vec![b::var("it"), b::op("."), b::bare("cpu")],
+        vec![
+            b::var("it"),
+            b::op("."),
+            b::bare("amount"),
+            b::op("."),
+            b::bare("max"),
+        ],
+        vec![b::var("it"), b::op("."), b::string("max ghz")],
+        vec![
+            b::var("it"),
+            b::op("."),
+            b::bare("cpu"),
+            b::op("."),
+            b::int(0),
+        ],
+        vec![b::var("it"), b::op("."), b::int(-1)],
+        vec![
+            b::var("it"),
+            b::op("."),
+            b::string("cpu"),
+            b::op("."),
+            b::int(10),
+        ],
+    ];
+
+    for tokens in cases {
+        let (span, source) = expand_path_span(tokens);
+        assert_eq!(span.slice(&source), source);
+    }
+}
+
+#[test]
+fn test_parse_conditional_member() {
+    parse_tokens(
+        VariablePathShape,
+        vec![
+            b::var("it"),
+            b::op("."),
+            b::bare("name"),
+            b::sp(),
+            b::bare("if"),
+            b::sp(),
+            b::op("."),
+            b::bare("active"),
+        ],
+        |tokens| {
+            let (outer_var, inner_var) = tokens[0].expect_var();
+            let name = tokens[2].expect_bare();
+            let dot = tokens[6].span();
+            let active = tokens[7].expect_bare();
+
+            hir::Expression::path(
+                hir::Expression::it_variable(inner_var, outer_var),
+                vec![PathMember::conditional(
+                    PathMember::string("name", name),
+                    hir::Expression::bare(dot.until(active)),
+                    name.until(active),
+                )],
+                outer_var.until(active),
+            )
+        },
+    );
+}
+
+#[test]
+fn test_parse_conditional_member_without_predicate() {
+    let err = parse_error(
+        VariablePathShape,
+        vec![
+            b::var("it"),
+            b::op("."),
+            b::bare("name"),
+            b::sp(),
+            b::bare("if"),
+        ],
+    );
+
+    match err.reason() {
+        ParseErrorReason::Eof { .. } => {}
+        other => panic!("expected an unexpected eof error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_column_path_leaves_if_alone() {
+    // `pick name if` picks a column named `if`, so the column path has to stop at `name`
+    parse_tokens(
+        ColumnPathShape,
+        vec![b::bare("name"), b::sp(), b::bare("if")],
+        |tokens| {
+            let name = tokens[0].expect_bare();
+
+            ColumnPathMembers::from_vec(vec![Member::Bare(name)]).tagged(name)
+        },
+    );
+}
+
+#[test]
+fn test_evaluate_conditional_member() {
+    // $it.name if .active
+    let tokens = || {
+        vec![
+            b::var("it"),
+            b::op("."),
+            b::bare("name"),
+            b::sp(),
+            b::bare("if"),
+            b::sp(),
+            b::op("."),
+            b::bare("active"),
+        ]
+    };
+
+    let user = |active: bool| {
+        Value::row(indexmap! {
+            "name".into() => Value::string("nu").tagged_unknown(),
+            "active".into() => Value::boolean(active).tagged_unknown()
+        })
+        .tagged_unknown()
+    };
+
+    assert_eq!(
+        evaluate_tokens(tokens(), user(true)).unwrap().item,
+        Value::string("nu")
+    );
+
+    assert_eq!(
+        evaluate_tokens(tokens(), user(false)).unwrap().item,
+        Value::nothing()
+    );
+}
+
+#[test]
+fn test_parse_cast_member() {
+    parse_tokens(
+        VariablePathShape,
+        vec![
+            b::var("it"),
+            b::op("."),
+            b::bare("age"),
+            b::sp(),
+            b::bare("as"),
+            b::sp(),
+            b::bare("int"),
+        ],
+        |tokens| {
+            let (outer_var, inner_var) = tokens[0].expect_var();
+            let age = tokens[2].expect_bare();
+            let int = tokens[6].expect_bare();
+
+            hir::Expression::path(
+                hir::Expression::it_variable(inner_var, outer_var),
+                vec![PathMember::cast(
+                    PathMember::string("age", age),
+                    TypeName::Int,
+                    age.until(int),
+                )],
+                outer_var.until(int),
+            )
+        },
+    );
+
+    parse_tokens(
+        VariablePathShape,
+        vec![
+            b::var("it"),
+            b::op("."),
+            b::bare("age"),
+            b::sp(),
+            b::bare("as"),
+            b::sp(),
+            b::bare("int"),
+            b::op("."),
+            b::bare("next"),
+        ],
+        |tokens| {
+            let (outer_var, inner_var) = tokens[0].expect_var();
+            let age = tokens[2].expect_bare();
+            let int = tokens[6].expect_bare();
+            let next = tokens[8].expect_bare();
+
+            hir::Expression::path(
+                hir::Expression::it_variable(inner_var, outer_var),
+                vec![
+                    PathMember::cast(
+                        PathMember::string("age", age),
+                        TypeName::Int,
+                        age.until(int),
+                    ),
+                    PathMember::string("next", next),
+                ],
+                outer_var.until(next),
+            )
+        },
+    );
+}
+
+#[test]
+fn test_parse_cast_member_with_unknown_type() {
+    let err = parse_error(
+        VariablePathShape,
+        vec![
+            b::var("it"),
+            b::op("."),
+            b::bare("age"),
+            b::sp(),
+            b::bare("as"),
+            b::sp(),
+            b::bare("integr"),
+        ],
+    );
+
+    match err.reason() {
+        ParseErrorReason::Mismatch { expected, actual } => {
+            assert_eq!(*expected, "type name");
+            assert_eq!(actual.item, "integr");
+        }
+        other => panic!("expected a mismatch error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_column_path_leaves_as_alone() {
+    // like `if`, an `as` after a column path is the command's business
+    parse_tokens(
+        ColumnPathShape,
+        vec![
+            b::bare("age"),
+            b::sp(),
+            b::bare("as"),
+            b::sp(),
+            b::bare("int"),
+        ],
+        |tokens| {
+            let age = tokens[0].expect_bare();
+
+            ColumnPathMembers::from_vec(vec![Member::Bare(age)]).tagged(age)
+        },
+    );
+}
+
+#[test]
+fn test_evaluate_cast_member() {
+    let cast = |value: Value, type_name: &str| {
+        let it = Value::row(indexmap! {
+            "value".into() => value.tagged_unknown()
+        });
+
+        // $it.value as <type_name>
+        evaluate_tokens(
+            vec![
+                b::var("it"),
+                b::op("."),
+                b::bare("value"),
+                b::sp(),
+                b::bare("as"),
+                b::sp(),
+                b::bare(type_name),
+            ],
+            it.tagged_unknown(),
+        )
+        .map(|value| value.item)
+    };
+
+    assert_eq!(cast(Value::string("42"), "int").unwrap(), Value::int(42));
+    assert_eq!(cast(Value::int(42), "string").unwrap(), Value::string("42"));
+    assert_eq!(cast(Value::int(42), "int").unwrap(), Value::int(42));
+    assert!(cast(Value::string("forty-two"), "int").is_err());
+}
+
+#[test]
+fn test_parse_splat_member() {
+    parse_tokens(
+        ColumnPathShape,
+        vec![b::bare("data"), b::op("."), b::op("."), b::op(".")],
+        |tokens| {
+            let data = tokens[0].expect_bare();
+            let last_dot = tokens[3].span();
+
+            ColumnPathMembers::from_vec(vec![Member::Splat(
+                Box::new(Member::Bare(data)),
+                data.until(last_dot),
+            )])
+            .tagged(data.until(last_dot))
+        },
+    );
+}
+
+#[test]
+fn test_parse_splat_path() {
+    parse_tokens(
+        VariablePathShape,
+        vec![
+            b::var("it"),
+            b::op("."),
+            b::bare("data"),
+            b::op("."),
+            b::op("."),
+            b::op("."),
+        ],
+        |tokens| {
+            let (outer_var, inner_var) = tokens[0].expect_var();
+            let data = tokens[2].expect_bare();
+            let last_dot = tokens[5].span();
+            hir::Expression::path(
+                hir::Expression::it_variable(inner_var, outer_var),
+                vec![PathMember::splat(
+                    PathMember::string("data", data),
+                    data.until(last_dot),
+                )],
+                outer_var.until(last_dot),
+            )
+        },
+    );
+}
+
+#[test]
+fn test_parse_two_dots_is_not_a_splat() {
+    let err = parse_error(
+        ColumnPathShape,
+        vec![b::bare("data"), b::op("."), b::op("."), b::bare("name")],
+    );
+
+    match err.reason() {
+        ParseErrorReason::RepeatedDot { previous, dot } => {
+            assert_eq!(*previous, Span::new(4, 5));
+            assert_eq!(*dot, Span::new(5, 6));
+        }
+        other => panic!("expected a repeated dot error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_repeated_dot_in_variable_path() {
+    let err = parse_error(
+        VariablePathShape,
+        vec![b::var("x"), b::op("."), b::op("."), b::bare("y")],
+    );
+
+    match err.reason() {
+        ParseErrorReason::RepeatedDot { previous, dot } => {
+            assert_eq!(*previous, Span::new(2, 3));
+            assert_eq!(*dot, Span::new(3, 4));
+        }
+        other => panic!("expected a repeated dot error, got {:?}", other),
+    }
+
+    assert_eq!(
+        ShellError::from(err),
+        ShellError::labeled_error_with_secondary(
+            "Expected a column name",
+            "expected a column name after '.', found another '.'",
+            Span::new(3, 4),
+            "after this '.'",
+            Span::new(2, 3),
+        )
+    );
+}
+
+#[test]
+fn test_parse_unicode_bare_member() {
+    parse_tokens(MemberShape, vec![b::bare("名前")], |tokens| {
+        Member::Bare(tokens[0].expect_bare())
+    });
+
+    parse_tokens(MemberShape, vec![b::bare("café")], |tokens| {
+        Member::Bare(tokens[0].expect_bare())
+    });
+
+    parse_tokens(
+        VariablePathShape,
+        vec![b::var("record"), b::op("."), b::bare("名前")],
+        |tokens| {
+            let (outer_var, inner_var) = tokens[0].expect_var();
+            let bare = tokens[2].expect_bare();
+            hir::Expression::path(
+                hir::Expression::variable(inner_var, outer_var),
+                vec![PathMember::string("名前", bare)],
+                outer_var.until(bare),
+            )
+        },
+    );
+}
+
+#[test]
+fn test_parse_column_number_member() {
+    parse_tokens(MemberShape, vec![b::bare("#3")], |tokens| {
+        Member::ColumnNumber(BigInt::from(3), tokens[0].expect_bare())
+    });
+
+    parse_tokens(MemberShape, vec![b::int(3)], |tokens| {
+        Member::Int(BigInt::from(3), tokens[0].span())
+    });
+}
+
+#[test]
+fn test_parse_column_number_path() {
+    parse_tokens(
+        VariablePathShape,
+        vec![b::var("it"), b::op("."), b::bare("#2")],
+        |tokens| {
+            let (outer_var, inner_var) = tokens[0].expect_var();
+            let column = tokens[2].expect_bare();
+            hir::Expression::path(
+                hir::Expression::it_variable(inner_var, outer_var),
+                vec![PathMember::column_number(2, column)],
+                outer_var.until(column),
+            )
+        },
+    );
+}
+
+#[test]
+fn test_parse_computed_member() {
+    parse_tokens(
+        MemberShape,
+        vec![b::parens(vec![
+            b::bare("a"),
+            b::sp(),
+            b::op("??"),
+            b::sp(),
+            b::bare("b"),
+            b::sp(),
+            b::op("??"),
+            b::sp(),
+            b::string("x"),
+        ])],
+        |tokens| {
+            // (a ?? b ?? "x")
+            Member::Computed(
+                hir::Expression::infix(
+                    hir::Expression::infix(
+                        hir::Expression::bare(Span::new(1, 2)),
+                        Operator::Coalesce.spanned(Span::new(3, 5)),
+                        hir::Expression::bare(Span::new(6, 7)),
+                    ),
+                    Operator::Coalesce.spanned(Span::new(8, 10)),
+                    hir::Expression::string(Span::new(12, 13), Span::new(11, 14)),
+                ),
+                tokens[0].span(),
+            )
+        },
+    );
+}
+
+#[test]
+fn test_evaluate_computed_member() {
+    // $it.(a ?? b ?? "x")
+    let tokens = || {
+        vec![
+            b::var("it"),
+            b::op("."),
+            b::parens(vec![
+                b::bare("a"),
+                b::sp(),
+                b::op("??"),
+                b::sp(),
+                b::bare("b"),
+                b::sp(),
+                b::op("??"),
+                b::sp(),
+                b::string("x"),
+            ]),
+        ]
+    };
+
+    let row = Value::row(indexmap! {
+        "b".into() => Value::int(2).tagged_unknown()
+    });
+
+    assert_eq!(
+        evaluate_tokens(tokens(), row.tagged_unknown())
+            .unwrap()
+            .item,
+        Value::int(2)
+    );
+
+    assert_eq!(
+        evaluate_tokens(tokens(), Value::row(indexmap! {}).tagged_unknown())
+            .unwrap()
+            .item,
+        Value::string("x")
+    );
+}
+
+#[test]
+fn test_parse_filter_member() {
+    parse_tokens(
+        ColumnPathShape,
+        vec![
+            b::bare("users"),
+            b::op("."),
+            b::square(vec![b::op("."), b::bare("active")]),
+            b::op("."),
+            b::bare("name"),
+        ],
+        |tokens| {
+            let users = tokens[0].expect_bare();
+            let filter = tokens[2].span();
+            let name = tokens[4].expect_bare();
+
+            // the predicate is `.active`, just inside the brackets
+            let predicate = Span::new(filter.start() + 1, filter.end() - 1);
+
+            ColumnPathMembers::from_vec(vec![
+                Member::Bare(users),
+                Member::Filter(hir::Expression::bare(predicate), filter),
+                Member::Bare(name),
+            ])
+            .tagged(users.until(name))
+        },
+    );
+}
+
+#[test]
+fn test_evaluate_filter_member() {
+    // $it.users.[.active].name
+    let tokens = vec![
+        b::var("it"),
+        b::op("."),
+        b::bare("users"),
+        b::op("."),
+        b::square(vec![b::op("."), b::bare("active")]),
+        b::op("."),
+        b::bare("name"),
+    ];
+
+    let user = |name: &str, active: bool| {
+        Value::row(indexmap! {
+            "name".into() => Value::string(name).tagged_unknown(),
+            "active".into() => Value::boolean(active).tagged_unknown()
+        })
+        .tagged_unknown()
+    };
+
+    let it = Value::row(indexmap! {
+        "users".into() => Value::table(&vec![
+            user("andres", true),
+            user("jonathan", false),
+            user("yehuda", true)
+        ])
+        .tagged_unknown()
+    });
+
+    assert_eq!(
+        table_items(evaluate_tokens(tokens, it.tagged_unknown()).unwrap()),
+        vec![Value::string("andres"), Value::string("yehuda")]
+    );
+}
+
+#[test]
+fn test_parse_malformed_filter_member() {
+    let err = parse_error(
+        MemberShape,
+        vec![b::square(vec![
+            b::op("."),
+            b::bare("active"),
+            b::sp(),
+            b::op("."),
+            b::bare("admin"),
+        ])],
+    );
+
+    match err.reason() {
+        ParseErrorReason::Mismatch { expected, .. } => assert_eq!(*expected, "end of filter"),
+        other => panic!("expected a mismatch error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_computed_member_nesting_limit() {
+    // ($it.($it.(a)))
+    let err = with_tokens(
+        vec![b::parens(vec![
+            b::var("it"),
+            b::op("."),
+            b::parens(vec![
+                b::var("it"),
+                b::op("."),
+                b::parens(vec![b::bare("a")]),
+            ]),
+        ])],
+        |iterator, context, _| {
+            let context = context.with_member_nesting_limit(2);
+
+            match expand_syntax(&MemberShape, iterator, &context) {
+                Ok(member) => panic!("expected a parse error, got {:?}", member),
+                Err(err) => err,
+            }
+        },
+    );
+
+    match err.reason() {
+        // the innermost open paren
+        ParseErrorReason::TooDeep { span } => assert_eq!(*span, Span::new(10, 11)),
+        other => panic!("expected a too deep error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_spread_member() {
+    parse_tokens(
+        MemberShape,
+        vec![b::parens(vec![
+            b::op("."),
+            b::op("."),
+            b::op("."),
+            b::var("defaults"),
+        ])],
+        |tokens| {
+            // (...$defaults)
+            Member::Spread(
+                hir::Expression::variable(Span::new(5, 13), Span::new(4, 13)),
+                tokens[0].span(),
+            )
+        },
+    );
+
+    parse_tokens(
+        MemberShape,
+        vec![b::parens(vec![
+            b::op("."),
+            b::op("."),
+            b::op("."),
+            b::string("x"),
+        ])],
+        |tokens| {
+            // (..."x")
+            Member::Spread(
+                hir::Expression::string(Span::new(5, 6), Span::new(4, 7)),
+                tokens[0].span(),
+            )
+        },
+    );
+}
+
+#[test]
+fn test_evaluate_spread_member() {
+    // $it.(...defaults)
+    let tokens = vec![
+        b::var("it"),
+        b::op("."),
+        b::parens(vec![
+            b::op("."),
+            b::op("."),
+            b::op("."),
+            b::bare("defaults"),
+        ]),
+    ];
+
+    let it = Value::row(indexmap! {
+        "name".into() => Value::string("nu").tagged_unknown(),
+        "defaults".into() => Value::row(indexmap! {
+            "name".into() => Value::string("unnamed").tagged_unknown(),
+            "version".into() => Value::string("0.1.0").tagged_unknown()
+        })
+        .tagged_unknown()
+    });
+
+    let merged = evaluate_tokens(tokens, it.tagged_unknown()).unwrap();
+    let column = |name: &str| {
+        merged
+            .item
+            .get_data_by_key(name.spanned(Span::unknown()))
+            .unwrap()
+            .item
+    };
+
+    assert_eq!(
+        merged.item.data_descriptors(),
+        vec!["name", "defaults", "version"]
+    );
+    assert_eq!(column("name"), Value::string("nu"));
+    assert_eq!(column("version"), Value::string("0.1.0"));
+}
+
+#[test]
+fn test_parse_spread_member_without_expression() {
+    let err = parse_error(
+        MemberShape,
+        vec![b::parens(vec![b::op("."), b::op("."), b::op(".")])],
+    );
+
+    match err.reason() {
+        ParseErrorReason::Eof { .. } => {}
+        other => panic!("expected an unexpected eof error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_range_member() {
+    parse_tokens(
+        ColumnPathShape,
+        vec![
+            b::bare("name"),
+            b::square(vec![b::bare("0"), b::op("."), b::op("."), b::bare("3")]),
+        ],
+        |tokens| {
+            let name = tokens[0].expect_bare();
+            let (square, from, dotdot, to) = expect_range(&tokens[1]);
+
+            ColumnPathMembers::from_vec(vec![
+                Member::Bare(name),
+                Member::Range(
+                    hir::Expression::range(
+                        hir::Expression::number(BigInt::from(0), from),
+                        dotdot,
+                        hir::Expression::number(BigInt::from(3), to),
+                        square,
+                    ),
+                    square,
+                ),
+            ])
+            .tagged(name.until(square))
+        },
+    );
+
+    parse_tokens(
+        VariablePathShape,
+        vec![
+            b::var("it"),
+            b::op("."),
+            b::bare("name"),
+            b::square(vec![b::bare("0"), b::op("."), b::op("."), b::bare("3")]),
+        ],
+        |tokens| {
+            let (outer_var, inner_var) = tokens[0].expect_var();
+            let name = tokens[2].expect_bare();
+            let square = tokens[3].span();
+
+            hir::Expression::path(
+                hir::Expression::it_variable(inner_var, outer_var),
+                vec![
+                    PathMember::string("name", name),
+                    PathMember::range(0, Some(BigInt::from(3)), false, square),
+                ],
+                outer_var.until(square),
+            )
+        },
+    );
+}
+
+#[test]
+fn test_parse_range_member_with_variable_bounds() {
+    parse_tokens(
+        VariablePathShape,
+        vec![
+            b::var("it"),
+            b::op("."),
+            b::bare("rows"),
+            b::square(vec![b::var("start"), b::op("."), b::op("."), b::bare("3")]),
+        ],
+        |tokens| {
+            let (outer_var, inner_var) = tokens[0].expect_var();
+            let rows = tokens[2].expect_bare();
+            let (square, from, dotdot, to) = expect_range(&tokens[3]);
+            let start = Span::new(from.start() + 1, from.end());
+
+            hir::Expression::path(
+                hir::Expression::it_variable(inner_var, outer_var),
+                vec![
+                    PathMember::string("rows", rows),
+                    PathMember::computed_range(
+                        hir::Range::new(
+                            Some(hir::Expression::variable(start, from)),
+                            dotdot,
+                            Some(hir::Expression::number(BigInt::from(3), to)),
+                            false,
+                        ),
+                        square,
+                    ),
+                ],
+                outer_var.until(square),
+            )
+        },
+    );
+}
+
+#[test]
+fn test_parse_column_path_from_tokens() {
+    with_tokens(
+        vec![b::bare("name"), b::op("."), b::bare("first")],
+        |_, context, tokens| {
+            let name = tokens[0].expect_bare();
+            let first = tokens[2].expect_bare();
+
+            let path = parse_column_path_from_tokens(tokens, &context).unwrap();
+
+            assert_eq!(
+                path,
+                ColumnPathMembers::from_vec(vec![Member::Bare(name), Member::Bare(first)])
+                    .tagged(name.until(first))
+            );
+        },
+    );
+
+    with_tokens(
+        vec![b::bare("name"), b::sp(), b::bare("first")],
+        |_, context, tokens| match parse_column_path_from_tokens(tokens, &context) {
+            Err(err) => match err.reason() {
+                ParseErrorReason::Mismatch { expected, .. } => {
+                    assert_eq!(*expected, "end of column path")
+                }
+                other => panic!("expected a mismatch error, got {:?}", other),
+            },
+            Ok(path) => panic!("expected a parse error, got {:?}", path),
+        },
+    );
+}
+
+#[test]
+fn test_canonical_int_member() {
+    let padded = expand_member(vec![b::bare("007")]);
+    let unpadded = expand_member(vec![b::int(7)]);
+
+    match (&padded, &unpadded) {
+        (Member::Int(padded, _), Member::Int(unpadded, _)) => assert_eq!(padded, unpadded),
+        other => panic!("expected two integer members, got {:?}", other),
+    }
+
+    assert_eq!(padded.canonical_int_string(), Some("7".to_string()));
+    assert_eq!(unpadded.canonical_int_string(), Some("7".to_string()));
+    assert_eq!(
+        expand_member(vec![b::bare("+07")]).canonical_int_string(),
+        Some("7".to_string())
+    );
+    assert_eq!(
+        expand_member(vec![b::bare("name")]).canonical_int_string(),
+        None
+    );
+}
+
+#[test]
+fn test_radix_int_member() {
+    let cases = vec![("0xff", 255), ("0o17", 15), ("0b1010", 10)];
+
+    for (text, value) in cases {
+        let span = Span::new(0, text.len());
+
+        assert_eq!(
+            expand_member(vec![b::int_literal(text)]),
+            Member::Int(BigInt::from(value), span)
+        );
+        assert_eq!(
+            expand_member(vec![b::bare(text)]),
+            Member::Int(BigInt::from(value), span)
+        );
+    }
+}
+
+#[test]
+fn test_parse_invalid_radix_int_member() {
+    for text in &["0xzz", "0x"] {
+        expect_int_member_mismatch(text);
+    }
+}
+
+#[test]
+fn test_int_member_separators() {
+    assert_eq!(
+        expand_member(vec![b::int_literal("1_000")]),
+        Member::Int(BigInt::from(1000), Span::new(0, 5))
+    );
+    assert_eq!(
+        expand_member(vec![b::bare("1_000")]),
+        Member::Int(BigInt::from(1000), Span::new(0, 5))
+    );
+
+    for text in &["_1", "1__0", "1_"] {
+        expect_int_member_mismatch(text);
+    }
+}
+
+#[test]
+fn test_parse_duration() {
+    parse_tokens(DurationShape, vec![b::bare("10min")], |tokens| {
+        (
+            RawNumber::int(Span::new(0, 2)),
+            Unit::Minute.spanned(Span::new(2, 5)),
+        )
+            .spanned(tokens[0].span())
+    });
+
+    with_tokens(vec![b::bare("10min")], |iterator, context, _| {
+        assert_eq!(
+            expand_expr(&AnyExpressionShape, iterator, &context).unwrap(),
+            hir::Expression::size(10, Unit::Minute, Span::new(0, 5))
+        );
+    });
+}
+
+#[test]
+fn test_parse_separated_duration() {
+    let err = parse_error(DurationShape, vec![b::int(10), b::sp(), b::bare("min")]);
+
+    match err.reason() {
+        ParseErrorReason::Mismatch { expected, .. } => assert_eq!(*expected, "duration"),
+        other => panic!("expected a mismatch error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_unknown_duration_unit() {
+    let err = parse_error(DurationShape, vec![b::bare("10mins")]);
+
+    match err.reason() {
+        ParseErrorReason::Mismatch { expected, actual } => {
+            assert_eq!(*expected, "duration unit");
+            assert_eq!(actual.item, "mins");
+            assert_eq!(actual.span, Span::new(2, 6));
+        }
+        other => panic!("expected a mismatch error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_sub_second_duration() {
+    for text in &["500ns", "500us", "500ms"] {
+        let err = parse_error(DurationShape, vec![b::bare(*text)]);
+
+        match err.reason() {
+            ParseErrorReason::SubSecondDuration { span } => {
+                assert_eq!(*span, Span::new(3, 5))
+            }
+            other => panic!("expected a sub-second duration error, got {:?}", other),
+        }
+    }
+}
+
+#[test]
+fn test_parse_filesize() {
+    let units = [
+        ("b", Unit::Byte),
+        ("kb", Unit::Kilobyte),
+        ("mb", Unit::Megabyte),
+        ("gb", Unit::Gigabyte),
+        ("kib", Unit::Kibibyte),
+        ("mib", Unit::Mebibyte),
+        ("gib", Unit::Gibibyte),
+    ];
+
+    for (suffix, unit) in &units {
+        let text = format!("10{}", suffix);
+
+        parse_tokens(FilesizeShape, vec![b::bare(&text)], |tokens| {
+            (
+                RawNumber::int(Span::new(0, 2)),
+                (*unit).spanned(Span::new(2, text.len())),
+            )
+                .spanned(tokens[0].span())
+        });
+    }
+}
+
+#[test]
+fn test_decimal_filesize_units_are_powers_of_1000() {
+    let units = [
+        (Unit::Kilobyte, 1_000u64),
+        (Unit::Megabyte, 1_000_000),
+        (Unit::Gigabyte, 1_000_000_000),
+    ];
+
+    for (unit, bytes) in &units {
+        assert_eq!(unit.compute(&Number::from(10)), Value::number(10 * bytes));
+    }
+}
+
+#[test]
+fn test_binary_filesize_units_are_powers_of_1024() {
+    let units = [
+        (Unit::Kibibyte, 1_024u64),
+        (Unit::Mebibyte, 1_048_576),
+        (Unit::Gibibyte, 1_073_741_824),
+    ];
+
+    for (unit, bytes) in &units {
+        assert_eq!(unit.compute(&Number::from(10)), Value::number(10 * bytes));
+    }
+}
+
+#[test]
+fn test_parse_unknown_filesize_unit() {
+    let err = parse_error(FilesizeShape, vec![b::bare("10xb")]);
+
+    match err.reason() {
+        ParseErrorReason::Mismatch { expected, actual } => {
+            assert_eq!(*expected, "filesize unit");
+            assert_eq!(actual.item, "xb");
+            assert_eq!(actual.span, Span::new(2, 4));
+        }
+        other => panic!("expected a mismatch error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_interpolation() {
+    let expr = expand_interpolation(vec![
+        b::segment("a "),
+        b::parens(vec![b::var("x")]),
+        b::segment(" b"),
+    ])
+    .unwrap();
+
+    assert_eq!(
+        expr,
+        hir::Expression::interpolation(
+            vec![
+                synthetic_string("a ", Span::new(2, 4)),
+                hir::Expression::variable(Span::new(6, 7), Span::new(5, 7)),
+                synthetic_string(" b", Span::new(8, 10)),
+            ],
+            Span::new(0, 11)
+        )
+    );
+}
+
+#[test]
+fn test_parse_escaped_interpolation() {
+    let expr = expand_interpolation(vec![b::segment(r"a \(b")]).unwrap();
+
+    assert_eq!(
+        expr,
+        hir::Expression::interpolation(
+            vec![synthetic_string("a (b", Span::new(2, 7))],
+            Span::new(0, 8)
+        )
+    );
+}
+
+#[test]
+fn test_parse_escaped_backslash_interpolation() {
+    let expr = expand_interpolation(vec![
+        b::segment(r"a \\"),
+        b::parens(vec![b::var("x")]),
+        b::segment(r" \\\(b \n"),
+    ])
+    .unwrap();
+
+    assert_eq!(
+        expr,
+        hir::Expression::interpolation(
+            vec![
+                synthetic_string(r"a \", Span::new(2, 6)),
+                hir::Expression::variable(Span::new(8, 9), Span::new(7, 9)),
+                synthetic_string(r" \(b \n", Span::new(10, 19)),
+            ],
+            Span::new(0, 20)
+        )
+    );
+}
+
+#[test]
+fn test_parse_unterminated_interpolation() {
+    let err = expand_interpolation(vec![b::segment("a "), b::segment("("), b::segment("$x b")])
+        .unwrap_err();
+
+    match err.reason() {
+        ParseErrorReason::Mismatch { expected, actual } => {
+            assert_eq!(*expected, "string interpolation");
+            assert_eq!(actual.span, Span::new(4, 5));
+        }
+        other => panic!("expected a mismatch error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_type_test() {
+    for (name, type_name) in &[("int", TypeName::Int), ("string", TypeName::String)] {
+        with_tokens(
+            vec![
+                b::var("it"),
+                b::op("."),
+                b::bare("value"),
+                b::sp(),
+                b::bare("is"),
+                b::sp(),
+                b::bare(*name),
+            ],
+            |iterator, context, _| {
+                let expr = match expand_expr(&AnyExpressionShape, iterator, &context) {
+                    Ok(expr) => expr,
+                    Err(err) => panic!("expected {:?} to parse, got {:?}", context.source(), err),
+                };
+
+                assert_eq!(expr.span, Span::new(0, context.source().len()));
+
+                match &expr.item {
+                    hir::RawExpression::TypeTest(test) => {
+                        assert_eq!(test.type_name().item, *type_name);
+                        assert_eq!(test.expr().span.slice(context.source()), "$it.value");
+                    }
+                    other => panic!("expected a type test, got {:?}", other),
+                }
+            },
+        );
+    }
+}
+
+#[test]
+fn test_parse_type_test_with_unknown_type() {
+    let err = parse_error(
+        ExpressionContinuationShape,
+        vec![b::sp(), b::bare("is"), b::sp(), b::bare("integr")],
+    );
+
+    match err.reason() {
+        ParseErrorReason::Mismatch { expected, actual } => {
+            assert_eq!(*expected, "type name");
+            assert_eq!(actual.item, "integr");
+        }
+        other => panic!("expected a mismatch error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_path_semantic_tokens() {
+    with_tokens(
+        vec![
+            b::var("it"),
+            b::op("."),
+            b::bare("a"),
+            b::op("."),
+            b::bare("0"),
+        ],
+        |iterator, context, tokens| {
+            let semantic = VariablePathShape.semantic_tokens(iterator, &context);
+            let token_type = |name| {
+                SEMANTIC_TOKEN_TYPES
+                    .iter()
+                    .position(|t| *t == name)
+                    .unwrap() as u32
+            };
+
+            assert_eq!(
+                semantic,
+                vec![
+                    SemanticToken {
+                        token_type: token_type("variable"),
+                        modifiers: 1,
+                        span: tokens[0].span(),
+                    },
+                    SemanticToken {
+                        token_type: token_type("operator"),
+                        modifiers: 0,
+                        span: tokens[1].span(),
+                    },
+                    SemanticToken {
+                        token_type: token_type("property"),
+                        modifiers: 0,
+                        span: tokens[2].span(),
+                    },
+                    SemanticToken {
+                        token_type: token_type("operator"),
+                        modifiers: 0,
+                        span: tokens[3].span(),
+                    },
+                    SemanticToken {
+                        token_type: token_type("property"),
+                        modifiers: 0,
+                        span: tokens[4].span(),
+                    },
+                ]
+            );
+        },
+    )
+}
+
+#[test]
+fn test_parse_entries_member() {
+    parse_tokens(
+        VariablePathShape,
+        vec![b::var("it"), b::op("."), b::bare("~")],
+        |tokens| {
+            let (outer_var, inner_var) = tokens[0].expect_var();
+            let tilde = tokens[2].expect_bare();
+
+            hir::Expression::path(
+                hir::Expression::it_variable(inner_var, outer_var),
+                vec![PathMember::entries(tilde)],
+                outer_var.until(tilde),
+            )
+        },
+    );
+
+    parse_tokens(
+        VariablePathShape,
+        vec![
+            b::var("it"),
+            b::op("."),
+            b::bare("~"),
+            b::op("."),
+            b::bare("key"),
+        ],
+        |tokens| {
+            let (outer_var, inner_var) = tokens[0].expect_var();
+            let tilde = tokens[2].expect_bare();
+            let key = tokens[4].expect_bare();
+
+            hir::Expression::path(
+                hir::Expression::it_variable(inner_var, outer_var),
+                vec![PathMember::entries(tilde), PathMember::string("key", key)],
+                outer_var.until(key),
+            )
+        },
+    );
+}
+
+#[test]
+fn test_entries_member_semantic_tokens() {
+    with_tokens(
+        vec![b::var("it"), b::op("."), b::bare("~")],
+        |iterator, context, tokens| {
+            let semantic = VariablePathShape.semantic_tokens(iterator, &context);
+            let property = SEMANTIC_TOKEN_TYPES
+                .iter()
+                .position(|t| *t == "property")
+                .unwrap() as u32;
+
+            assert_eq!(
+                semantic.last(),
+                Some(&SemanticToken {
+                    token_type: property,
+                    modifiers: 0,
+                    span: tokens[2].span(),
+                })
+            );
+        },
+    )
+}
+
+#[test]
+fn test_parse_wildcard_member() {
+    parse_tokens(
+        VariablePathShape,
+        vec![
+            b::var("x"),
+            b::op("."),
+            b::pattern("*"),
+            b::op("."),
+            b::bare("value"),
+        ],
+        |tokens| {
+            let (outer_var, inner_var) = tokens[0].expect_var();
+            let star = tokens[2].span();
+            let value = tokens[4].expect_bare();
+
+            hir::Expression::path(
+                hir::Expression::variable(inner_var, outer_var),
+                vec![
+                    PathMember::wildcard(star),
+                    PathMember::string("value", value),
+                ],
+                outer_var.until(value),
+            )
+        },
+    );
+}
+
+#[test]
+fn test_wildcard_member_semantic_tokens() {
+    with_tokens(
+        vec![
+            b::var("x"),
+            b::op("."),
+            b::pattern("*"),
+            b::op("."),
+            b::bare("value"),
+        ],
+        |iterator, context, tokens| {
+            let semantic = VariablePathShape.semantic_tokens(iterator, &context);
+            let property = SEMANTIC_TOKEN_TYPES
+                .iter()
+                .position(|t| *t == "property")
+                .unwrap() as u32;
+
+            // the `*` is a member, not a glob pattern
+            assert!(semantic.contains(&SemanticToken {
+                token_type: property,
+                modifiers: 0,
+                span: tokens[2].span(),
+            }));
+        },
+    )
+}
+
+#[test]
+fn test_comparison_operator_shape() {
+    with_tokens(
+        vec![b::var("a"), b::sp(), b::op(">"), b::sp(), b::var("b")],
+        |iterator, context, tokens| {
+            let shapes = color_shapes(AnyExpressionShape, iterator, &context);
+            let operator = shapes
+                .iter()
+                .find(|shape| shape.span == tokens[2].span())
+                .expect("the operator is colored");
+
+            match operator.item {
+                FlatShape::ComparisonOperator => {}
+                other => panic!("expected a comparison operator shape, got {:?}", other),
+            }
+        },
+    )
+}
+
+#[test]
+fn test_color_dangling_dot() {
+    with_tokens(
+        vec![b::var("x"), b::op(".")],
+        |iterator, context, tokens| {
+            let (variable, _) = tokens[0].expect_var();
+            let dot = tokens[1].span();
+
+            let mut shapes = color_shapes(VariableShape, iterator, &context);
+            shapes.extend(color_shapes(PathTailShape, iterator, &context));
+
+            match &shapes[..] {
+                [Spanned {
+                    item: FlatShape::Variable,
+                    span: variable_span,
+                }, Spanned {
+                    item: FlatShape::Dot,
+                    span: dot_span,
+                }] => {
+                    assert_eq!(*variable_span, variable);
+                    assert_eq!(*dot_span, dot);
+                }
+                other => panic!("expected a variable and a dot, got {:?}", other),
+            }
+
+            // the dangling dot was consumed along with its color
+            assert!(iterator.at_end());
+        },
+    )
+}
+
+#[test]
+fn test_color_variable_path_with_dangling_dot() {
+    with_tokens(
+        vec![b::var("config"), b::op(".")],
+        |iterator, context, tokens| {
+            let (variable, _) = tokens[0].expect_var();
+            let dot = tokens[1].span();
+
+            let shapes = color_shapes(VariablePathShape, iterator, &context);
+
+            match &shapes[..] {
+                [Spanned {
+                    item: FlatShape::Variable,
+                    span: variable_span,
+                }, Spanned {
+                    item: FlatShape::Dot,
+                    span: dot_span,
+                }] => {
+                    assert_eq!(*variable_span, variable);
+                    assert_eq!(*dot_span, dot);
+                }
+                other => panic!("expected a variable and a dot, got {:?}", other),
+            }
+
+            assert!(iterator.at_end());
+        },
+    )
+}
+
+#[test]
+fn test_color_int_member() {
+    with_tokens(
+        vec![b::var("list"), b::op("."), b::int(0)],
+        |iterator, context, tokens| {
+            let shapes = color_shapes(VariablePathShape, iterator, &context);
+            let index = shapes
+                .iter()
+                .find(|shape| shape.span == tokens[2].span())
+                .expect("the index is colored");
+
+            match index.item {
+                FlatShape::IntMember => {}
+                other => panic!("expected an integer member shape, got {:?}", other),
+            }
+        },
+    )
+}
+
+#[test]
+fn test_color_optional_member() {
+    with_tokens(
+        vec![b::var("x"), b::op("."), b::bare("name"), b::bare("?")],
+        |iterator, context, tokens| {
+            let (variable, _) = tokens[0].expect_var();
+            let dot = tokens[1].span();
+            let name = tokens[2].span();
+            let question = tokens[3].span();
+
+            let shapes = color_shapes(VariablePathShape, iterator, &context);
+
+            match &shapes[..] {
+                [Spanned {
+                    item: FlatShape::Variable,
+                    span: variable_span,
+                }, Spanned {
+                    item: FlatShape::Dot,
+                    span: dot_span,
+                }, Spanned {
+                    item: FlatShape::BareMember,
+                    span: name_span,
+                }, Spanned {
+                    item: FlatShape::QuestionMark,
+                    span: question_span,
+                }] => {
+                    assert_eq!(*variable_span, variable);
+                    assert_eq!(*dot_span, dot);
+                    assert_eq!(*name_span, name);
+                    assert_eq!(*question_span, question);
+                }
+                other => panic!("expected a path with a question mark, got {:?}", other),
+            }
+
+            assert!(iterator.at_end());
+        },
+    )
+}
+
+#[test]
+fn test_parse_range_head() {
+    parse_tokens(
+        PrimaryExpressionShape,
+        vec![
+            b::parens(vec![b::bare("1"), b::op("."), b::op("."), b::bare("5")]),
+            b::op("."),
+            b::int(0),
+        ],
+        |tokens| {
+            let (parens, from, dotdot, to) = expect_range(&tokens[0]);
+            let int = tokens[2].span();
+
+            hir::Expression::path(
+                hir::Expression::range(
+                    hir::Expression::number(BigInt::from(1), from),
+                    dotdot,
+                    hir::Expression::number(BigInt::from(5), to),
+                    parens,
+                ),
+                vec![PathMember::int(0, int)],
+                parens.until(int),
+            )
+        },
+    );
+
+    parse_tokens(
+        AnyExpressionShape,
+        vec![
+            b::parens(vec![b::bare("1"), b::op("."), b::op("."), b::bare("5")]),
+            b::op("."),
+            b::bare("length"),
+        ],
+        |tokens| {
+            let (parens, from, dotdot, to) = expect_range(&tokens[0]);
+            let length = tokens[2].expect_bare();
+
+            hir::Expression::path(
+                hir::Expression::range(
+                    hir::Expression::number(BigInt::from(1), from),
+                    dotdot,
+                    hir::Expression::number(BigInt::from(5), to),
+                    parens,
+                ),
+                vec![PathMember::string("length", length)],
+                parens.until(length),
+            )
+        },
+    );
+}
+
+#[test]
+fn test_parse_range_expression() {
+    parse_tokens(
+        AnyExpressionShape,
+        vec![b::bare("1"), b::op("."), b::op("."), b::bare("10")],
+        |tokens| {
+            let from = tokens[0].expect_bare();
+            let dotdot = tokens[1].span().until(tokens[2].span());
+            let to = tokens[3].expect_bare();
+
+            hir::Expression::range(
+                hir::Expression::number(BigInt::from(1), from),
+                dotdot,
+                hir::Expression::number(BigInt::from(10), to),
+                from.until(to),
+            )
+        },
+    );
+
+    parse_tokens(
+        AnyExpressionShape,
+        vec![
+            b::bare("1"),
+            b::op("."),
+            b::op("."),
+            b::bare("="),
+            b::bare("10"),
+        ],
+        |tokens| {
+            let from = tokens[0].expect_bare();
+            let dotdot = tokens[1].span().until(tokens[2].span());
+            let to = tokens[4].expect_bare();
+
+            hir::Expression::range_with_bounds(
+                Some(hir::Expression::number(BigInt::from(1), from)),
+                dotdot,
+                Some(hir::Expression::number(BigInt::from(10), to)),
+                true,
+                from.until(to),
+            )
+        },
+    );
+
+    parse_tokens(
+        AnyExpressionShape,
+        vec![b::op("."), b::op("."), b::bare("5")],
+        |tokens| {
+            let dotdot = tokens[0].span().until(tokens[1].span());
+            let to = tokens[2].expect_bare();
+
+            hir::Expression::range_with_bounds(
+                None,
+                dotdot,
+                Some(hir::Expression::number(BigInt::from(5), to)),
+                false,
+                dotdot.until(to),
+            )
+        },
+    );
+
+    parse_tokens(
+        AnyExpressionShape,
+        vec![b::var("a"), b::op("."), b::op("."), b::var("b")],
+        |tokens| {
+            let (from_outer, from_inner) = tokens[0].expect_var();
+            let dotdot = tokens[1].span().until(tokens[2].span());
+            let (to_outer, to_inner) = tokens[3].expect_var();
+
+            hir::Expression::range(
+                hir::Expression::variable(from_inner, from_outer),
+                dotdot,
+                hir::Expression::variable(to_inner, to_outer),
+                from_outer.until(to_outer),
+            )
+        },
+    );
+}
+
+#[test]
+fn test_parse_open_range_expression() {
+    // `5..` would be evaluated into an endless table
+    let err = parse_error(
+        AnyExpressionShape,
+        vec![b::bare("5"), b::op("."), b::op(".")],
+    );
+
+    match err.reason() {
+        ParseErrorReason::Mismatch { expected, actual } => {
+            assert_eq!(*expected, "range with an upper bound");
+            assert_eq!(actual.span, Span::new(0, 3));
+        }
+        other => panic!("expected a mismatch, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_evaluate_range_expression() {
+    let evaluate = |to: i64| {
+        let expr = hir::Expression::range(
+            hir::Expression::number(BigInt::from(0), Span::unknown()),
+            Span::unknown(),
+            hir::Expression::number(BigInt::from(to), Span::unknown()),
+            Span::unknown(),
+        );
+
+        evaluate_baseline_expr(
+            &expr,
+            &CommandRegistry::empty(),
+            &Scope::empty(),
+            &Text::from(""),
+        )
+    };
+
+    assert_eq!(
+        table_items(evaluate(3).unwrap()),
+        vec![Value::int(0), Value::int(1), Value::int(2)]
+    );
+
+    // too big to materialize
+    assert!(evaluate(10_000_000_000).is_err());
+}
+
+#[test]
+fn test_parse_dotted_word_is_not_a_range() {
+    parse_tokens(
+        AnyExpressionShape,
+        vec![b::bare("5"), b::op("."), b::op("."), b::bare("name")],
+        |tokens| hir::Expression::bare(tokens[0].span().until(tokens[3].span())),
+    );
+}
+
+#[test]
+fn test_parse_lenient_path() {
+    parse_tokens(
+        VariablePathShape,
+        vec![
+            b::var("it"),
+            b::op("."),
+            b::bare("a"),
+            b::op("."),
+            b::bare("b"),
+            b::op("."),
+            b::pattern("?"),
+        ],
+        |tokens| {
+            let (outer_var, inner_var) = tokens[0].expect_var();
+            let a = tokens[2].expect_bare();
+            let b = tokens[4].expect_bare();
+            let question = tokens[6].expect_pattern();
+
+            hir::Expression::lenient_path(
+                hir::Expression::it_variable(inner_var, outer_var),
+                vec![PathMember::string("a", a), PathMember::string("b", b)],
+                outer_var.until(question),
+            )
+        },
+    );
+}
+
+#[test]
+fn test_parse_escaped_dot_member() {
+    parse_tokens(
+        MemberShape,
+        vec![b::bare(r"a\"), b::op("."), b::bare("b")],
+        |tokens| Member::Bare(tokens[0].expect_bare().until(tokens[2].expect_bare())),
+    );
+
+    parse_tokens(
+        VariablePathShape,
+        vec![
+            b::var("record"),
+            b::op("."),
+            b::bare(r"a\"),
+            b::op("."),
+            b::bare("b"),
+        ],
+        |tokens| {
+            let (outer_var, inner_var) = tokens[0].expect_var();
+            let member = tokens[2].expect_bare().until(tokens[4].expect_bare());
+
+            hir::Expression::path(
+                hir::Expression::variable(inner_var, outer_var),
+                vec![PathMember::string("a.b", member)],
+                outer_var.until(member),
+            )
+        },
+    );
+
+    // without the backslash, it's two members
+    parse_tokens(
+        VariablePathShape,
+        vec![
+            b::var("record"),
+            b::op("."),
+            b::bare("a"),
+            b::op("."),
+            b::bare("b"),
+        ],
+        |tokens| {
+            let (outer_var, inner_var) = tokens[0].expect_var();
+            let a = tokens[2].expect_bare();
+            let b = tokens[4].expect_bare();
+
+            hir::Expression::path(
+                hir::Expression::variable(inner_var, outer_var),
+                vec![PathMember::string("a", a), PathMember::string("b", b)],
+                outer_var.until(b),
+            )
+        },
+    );
+}
+
+#[test]
+fn test_parse_glob_member() {
+    parse_tokens(MemberShape, vec![b::pattern("user_*")], |tokens| {
+        Member::Glob(tokens[0].expect_pattern())
+    });
+
+    parse_tokens(MemberShape, vec![b::bare("it?m")], |tokens| {
+        Member::Glob(tokens[0].expect_bare())
+    });
+
+    // without a wildcard, it's an ordinary word
+    parse_tokens(MemberShape, vec![b::bare("user")], |tokens| {
+        Member::Bare(tokens[0].expect_bare())
+    });
+
+    parse_tokens(
+        VariablePathShape,
+        vec![b::var("table"), b::op("."), b::pattern("user_*")],
+        |tokens| {
+            let (outer_var, inner_var) = tokens[0].expect_var();
+            let glob = tokens[2].expect_pattern();
+
+            hir::Expression::path(
+                hir::Expression::variable(inner_var, outer_var),
+                vec![PathMember::glob("user_*", glob)],
+                outer_var.until(glob),
+            )
+        },
+    );
+}
+
+#[test]
+fn test_parse_optional_member_is_not_a_glob() {
+    // the `?` in `item?` marks the member, so `item` is looked up as it's written
+    parse_tokens(
+        VariablePathShape,
+        vec![b::var("table"), b::op("."), b::bare("item"), b::bare("?")],
+        |tokens| {
+            let (outer_var, inner_var) = tokens[0].expect_var();
+            let item = tokens[2].expect_bare();
+            let optional = tokens[3].expect_bare();
+
+            hir::Expression::path(
+                hir::Expression::variable(inner_var, outer_var),
+                vec![PathMember::string("item", item.until(optional))],
+                outer_var.until(optional),
+            )
+            .with_markers(vec![Some(MemberMarker::Optional)])
+        },
+    );
+}
+
+#[test]
+fn test_parse_member_markers() {
+    parse_tokens(
+        VariablePathShape,
+        vec![
+            b::var("it"),
+            b::op("."),
+            b::bare("a"),
+            b::bare("?"),
+            b::op("."),
+            b::bare("b"),
+            b::bare("!"),
+            b::op("."),
+            b::bare("c"),
+        ],
+        |tokens| {
+            let (outer_var, inner_var) = tokens[0].expect_var();
+            let first = tokens[2].expect_bare();
+            let optional = tokens[3].expect_bare();
+            let second = tokens[5].expect_bare();
+            let required = tokens[6].expect_bare();
+            let third = tokens[8].expect_bare();
+
+            hir::Expression::path(
+                hir::Expression::it_variable(inner_var, outer_var),
+                vec![
+                    PathMember::string("a", first.until(optional)),
+                    PathMember::string("b", second.until(required)),
+                    PathMember::string("c", third),
+                ],
+                outer_var.until(third),
+            )
+            .with_markers(vec![
+                Some(MemberMarker::Optional),
+                Some(MemberMarker::Required),
+                None,
+            ])
+        },
+    );
+}
+
+#[test]
+fn test_parse_marker_before_index() {
+    let err = parse_error(
+        VariablePathShape,
+        vec![
+            b::var("it"),
+            b::op("."),
+            b::bare("a"),
+            b::bare("?"),
+            b::square(vec![b::int(0)]),
+        ],
+    );
+
+    match err.reason() {
+        // the `?`
+        ParseErrorReason::AmbiguousMarker { span } => assert_eq!(*span, Span::new(5, 6)),
+        other => panic!("expected an ambiguous marker error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_marker_after_index() {
+    parse_tokens(
+        VariablePathShape,
+        vec![
+            b::var("it"),
+            b::op("."),
+            b::bare("a"),
+            b::square(vec![b::int(0)]),
+            b::bare("?"),
+        ],
+        |tokens| {
+            let (outer_var, inner_var) = tokens[0].expect_var();
+            let member = tokens[2].expect_bare();
+            let index = tokens[3].span();
+            let optional = tokens[4].expect_bare();
+
+            hir::Expression::path(
+                hir::Expression::it_variable(inner_var, outer_var),
+                vec![
+                    PathMember::string("a", member),
+                    PathMember::int(0, index.until(optional)),
+                ],
+                outer_var.until(optional),
+            )
+            .with_markers(vec![None, Some(MemberMarker::Optional)])
+        },
+    );
+
+    parse_tokens(
+        VariablePathShape,
+        vec![
+            b::var("it"),
+            b::op("."),
+            b::bare("a"),
+            b::bare("?"),
+            b::op("."),
+            b::bare("b"),
+        ],
+        |tokens| {
+            let (outer_var, inner_var) = tokens[0].expect_var();
+            let first = tokens[2].expect_bare();
+            let optional = tokens[3].expect_bare();
+            let second = tokens[5].expect_bare();
+
+            hir::Expression::path(
+                hir::Expression::it_variable(inner_var, outer_var),
+                vec![
+                    PathMember::string("a", first.until(optional)),
+                    PathMember::string("b", second),
+                ],
+                outer_var.until(second),
+            )
+            .with_markers(vec![Some(MemberMarker::Optional), None])
+        },
+    );
+}
+
+#[test]
+fn test_expand_expr_or_rolls_back() {
+    // $x..y, which fails as a variable path at the second dot
+    with_tokens(
+        vec![b::var("x"), b::op("."), b::op("."), b::bare("y")],
+        |iterator, context, tokens| {
+            let expr = expand_expr_or(&VariablePathShape, iterator, &context, |iterator| {
+                let next = iterator.peek_any().not_eof("variable")?.commit();
+                Ok(hir::Expression::bare(next.span()))
+            });
+
+            assert_eq!(expr.unwrap(), hir::Expression::bare(tokens[0].span()));
+        },
+    )
+}
+
+#[test]
+fn test_parse_chained_optional_members() {
+    // $a.b?.c.d?
+    let tokens = vec![
+        b::var("a"),
+        b::op("."),
+        b::bare("b"),
+        b::bare("?"),
+        b::op("."),
+        b::bare("c"),
+        b::op("."),
+        b::bare("d"),
+        b::bare("?"),
+    ];
+
+    let expr = parse_tokens(VariablePathShape, tokens, |tokens| {
+        let (outer_var, inner_var) = tokens[0].expect_var();
+        let b_member = tokens[2].expect_bare();
+        let b_optional = tokens[3].expect_bare();
+        let c_member = tokens[5].expect_bare();
+        let d_member = tokens[7].expect_bare();
+        let d_optional = tokens[8].expect_bare();
+
+        hir::Expression::path(
+            hir::Expression::variable(inner_var, outer_var),
+            vec![
+                PathMember::string("b", b_member.until(b_optional)),
+                PathMember::string("c", c_member),
+                PathMember::string("d", d_member.until(d_optional)),
+            ],
+            outer_var.until(d_optional),
+        )
+        .with_markers(vec![
+            Some(MemberMarker::Optional),
+            None,
+            Some(MemberMarker::Optional),
+        ])
+    });
+
+    match &expr.item {
+        hir::RawExpression::Path(path) => {
+            assert!(path.short_circuits_at(0));
+            assert!(!path.short_circuits_at(1));
+            assert!(path.short_circuits_at(2));
+        }
+        other => panic!("expected a path, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_evaluate_chained_optional_members() {
+    // $it.b?.c
+    let tokens = || {
+        vec![
+            b::var("it"),
+            b::op("."),
+            b::bare("b"),
+            b::bare("?"),
+            b::op("."),
+            b::bare("c"),
+        ]
+    };
+
+    // a missing `b` skips the rest of the path
+    let it = Value::row(indexmap! {});
+
+    assert_eq!(
+        evaluate_tokens(tokens(), it.tagged_unknown()).unwrap().item,
+        Value::nothing()
+    );
+
+    // but once `b` is there, `c` has to be too
+    let it = Value::row(indexmap! {
+        "b".into() => Value::row(indexmap! {}).tagged_unknown()
+    });
+
+    assert!(evaluate_tokens(tokens(), it.tagged_unknown()).is_err());
+}
+
+#[test]
+fn test_parse_rooted_path() {
+    for (member, rooted) in &[("/a/b", true), ("a/b", false)] {
+        with_tokens(
+            vec![b::var("it"), b::op("."), b::bare(*member)],
+            |iterator, context, _| {
+                let context = context.with_path_separator('/');
+
+                let expr = match expand_expr(&VariablePathShape, iterator, &context) {
+                    Ok(expr) => expr,
+                    Err(err) => panic!("expected {:?} to parse, got {:?}", context.source(), err),
+                };
+
+                match &expr.item {
+                    hir::RawExpression::Path(path) => {
+                        assert_eq!(path.rooted(), rooted);
+
+                        let members: Vec<&str> = path
+                            .tail()
+                            .iter()
+                            .map(|member| member.span.slice(context.source()))
+                            .collect();
+                        assert_eq!(members, vec!["a", "b"]);
+                    }
+                    other => panic!("expected a path, got {:?}", other),
+                }
+            },
+        );
+    }
+}
+
+#[test]
+fn test_parse_small_and_big_int_members() {
+    let huge = BigInt::from_str("99999999999999999999").unwrap();
+
+    for (index, expected) in &[
+        (BigInt::from(3), RawPathMember::SmallInt(3)),
+        (huge.clone(), RawPathMember::Int(huge.clone())),
+    ] {
+        with_tokens(
+            vec![b::var("x"), b::op("."), b::int(index.clone())],
+            |iterator, context, _| {
+                let expr = expand_expr(&VariablePathShape, iterator, &context).unwrap();
+
+                match &expr.item {
+                    hir::RawExpression::Path(path) => assert_eq!(path.tail()[0].item, *expected),
+                    other => panic!("expected a path, got {:?}", other),
+                }
+            },
+        );
+    }
+}
+
+#[test]
+fn test_build_column_path() {
+    let built = ColumnPath::build(vec![
+        PathMember::bare_from("a"),
+        PathMember::int(0, Span::unknown()),
+        PathMember::bare_from("b"),
+    ]);
+
+    let parsed = with_tokens(
+        vec![
+            b::bare("a"),
+            b::op("."),
+            b::int(0),
+            b::op("."),
+            b::bare("b"),
+        ],
+        |iterator, context, _| {
+            let members = expand_syntax(&ColumnPathShape, iterator, &context).unwrap();
+
+            ColumnPath::build(
+                members
+                    .iter()
+                    .map(|member| member.to_path_member(context.source()))
+                    .collect(),
+            )
+        },
+    );
+
+    let items = |path: &ColumnPath| path.iter().map(|m| m.item.clone()).collect::<Vec<_>>();
+
+    assert_eq!(items(&built), items(&parsed));
+}
+
+#[test]
+fn test_column_path_lookahead() {
+    with_tokens(
+        vec![b::bare("a"), b::op("."), b::bare("b")],
+        |iterator, context, tokens| {
+            let first = &tokens[0];
+
+            let peeked = ColumnPathShape.test(iterator, &context);
+            assert_eq!(peeked.map(|peeked| peeked.node), Some(Some(first)));
+            assert_eq!(iterator.pos(false), Some(0));
+
+            // the probe doesn't get in the way of expanding the path for real
+            let path = expand_syntax(&ColumnPathShape, iterator, &context).unwrap();
+            assert_eq!(path.len(), 2);
+        },
+    );
+
+    with_tokens(vec![b::op("."), b::bare("name")], |iterator, context, _| {
+        assert!(ColumnPathShape.test(iterator, &context).is_none());
+        assert_eq!(iterator.pos(false), Some(0));
+    });
+}
+
+#[test]
+fn test_parse_leading_dot_is_strict_by_default() {
+    let err = parse_error(ColumnPathShape, vec![b::op("."), b::bare("name")]);
+
+    match err.reason() {
+        ParseErrorReason::Mismatch { expected, .. } => assert_eq!(*expected, "column path"),
+        other => panic!("expected a mismatch error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_relative_column_path() {
+    with_tokens(
+        vec![b::op("."), b::bare("name"), b::op("."), b::bare("first")],
+        |iterator, context, tokens| {
+            let context = context.with_relative_column_paths();
+            let dot = tokens[0].span();
+            let name = tokens[1].expect_bare();
+            let first = tokens[3].expect_bare();
+
+            match expand_syntax(&ColumnPathShape, iterator, &context) {
+                Ok(path) => assert_eq!(
+                    path,
+                    ColumnPathMembers::from_vec(vec![Member::Bare(name), Member::Bare(first)])
+                        .tagged(dot.until(first))
+                ),
+                Err(err) => panic!("expected {:?} to parse, got {:?}", context.source(), err),
+            }
+
+            let mut iterator = TokensIterator::all(tokens, tokens.span);
+
+            match expand_relative_column_path(iterator, &context) {
+                Ok(expr) => assert_eq!(
+                    expr,
+                    hir::Expression::path(
+                        hir::Expression::it_variable(dot, dot),
+                        vec![
+                            PathMember::string("name", name),
+                            PathMember::string("first", first)
+                        ],
+                        dot.until(first),
+                    )
+                ),
+                Err(err) => panic!("expected {:?} to parse, got {:?}", context.source(), err),
+            }
+        },
+    );
+}
+
+#[test]
+fn test_parse_lenient_marker_in_middle() {
+    let err = parse_error(
+        VariablePathShape,
+        vec![
+            b::var("it"),
+            b::op("."),
+            b::bare("a"),
+            b::op("."),
+            b::pattern("?"),
+            b::op("."),
+            b::bare("b"),
+        ],
+    );
+
+    match err.reason() {
+        ParseErrorReason::Mismatch { expected, actual } => {
+            assert_eq!(*expected, "end of path after `.?`");
+            assert_eq!(actual.item, "dot");
+        }
+        other => panic!("expected a mismatch error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_unknown_column_suggestion() {
+    let known = vec!["name".to_string(), "email".to_string()];
+
+    let warnings = parse_warnings(&known, vec![b::bare("nme")]);
+
+    match &warnings[..] {
+        [warning] => match warning.reason() {
+            ParseWarningReason::UnknownColumn { name, suggestion } => {
+                assert_eq!(name.item, "nme");
+                assert_eq!(suggestion.as_ref().map(|s| &s[..]), Some("name"));
+            }
+            other => panic!("expected an unknown column warning, got {:?}", other),
+        },
+        other => panic!("expected one warning, got {:?}", other),
+    }
+
+    let warnings = parse_warnings(&known, vec![b::bare("address")]);
+
+    match &warnings[..] {
+        [warning] => match warning.reason() {
+            ParseWarningReason::UnknownColumn { suggestion, .. } => {
+                assert_eq!(*suggestion, None);
+            }
+            other => panic!("expected an unknown column warning, got {:?}", other),
+        },
+        other => panic!("expected one warning, got {:?}", other),
+    }
+
+    assert_eq!(parse_warnings(&known, vec![b::bare("email")]), vec![]);
+}
+
+#[test]
+fn test_unknown_column_suggestion_for_swapped_letters() {
+    let known = vec!["name".to_string(), "email".to_string()];
+
+    let warnings = parse_warnings(&known, vec![b::bare("nmae")]);
+
+    match &warnings[..] {
+        [warning] => match warning.reason() {
+            ParseWarningReason::UnknownColumn { name, suggestion } => {
+                assert_eq!(name.item, "nmae");
+                assert_eq!(suggestion.as_ref().map(|s| &s[..]), Some("name"));
+            }
+            other => panic!("expected an unknown column warning, got {:?}", other),
+        },
+        other => panic!("expected one warning, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_column_error_suggestion() {
+    let known = vec!["name".to_string(), "email".to_string()];
+
+    let err = parse_column_error(&known, vec![b::external_word("nmae")]);
+
+    match err.reason() {
+        ParseErrorReason::ColumnMismatch { actual, suggestion } => {
+            assert_eq!(actual.item, "nmae");
+            assert_eq!(actual.span, Span::new(0, 4));
+            assert_eq!(suggestion, "name");
+        }
+        other => panic!("expected a column mismatch error, got {:?}", other),
+    }
+
+    // without a close match, it's the usual mismatch
+    let err = parse_column_error(&known, vec![b::external_word("zzzz")]);
+
+    match err.reason() {
+        ParseErrorReason::Mismatch { expected, .. } => assert_eq!(*expected, "column"),
+        other => panic!("expected a mismatch error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_reserved_member_warning() {
+    let reserved = vec!["__proto__".to_string(), "constructor".to_string()];
+
+    for token in vec![b::bare("__proto__"), b::string("constructor")] {
+        let warnings = parse_reserved_warnings(Some(&reserved), vec![token]);
+
+        match &warnings[..] {
+            [warning] => match warning.reason() {
+                ParseWarningReason::ReservedMember { name } => {
+                    assert!(reserved.contains(&name.item));
+                }
+                other => panic!("expected a reserved member warning, got {:?}", other),
+            },
+            other => panic!("expected one warning, got {:?}", other),
+        }
+    }
+
+    assert_eq!(
+        parse_reserved_warnings(Some(&reserved), vec![b::bare("name")]),
+        vec![]
+    );
+
+    // reserved members aren't checked unless the context asks for it
+    assert_eq!(
+        parse_reserved_warnings(None, vec![b::bare("__proto__")]),
+        vec![]
+    );
+}
+
+#[test]
+fn test_deprecated_member_warning() {
+    let warnings = RefCell::new(vec![]);
+    let deprecated = [MemberKind::ColumnNumber];
+
+    with_tokens(
+        vec![b::var("it"), b::op("."), b::bare("#3")],
+        |iterator, context, tokens| {
+            let context = context
+                .with_warnings(&warnings)
+                .with_deprecated_members(&deprecated);
+            let column = tokens[2].expect_bare();
+
+            // the member still parses
+            if let Err(err) = expand_expr(&VariablePathShape, iterator, &context) {
+                panic!("expected the parse to succeed, got {:?}", err);
+            }
+
+            assert_eq!(
+                warnings.borrow()[..],
+                [ParseWarning::deprecated_member(
+                    "column number".spanned(column)
+                )]
+            );
+        },
+    );
+
+    // other member forms aren't affected
+    let warnings = RefCell::new(vec![]);
+
+    with_tokens(
+        vec![b::var("it"), b::op("."), b::bare("name")],
+        |iterator, context, _| {
+            let context = context
+                .with_warnings(&warnings)
+                .with_deprecated_members(&deprecated);
+
+            expand_expr(&VariablePathShape, iterator, &context).unwrap();
+            assert_eq!(warnings.borrow().len(), 0);
+        },
+    );
+}
+
+#[test]
+fn test_warnings_roll_back_with_checkpoint() {
+    let known = vec!["name".to_string()];
+    let warnings = RefCell::new(vec![]);
+
+    with_tokens(vec![b::bare("nme")], |iterator, context, _| {
+        let context = context.with_known_columns(&known).with_warnings(&warnings);
+
+        {
+            let checkpoint = iterator.checkpoint(&context);
+            expand_syntax(&MemberShape, checkpoint.iterator, &context).unwrap();
+            assert_eq!(warnings.borrow().len(), 1);
+        }
+
+        // the checkpoint wasn't committed, so its warning is gone
+        assert_eq!(warnings.borrow().len(), 0);
+
+        // looking ahead doesn't leave a warning behind either, so expanding the path for
+        // real warns just once
+        assert!(ColumnPathShape.test(iterator, &context).is_some());
+        expand_syntax(&ColumnPathShape, iterator, &context).unwrap();
+        assert_eq!(warnings.borrow().len(), 1);
+    });
+}
+
+#[test]
+fn test_parse_renamed_it_variable() {
+    let parse_var = |name: &str| {
+        with_tokens(vec![b::var(name)], |iterator, context, tokens| {
+            let context = context.with_it_variable("row");
+            let (outer, inner) = tokens[0].expect_var();
+
+            let expr = expand_expr(&VariableShape, iterator, &context).unwrap();
+            (expr, outer, inner)
+        })
+    };
+
+    // `$row` takes the place of `$it`
+    let (expr, outer, inner) = parse_var("row");
+    assert_eq!(expr, hir::Expression::it_variable(inner, outer));
+
+    // and `$it` is an ordinary variable
+    let (expr, outer, inner) = parse_var("it");
+    assert_eq!(expr, hir::Expression::variable(inner, outer));
+}
+
+#[test]
+fn test_parse_malformed_unicode_escape_in_member() {
+    let err = parse_error(MemberShape, vec![b::string(r"\u{GGG}")]);
+
+    match err.reason() {
+        ParseErrorReason::Mismatch { expected, actual } => {
+            assert_eq!(*expected, "unicode escape");
+            assert_eq!(actual.item, r"\u{GGG}");
+            assert_eq!(actual.span, Span::new(1, 8));
+        }
+        other => panic!("expected a mismatch error, got {:?}", other),
+    }
+
+    let err = parse_error(MemberShape, vec![b::string(r"ab\u{GGG}")]);
+
+    match err.reason() {
+        ParseErrorReason::Mismatch { expected, actual } => {
+            assert_eq!(*expected, "unicode escape");
+            assert_eq!(actual.item, r"\u{GGG}");
+            assert_eq!(actual.span, Span::new(3, 10));
+        }
+        other => panic!("expected a mismatch error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_backtick_member() {
+    for name in &["weird name", "a.b", r"a\u{41}"] {
+        parse_tokens(
+            VariablePathShape,
+            vec![b::var("it"), b::op("."), b::backtick_string(*name)],
+            |tokens| {
+                let (outer_var, inner_var) = tokens[0].expect_var();
+                let (outer_member, _) = tokens[2].expect_string();
+
+                hir::Expression::path(
+                    hir::Expression::it_variable(inner_var, outer_var),
+                    vec![PathMember::string(*name, outer_member)],
+                    outer_var.until(outer_member),
+                )
+            },
+        );
+    }
+}
+
+#[test]
+fn test_parse_condition() {
+    parse_tokens(
+        ConditionShape,
+        vec![b::bare("size"), b::sp(), b::op(">"), b::sp(), b::int(10)],
+        |tokens| {
+            let size = tokens[0].expect_bare();
+            let op = tokens[2].span();
+            let ten = tokens[4].span();
+
+            hir::Expression::infix(
+                hir::Expression::path(
+                    hir::Expression::it_variable(Span::unknown(), Span::unknown()),
+                    vec![PathMember::string("size", size)],
+                    size,
+                ),
+                Operator::GreaterThan.spanned(op),
+                hir::Expression::number(BigInt::from(10), ten),
+            )
+        },
+    );
+
+    parse_tokens(
+        ConditionShape,
+        vec![
+            b::bare("name"),
+            b::sp(),
+            b::op("=="),
+            b::sp(),
+            b::string("x"),
+        ],
+        |tokens| {
+            let name = tokens[0].expect_bare();
+            let op = tokens[2].span();
+            let (outer, inner) = tokens[4].expect_string();
+
+            hir::Expression::infix(
+                hir::Expression::path(
+                    hir::Expression::it_variable(Span::unknown(), Span::unknown()),
+                    vec![PathMember::string("name", name)],
+                    name,
+                ),
+                Operator::Equal.spanned(op),
+                hir::Expression::string(inner, outer),
+            )
+        },
+    );
+}
+
+#[test]
+fn test_parse_modulo_condition() {
+    // where size % 2 == 0
+    with_tokens(
+        vec![
+            b::bare("size"),
+            b::sp(),
+            b::op("%"),
+            b::sp(),
+            b::int(2),
+            b::sp(),
+            b::op("=="),
+            b::sp(),
+            b::int(0),
+        ],
+        |iterator, context, tokens| {
+            let expr = expand_expr(&ConditionShape, iterator, &context).expect("parse failed");
+
+            let size = tokens[0].expect_bare();
+            let modulo = tokens[2].span();
+            let two = tokens[4].span();
+            let eq = tokens[6].span();
+            let zero = tokens[8].span();
+
+            assert_eq!(
+                expr,
+                hir::Expression::infix(
+                    hir::Expression::infix(
+                        hir::Expression::path(
+                            hir::Expression::it_variable(Span::unknown(), Span::unknown()),
+                            vec![PathMember::string("size", size)],
+                            size,
+                        ),
+                        Operator::Mod.spanned(modulo),
+                        hir::Expression::number(BigInt::from(2), two),
+                    ),
+                    Operator::Equal.spanned(eq),
+                    hir::Expression::number(BigInt::from(0), zero),
+                )
+            );
+
+            assert!(expr.operators_used().contains(&Operator::Mod));
+        },
+    )
+}
+
+#[test]
+fn test_parse_infix_precedence() {
+    // A lone `*` lexes as a glob
+    parse_tokens(
+        AnyExpressionShape,
+        vec![
+            b::int(1),
+            b::sp(),
+            b::op("+"),
+            b::sp(),
+            b::int(2),
+            b::sp(),
+            b::pattern("*"),
+            b::sp(),
+            b::int(3),
+        ],
+        |tokens| {
+            let one = tokens[0].span();
+            let plus = tokens[2].span();
+            let two = tokens[4].span();
+            let times = tokens[6].span();
+            let three = tokens[8].span();
+
+            hir::Expression::infix(
+                hir::Expression::number(BigInt::from(1), one),
+                Operator::Plus.spanned(plus),
+                hir::Expression::infix(
+                    hir::Expression::number(BigInt::from(2), two),
+                    Operator::Multiply.spanned(times),
+                    hir::Expression::number(BigInt::from(3), three),
+                ),
+            )
+        },
+    );
+
+    parse_tokens(
+        AnyExpressionShape,
+        vec![
+            b::int(2),
+            b::sp(),
+            b::pattern("*"),
+            b::sp(),
+            b::int(3),
+            b::sp(),
+            b::op("+"),
+            b::sp(),
+            b::int(4),
+        ],
+        |tokens| {
+            let two = tokens[0].span();
+            let times = tokens[2].span();
+            let three = tokens[4].span();
+            let plus = tokens[6].span();
+            let four = tokens[8].span();
+
+            hir::Expression::infix(
+                hir::Expression::infix(
+                    hir::Expression::number(BigInt::from(2), two),
+                    Operator::Multiply.spanned(times),
+                    hir::Expression::number(BigInt::from(3), three),
+                ),
+                Operator::Plus.spanned(plus),
+                hir::Expression::number(BigInt::from(4), four),
+            )
+        },
+    );
+}
+
+#[test]
+fn test_infix_precedence_keeps_parenthesized_left_whole() {
+    // (1 + 2) * 3
+    let sum = hir::Expression::infix(
+        hir::Expression::number(BigInt::from(1), Span::new(1, 2)),
+        Operator::Plus.spanned(Span::new(3, 4)),
+        hir::Expression::number(BigInt::from(2), Span::new(5, 6)),
+    );
+    let times = Operator::Multiply.spanned(Span::new(8, 9));
+    let three = hir::Expression::number(BigInt::from(3), Span::new(10, 11));
+
+    let grouped = hir::Expression::parenthesized(sum.clone(), Span::new(0, 7));
+
+    assert_eq!(
+        hir::Expression::infix_by_precedence(grouped.clone(), times, three.clone()),
+        hir::Expression::infix(grouped, times, three.clone())
+    );
+
+    // without the parentheses, the same chain folds the `*` into the `+`
+    assert_eq!(
+        hir::Expression::infix_by_precedence(sum, times, three.clone()),
+        hir::Expression::infix(
+            hir::Expression::number(BigInt::from(1), Span::new(1, 2)),
+            Operator::Plus.spanned(Span::new(3, 4)),
+            hir::Expression::infix(
+                hir::Expression::number(BigInt::from(2), Span::new(5, 6)),
+                times,
+                three
+            ),
+        )
+    );
+}
+
+#[test]
+fn test_parse_logical_operators() {
+    parse_tokens(
+        AnyExpressionShape,
+        vec![
+            b::var("a"),
+            b::sp(),
+            b::op(">"),
+            b::sp(),
+            b::int(1),
+            b::sp(),
+            b::op("&&"),
+            b::sp(),
+            b::var("b"),
+            b::sp(),
+            b::op("<"),
+            b::sp(),
+            b::int(2),
+        ],
+        |tokens| {
+            let (a_outer, a_inner) = tokens[0].expect_var();
+            let gt = tokens[2].span();
+            let one = tokens[4].span();
+            let and = tokens[6].span();
+            let (b_outer, b_inner) = tokens[8].expect_var();
+            let lt = tokens[10].span();
+            let two = tokens[12].span();
+
+            hir::Expression::infix(
+                hir::Expression::infix(
+                    hir::Expression::variable(a_inner, a_outer),
+                    Operator::GreaterThan.spanned(gt),
+                    hir::Expression::number(BigInt::from(1), one),
+                ),
+                Operator::And.spanned(and),
+                hir::Expression::infix(
+                    hir::Expression::variable(b_inner, b_outer),
+                    Operator::LessThan.spanned(lt),
+                    hir::Expression::number(BigInt::from(2), two),
+                ),
+            )
+        },
+    );
+}
+
+#[test]
+fn test_parse_signed_operand() {
+    parse_tokens(
+        AnyExpressionShape,
+        vec![b::int(1), b::sp(), b::op("+"), b::sp(), b::int(-2)],
+        |tokens| {
+            hir::Expression::infix(
+                hir::Expression::number(BigInt::from(1), tokens[0].span()),
+                Operator::Plus.spanned(tokens[2].span()),
+                hir::Expression::number(BigInt::from(-2), tokens[4].span()),
+            )
+        },
+    );
+
+    parse_tokens(
+        AnyExpressionShape,
+        vec![b::int(1), b::sp(), b::bare("-"), b::sp(), b::int(-2)],
+        |tokens| {
+            hir::Expression::infix(
+                hir::Expression::number(BigInt::from(1), tokens[0].span()),
+                Operator::Minus.spanned(tokens[2].span()),
+                hir::Expression::number(BigInt::from(-2), tokens[4].span()),
+            )
+        },
+    );
+
+    parse_tokens(
+        AnyExpressionShape,
+        vec![b::int(1), b::sp(), b::bare("-"), b::sp(), b::int(2)],
+        |tokens| {
+            hir::Expression::infix(
+                hir::Expression::number(BigInt::from(1), tokens[0].span()),
+                Operator::Minus.spanned(tokens[2].span()),
+                hir::Expression::number(BigInt::from(2), tokens[4].span()),
+            )
+        },
+    );
+}
+
+#[test]
+fn test_parse_negation() {
+    parse_tokens(
+        AnyExpressionShape,
+        vec![b::op("-"), b::var("x")],
+        |tokens| {
+            let (outer_var, inner_var) = tokens[1].expect_var();
+
+            hir::Expression::unary(
+                Operator::Negate.spanned(tokens[0].span()),
+                hir::Expression::variable(inner_var, outer_var),
+            )
+        },
+    );
+
+    parse_tokens(
+        AnyExpressionShape,
+        vec![
+            b::op("-"),
+            b::parens(vec![b::int(1), b::sp(), b::op("+"), b::sp(), b::int(2)]),
+        ],
+        |tokens| {
+            // -(1 + 2)
+            hir::Expression::unary(
+                Operator::Negate.spanned(Span::new(0, 1)),
+                hir::Expression::parenthesized(
+                    hir::Expression::infix(
+                        hir::Expression::number(BigInt::from(1), Span::new(2, 3)),
+                        Operator::Plus.spanned(Span::new(4, 5)),
+                        hir::Expression::number(BigInt::from(2), Span::new(6, 7)),
+                    ),
+                    tokens[1].span(),
+                ),
+            )
+        },
+    );
+}
+
+#[test]
+fn test_parse_not() {
+    parse_tokens(
+        NotExpressionShape,
+        vec![b::bare("not"), b::sp(), b::var("flag")],
+        |tokens| {
+            let (outer_var, inner_var) = tokens[2].expect_var();
+
+            hir::Expression::unary(
+                Operator::Not.spanned(tokens[0].span()),
+                hir::Expression::variable(inner_var, outer_var),
+            )
+        },
+    );
+
+    parse_tokens(
+        NotExpressionShape,
+        vec![
+            b::bare("not"),
+            b::sp(),
+            b::parens(vec![
+                b::var("a"),
+                b::sp(),
+                b::op("=="),
+                b::sp(),
+                b::var("b"),
+            ]),
+        ],
+        |tokens| {
+            // not ($a == $b)
+            hir::Expression::unary(
+                Operator::Not.spanned(Span::new(0, 3)),
+                hir::Expression::parenthesized(
+                    hir::Expression::infix(
+                        hir::Expression::variable(Span::new(6, 7), Span::new(5, 7)),
+                        Operator::Equal.spanned(Span::new(8, 10)),
+                        hir::Expression::variable(Span::new(12, 13), Span::new(11, 13)),
+                    ),
+                    tokens[2].span(),
+                ),
+            )
+        },
+    );
+}
+
+#[test]
+fn test_parse_not_keeps_parenthesized_operand_grouped() {
+    parse_tokens(
+        NotExpressionShape,
+        vec![
+            b::bare("not"),
+            b::sp(),
+            b::parens(vec![b::int(1), b::sp(), b::op("+"), b::sp(), b::int(2)]),
+            b::sp(),
+            b::pattern("*"),
+            b::sp(),
+            b::int(3),
+        ],
+        |tokens| {
+            // not (1 + 2) * 3 is not ((1 + 2) * 3)
+            hir::Expression::unary(
+                Operator::Not.spanned(tokens[0].span()),
+                hir::Expression::infix(
+                    hir::Expression::parenthesized(
+                        hir::Expression::infix(
+                            hir::Expression::number(BigInt::from(1), Span::new(5, 6)),
+                            Operator::Plus.spanned(Span::new(7, 8)),
+                            hir::Expression::number(BigInt::from(2), Span::new(9, 10)),
+                        ),
+                        tokens[2].span(),
+                    ),
+                    Operator::Multiply.spanned(tokens[4].span()),
+                    hir::Expression::number(BigInt::from(3), tokens[6].span()),
+                ),
+            )
+        },
+    );
+
+    parse_tokens(
+        NotExpressionShape,
+        vec![
+            b::bare("not"),
+            b::sp(),
+            b::parens(vec![
+                b::var("a"),
+                b::sp(),
+                b::op("||"),
+                b::sp(),
+                b::var("b"),
+            ]),
+            b::sp(),
+            b::op("=="),
+            b::sp(),
+            b::var("c"),
+        ],
+        |tokens| {
+            let (outer_c, inner_c) = tokens[6].expect_var();
+
+            // not ($a || $b) == $c is not (($a || $b) == $c)
+            hir::Expression::unary(
+                Operator::Not.spanned(tokens[0].span()),
+                hir::Expression::infix(
+                    hir::Expression::parenthesized(
+                        hir::Expression::infix(
+                            hir::Expression::variable(Span::new(6, 7), Span::new(5, 7)),
+                            Operator::Or.spanned(Span::new(8, 10)),
+                            hir::Expression::variable(Span::new(12, 13), Span::new(11, 13)),
+                        ),
+                        tokens[2].span(),
+                    ),
+                    Operator::Equal.spanned(tokens[4].span()),
+                    hir::Expression::variable(inner_c, outer_c),
+                ),
+            )
+        },
+    );
+}
+
+#[test]
+fn test_parse_not_binds_looser_than_comparisons() {
+    // not $a == $b && $c
+    parse_tokens(
+        NotExpressionShape,
+        vec![
+            b::bare("not"),
+            b::sp(),
+            b::var("a"),
+            b::sp(),
+            b::op("=="),
+            b::sp(),
+            b::var("b"),
+            b::sp(),
+            b::op("&&"),
+            b::sp(),
+            b::var("c"),
+        ],
+        |tokens| {
+            let (a_outer, a_inner) = tokens[2].expect_var();
+            let (b_outer, b_inner) = tokens[6].expect_var();
+            let (c_outer, c_inner) = tokens[10].expect_var();
+
+            hir::Expression::infix(
+                hir::Expression::unary(
+                    Operator::Not.spanned(tokens[0].span()),
+                    hir::Expression::infix(
+                        hir::Expression::variable(a_inner, a_outer),
+                        Operator::Equal.spanned(tokens[4].span()),
+                        hir::Expression::variable(b_inner, b_outer),
+                    ),
+                ),
+                Operator::And.spanned(tokens[8].span()),
+                hir::Expression::variable(c_inner, c_outer),
+            )
+        },
+    );
+}
+
+#[test]
+fn test_parse_not_in_a_command_argument() {
+    // `echo not foo` passes the word `not`
+    parse_tokens(
+        AnyExpressionShape,
+        vec![b::bare("not"), b::sp(), b::bare("foo")],
+        |tokens| hir::Expression::bare(tokens[0].span()),
+    );
+}
+
+#[test]
+fn test_parse_not_as_a_word() {
+    parse_tokens(AnyExpressionShape, vec![b::bare("not")], |tokens| {
+        hir::Expression::bare(tokens[0].span())
+    });
+
+    parse_tokens(
+        VariablePathShape,
+        vec![b::var("flags"), b::op("."), b::bare("not")],
+        |tokens| {
+            let (outer_var, inner_var) = tokens[0].expect_var();
+            let bare = tokens[2].expect_bare();
+
+            hir::Expression::path(
+                hir::Expression::variable(inner_var, outer_var),
+                vec![PathMember::string("not", bare)],
+                outer_var.until(bare),
+            )
+        },
+    );
+}
+
+#[test]
+fn test_parse_subtraction_is_not_negation() {
+    parse_tokens(
+        AnyExpressionShape,
+        vec![b::bare("a"), b::sp(), b::bare("-"), b::sp(), b::bare("b")],
+        |tokens| {
+            hir::Expression::infix(
+                hir::Expression::bare(tokens[0].span()),
+                Operator::Minus.spanned(tokens[2].span()),
+                hir::Expression::bare(tokens[4].span()),
+            )
+        },
+    );
+}
+
+#[test]
+fn test_parse_lone_minus_argument() {
+    parse_tokens(AnyExpressionShape, vec![b::bare("-")], |tokens| {
+        hir::Expression::bare(tokens[0].span())
+    });
+
+    parse_tokens(FilePathShape, vec![b::bare("-")], |tokens| {
+        hir::Expression::file_path("-", tokens[0].span())
+    });
+}
+
+#[test]
+fn test_parse_negative_member_is_not_subtraction() {
+    parse_tokens(
+        VariablePathShape,
+        vec![b::var("x"), b::op("."), b::int(-1)],
+        |tokens| {
+            let (outer_var, inner_var) = tokens[0].expect_var();
+            let index = tokens[2].span();
+            hir::Expression::path(
+                hir::Expression::variable(inner_var, outer_var),
+                vec![PathMember::int(-1, index)],
+                outer_var.until(index),
+            )
+        },
+    );
+
+    parse_tokens(
+        AnyExpressionShape,
+        vec![b::var("x"), b::sp(), b::bare("-"), b::sp(), b::int(1)],
+        |tokens| {
+            let (outer_var, inner_var) = tokens[0].expect_var();
+            hir::Expression::infix(
+                hir::Expression::variable(inner_var, outer_var),
+                Operator::Minus.spanned(tokens[2].span()),
+                hir::Expression::number(BigInt::from(1), tokens[4].span()),
+            )
+        },
+    );
+}
+
+#[test]
+fn test_operator_precedence() {
+    assert!(Operator::Pow.precedence() > Operator::Multiply.precedence());
+    assert!(Operator::Multiply.precedence() > Operator::Plus.precedence());
+    assert_eq!(Operator::Plus.precedence(), Operator::Minus.precedence());
+    assert_eq!(Operator::Multiply.precedence(), Operator::Mod.precedence());
+    assert!(Operator::Plus.precedence() > Operator::Equal.precedence());
+    assert!(Operator::Equal.precedence() > Operator::Not.precedence());
+    assert!(Operator::Not.precedence() > Operator::And.precedence());
+    assert!(Operator::And.precedence() > Operator::Or.precedence());
+}
+
+#[test]
+fn test_parse_pow() {
+    parse_tokens(
+        AnyExpressionShape,
+        vec![b::int(2), b::sp(), b::op("**"), b::sp(), b::int(3)],
+        |tokens| {
+            let two = tokens[0].span();
+            let op = tokens[2].span();
+            let three = tokens[4].span();
+
+            hir::Expression::infix(
+                hir::Expression::number(BigInt::from(2), two),
+                Operator::Pow.spanned(op),
+                hir::Expression::number(BigInt::from(3), three),
+            )
+        },
+    );
+}
+
+#[test]
+fn test_operator_associativity() {
+    assert_eq!(Operator::Pow.associativity(), Associativity::Right);
+    assert_eq!(Operator::Minus.associativity(), Associativity::Left);
+
+    // `2 ** 3 ** 2` is `2 ** (3 ** 2)`
+    parse_tokens(
+        AnyExpressionShape,
+        vec![
+            b::int(2),
+            b::sp(),
+            b::op("**"),
+            b::sp(),
+            b::int(3),
+            b::sp(),
+            b::op("**"),
+            b::sp(),
+            b::int(2),
+        ],
+        |tokens| {
+            hir::Expression::infix(
+                hir::Expression::number(BigInt::from(2), tokens[0].span()),
+                Operator::Pow.spanned(tokens[2].span()),
+                hir::Expression::infix(
+                    hir::Expression::number(BigInt::from(3), tokens[4].span()),
+                    Operator::Pow.spanned(tokens[6].span()),
+                    hir::Expression::number(BigInt::from(2), tokens[8].span()),
+                ),
+            )
+        },
+    );
+
+    // `2 - 3 - 1` is `(2 - 3) - 1`
+    parse_tokens(
+        AnyExpressionShape,
+        vec![
+            b::int(2),
+            b::sp(),
+            b::bare("-"),
+            b::sp(),
+            b::int(3),
+            b::sp(),
+            b::bare("-"),
+            b::sp(),
+            b::int(1),
+        ],
+        |tokens| {
+            hir::Expression::infix(
+                hir::Expression::infix(
+                    hir::Expression::number(BigInt::from(2), tokens[0].span()),
+                    Operator::Minus.spanned(tokens[2].span()),
+                    hir::Expression::number(BigInt::from(3), tokens[4].span()),
+                ),
+                Operator::Minus.spanned(tokens[6].span()),
+                hir::Expression::number(BigInt::from(1), tokens[8].span()),
+            )
+        },
+    );
+}
+
+#[test]
+fn test_parse_lexed_pow() {
+    let expr = with_lexed_tokens("2 ** 3", |iterator, context| {
+        expand_expr(&AnyExpressionShape, iterator, &context).unwrap()
+    });
+
+    match expr.item {
+        hir::RawExpression::Binary(binary) => assert_eq!(binary.op().item, Operator::Pow),
+        other => panic!("expected a binary expression, got {:?}", other),
+    }
+
+    // `2**3` is lexed as a single glob pattern, so it's never an accidental `2 ** 3`
+    let expr = with_lexed_tokens("2**3", |iterator, context| {
+        expand_expr(&AnyExpressionShape, iterator, &context).unwrap()
+    });
+
+    assert_eq!(expr, hir::Expression::pattern("2**3", Span::new(0, 4)));
+}
+
+#[test]
+fn test_parse_optional_dot_continuation() {
+    parse_continuation(
+        vec![b::var("x"), b::op("."), b::bare("maybe"), b::bare("?")],
+        |tokens| {
+            let dot = tokens[1].span();
+            let maybe = tokens[2].expect_bare();
+            let optional = tokens[3].expect_bare();
+
+            ExpressionContinuation::DotSuffix(
+                dot,
+                PathMember::string("maybe", maybe.until(optional)),
+                Some(MemberMarker::Optional),
+            )
+        },
+    );
+
+    parse_continuation(vec![b::var("x"), b::op("."), b::bare("maybe")], |tokens| {
+        let dot = tokens[1].span();
+        let maybe = tokens[2].expect_bare();
+
+        ExpressionContinuation::DotSuffix(dot, PathMember::string("maybe", maybe), None)
+    });
+}
+
+#[test]
+fn test_continuation_source_text() {
+    assert_eq!(
+        continuation_source_text(vec![b::var("x"), b::op("."), b::bare("name")]),
+        ".name"
+    );
+
+    assert_eq!(
+        continuation_source_text(vec![b::var("x"), b::sp(), b::op("+"), b::sp(), b::int(1)]),
+        "+ 1"
+    );
+}
+
+#[test]
+fn test_parse_index_suffix() {
+    parse_tokens(
+        AnyExpressionShape,
+        vec![b::var("x"), b::square(vec![b::int(0)])],
+        |tokens| {
+            let (outer_var, inner_var) = tokens[0].expect_var();
+            let square = tokens[1].span();
+
+            hir::Expression::path(
+                hir::Expression::variable(inner_var, outer_var),
+                vec![PathMember::int(0, square)],
+                outer_var.until(square),
+            )
+        },
+    );
+
+    parse_tokens(
+        AnyExpressionShape,
+        vec![b::var("x"), b::square(vec![b::string("a b")])],
+        |tokens| {
+            let (outer_var, inner_var) = tokens[0].expect_var();
+            let square = tokens[1].span();
+
+            hir::Expression::path(
+                hir::Expression::variable(inner_var, outer_var),
+                vec![PathMember::string("a b", square)],
+                outer_var.until(square),
+            )
+        },
+    );
+}
+
+#[test]
+fn test_parse_index_suffix_like_member_index() {
+    // a string index works after a member too
+    parse_tokens(
+        VariablePathShape,
+        vec![
+            b::var("it"),
+            b::op("."),
+            b::bare("a"),
+            b::square(vec![b::string("b c")]),
+        ],
+        |tokens| {
+            let (outer_var, inner_var) = tokens[0].expect_var();
+            let member = tokens[2].expect_bare();
+            let square = tokens[3].span();
+
+            hir::Expression::path(
+                hir::Expression::it_variable(inner_var, outer_var),
+                vec![
+                    PathMember::string("a", member),
+                    PathMember::string("b c", square),
+                ],
+                outer_var.until(square),
+            )
+        },
+    );
+
+    // and a range index works after an expression
+    parse_tokens(
+        AnyExpressionShape,
+        vec![
+            b::var("x"),
+            b::square(vec![b::bare("0"), b::op("."), b::op("."), b::bare("3")]),
+        ],
+        |tokens| {
+            let (outer_var, inner_var) = tokens[0].expect_var();
+            let square = tokens[1].span();
+
+            hir::Expression::path(
+                hir::Expression::variable(inner_var, outer_var),
+                vec![PathMember::range(0, Some(BigInt::from(3)), false, square)],
+                outer_var.until(square),
+            )
+        },
+    );
+}
+
+#[test]
+fn test_parse_list_is_not_an_index() {
+    parse_tokens(
+        AnyExpressionShape,
+        vec![b::square(vec![
+            b::int(1),
+            b::sp(),
+            b::int(2),
+            b::sp(),
+            b::int(3),
+        ])],
+        |tokens| {
+            let square = tokens[0].span();
+            let children = match &tokens[0] {
+                TokenNode::Delimited(delimited) => &delimited.item.children,
+                other => panic!("expected a delimited token, got {:?}", other),
+            };
+
+            hir::Expression::list(
+                vec![
+                    hir::Expression::number(BigInt::from(1), children[0].span()),
+                    hir::Expression::number(BigInt::from(2), children[2].span()),
+                    hir::Expression::number(BigInt::from(3), children[4].span()),
+                ],
+                square,
+            )
+        },
+    );
+}
+
+#[test]
+fn test_parse_equality_continuation() {
+    parse_continuation(
+        vec![b::var("x"), b::sp(), b::op("=="), b::sp(), b::int(3)],
+        |tokens| {
+            let op = tokens[2].span();
+            let three = tokens[4].span();
+
+            ExpressionContinuation::InfixSuffix(
+                Operator::Equal.spanned(op),
+                hir::Expression::number(BigInt::from(3), three),
+            )
+        },
+    );
+
+    parse_continuation(
+        vec![
+            b::var("name"),
+            b::sp(),
+            b::op("!="),
+            b::sp(),
+            b::string("bob"),
+        ],
+        |tokens| {
+            let op = tokens[2].span();
+            let (outer, inner) = tokens[4].expect_string();
+
+            ExpressionContinuation::InfixSuffix(
+                Operator::NotEqual.spanned(op),
+                hir::Expression::string(inner, outer),
+            )
+        },
+    );
+}
+
+#[test]
+fn test_parse_comparison_continuation() {
+    parse_continuation(
+        vec![b::var("a"), b::sp(), b::op(">="), b::sp(), b::var("b")],
+        |tokens| {
+            let op = tokens[2].span();
+            let (outer, inner) = tokens[4].expect_var();
+
+            ExpressionContinuation::InfixSuffix(
+                Operator::GreaterThanOrEqual.spanned(op),
+                hir::Expression::variable(inner, outer),
+            )
+        },
+    );
+
+    parse_continuation(
+        vec![b::var("a"), b::sp(), b::op("<="), b::sp(), b::var("b")],
+        |tokens| {
+            let op = tokens[2].span();
+            let (outer, inner) = tokens[4].expect_var();
+
+            ExpressionContinuation::InfixSuffix(
+                Operator::LessThanOrEqual.spanned(op),
+                hir::Expression::variable(inner, outer),
+            )
+        },
+    );
+
+    let source = "$a >= $b";
+    let op = Operator::GreaterThanOrEqual.spanned(Span::new(3, 5));
+    assert!(format!("{}", op.debug(source)).contains(">="));
+}
+
+#[test]
+fn test_parse_compare() {
+    parse_tokens(
+        AnyExpressionShape,
+        vec![b::var("a"), b::sp(), b::op("<=>"), b::sp(), b::var("b")],
+        |tokens| {
+            let (outer_a, inner_a) = tokens[0].expect_var();
+            let op = tokens[2].span();
+            let (outer_b, inner_b) = tokens[4].expect_var();
+
+            hir::Expression::infix(
+                hir::Expression::variable(inner_a, outer_a),
+                Operator::Compare.spanned(op),
+                hir::Expression::variable(inner_b, outer_b),
+            )
+        },
+    );
+
+    assert_eq!(
+        Operator::Compare.precedence(),
+        Operator::LessThan.precedence()
+    );
+}
+
+#[test]
+fn test_parse_append() {
+    parse_tokens(
+        AnyExpressionShape,
+        vec![b::var("a"), b::sp(), b::op("++"), b::sp(), b::var("b")],
+        |tokens| {
+            let (outer_a, inner_a) = tokens[0].expect_var();
+            let op = tokens[2].span();
+            let (outer_b, inner_b) = tokens[4].expect_var();
+
+            hir::Expression::infix(
+                hir::Expression::variable(inner_a, outer_a),
+                Operator::Append.spanned(op),
+                hir::Expression::variable(inner_b, outer_b),
+            )
+        },
+    );
+
+    assert_eq!(Operator::Append.precedence(), Operator::Plus.precedence());
+}
+
+#[test]
+fn test_parse_compare_without_whitespace() {
+    // the `<=>$b` in `$a<=>$b`
+    let err = parse_error(InfixShape, vec![b::op("<=>"), b::var("b")]);
+
+    match err.reason() {
+        ParseErrorReason::Mismatch { expected, .. } => assert_eq!(*expected, "whitespace"),
+        other => panic!("expected a mismatch error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_regex_match_continuation() {
+    parse_continuation(
+        vec![
+            b::var("name"),
+            b::sp(),
+            b::op("=~"),
+            b::sp(),
+            b::string("a.*"),
+        ],
+        |tokens| {
+            let op = tokens[2].span();
+            let (outer, inner) = tokens[4].expect_string();
+
+            ExpressionContinuation::InfixSuffix(
+                Operator::RegexMatch.spanned(op),
+                hir::Expression::string(inner, outer),
+            )
+        },
+    );
+
+    let source = r#"$name =~ "a.*""#;
+    let op = Operator::RegexMatch.spanned(Span::new(6, 8));
+    assert!(format!("{}", op.debug(source)).contains("=~"));
+}
+
+#[test]
+fn test_parse_membership_continuation() {
+    parse_continuation(
+        vec![
+            b::var("color"),
+            b::sp(),
+            b::bare("in"),
+            b::sp(),
+            b::var("palette"),
+        ],
+        |tokens| {
+            let op = tokens[2].expect_bare();
+            let (outer, inner) = tokens[4].expect_var();
+
+            ExpressionContinuation::InfixSuffix(
+                Operator::In.spanned(op),
+                hir::Expression::variable(inner, outer),
+            )
+        },
+    );
+
+    parse_continuation(
+        vec![
+            b::var("color"),
+            b::sp(),
+            b::bare("not-in"),
+            b::sp(),
+            b::var("palette"),
+        ],
+        |tokens| {
+            let op = tokens[2].expect_bare();
+            let (outer, inner) = tokens[4].expect_var();
+
+            ExpressionContinuation::InfixSuffix(
+                Operator::NotIn.spanned(op),
+                hir::Expression::variable(inner, outer),
+            )
+        },
+    );
+}
+
+#[test]
+fn test_parse_in_as_column_name() {
+    parse_tokens(
+        VariablePathShape,
+        vec![b::var("it"), b::op("."), b::bare("in")],
+        |tokens| {
+            let (outer_var, inner_var) = tokens[0].expect_var();
+            let bare = tokens[2].expect_bare();
+
+            hir::Expression::path(
+                hir::Expression::it_variable(inner_var, outer_var),
+                vec![PathMember::string("in", bare)],
+                outer_var.until(bare),
+            )
+        },
+    );
+}
+
+#[test]
+fn test_parse_comparison_without_whitespace() {
+    // the `>=$b` in `$a>=$b`
+    let err = parse_error(InfixShape, vec![b::op(">="), b::var("b")]);
+
+    match err.reason() {
+        ParseErrorReason::Mismatch { expected, .. } => assert_eq!(*expected, "whitespace"),
+        other => panic!("expected a mismatch error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_infix_without_trailing_whitespace() {
+    // the ` >$b` in `$a >$b`
+    let err = parse_error(InfixShape, vec![b::sp(), b::op(">"), b::var("b")]);
+
+    match err.reason() {
+        ParseErrorReason::InfixWithoutWhitespace { operator, span } => {
+            assert_eq!(*operator, Span::new(1, 2));
+            assert_eq!(*span, Span::new(2, 3));
+        }
+        other => panic!("expected an infix whitespace error, got {:?}", other),
+    }
+
+    assert_eq!(
+        ShellError::from(err),
+        ShellError::labeled_error(
+            "Missing whitespace",
+            "infix operator must be followed by whitespace",
+            Span::new(2, 3),
+        )
+    );
+}
+
+#[test]
+fn test_parse_condition_without_operator() {
+    let err = parse_error(ConditionShape, vec![b::bare("size"), b::sp(), b::int(10)]);
+
+    match err.reason() {
+        ParseErrorReason::Mismatch { expected, .. } => assert_eq!(*expected, "infix operator"),
+        other => panic!("expected a mismatch error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_pipe_suffix() {
+    // $it.users | each
+    with_tokens(
+        vec![
+            b::var("it"),
+            b::op("."),
+            b::bare("users"),
+            b::sp(),
+            b::bare("|"),
+            b::sp(),
+            b::bare("each"),
+        ],
+        |iterator, context, tokens| {
+            let expr = expand_expr(&AnyExpressionShape, iterator, &context).unwrap();
+            let (outer_var, inner_var) = tokens[0].expect_var();
+            let users = tokens[2].expect_bare();
+
+            let pipe = match &expr.item {
+                hir::RawExpression::Pipe(pipe) => pipe,
+                other => panic!("expected a pipe, got {:?}", other),
+            };
+
+            assert_eq!(
+                *pipe.head(),
+                hir::Expression::path(
+                    hir::Expression::it_variable(inner_var, outer_var),
+                    vec![PathMember::string("users", users)],
+                    outer_var.until(users),
+                )
+            );
+            assert_eq!(*pipe.pipe(), tokens[4].span());
+
+            let commands = &pipe.pipeline().commands.item;
+            assert_eq!(commands.len(), 1);
+
+            match &commands[0] {
+                ClassifiedCommand::External(each) => assert_eq!(each.name, "each"),
+                other => panic!("expected an external command, got {:?}", other),
+            }
+
+            assert!(iterator.at_end_possible_ws());
+        },
+    );
+}
+
+#[test]
+fn test_parse_pipe_suffix_into_a_pipeline() {
+    // $it.dir | ls *.txt | each
+    with_tokens(
+        vec![
+            b::var("it"),
+            b::op("."),
+            b::bare("dir"),
+            b::sp(),
+            b::bare("|"),
+            b::sp(),
+            b::bare("ls"),
+            b::sp(),
+            b::pattern("*.txt"),
+            b::sp(),
+            b::bare("|"),
+            b::sp(),
+            b::bare("each"),
+        ],
+        |iterator, context, tokens| {
+            let expr = expand_expr(&AnyExpressionShape, iterator, &context).unwrap();
+            let pat = tokens[8].expect_pattern();
+
+            let pipe = match &expr.item {
+                hir::RawExpression::Pipe(pipe) => pipe,
+                other => panic!("expected a pipe, got {:?}", other),
+            };
+
+            let commands = &pipe.pipeline().commands.item;
+            assert_eq!(commands.len(), 2);
+
+            match &commands[0] {
+                ClassifiedCommand::Internal(ls) => {
+                    assert_eq!(ls.name, "ls");
+                    assert_eq!(
+                        *ls.args.item.positional(),
+                        Some(vec![hir::Expression::pattern("*.txt", pat)])
+                    );
+                }
+                other => panic!("expected an internal command, got {:?}", other),
+            }
+
+            match &commands[1] {
+                ClassifiedCommand::External(each) => assert_eq!(each.name, "each"),
+                other => panic!("expected an external command, got {:?}", other),
+            }
+        },
+    );
+}
+
+#[test]
+fn test_parse_member_alternatives() {
+    parse_tokens(
+        ColumnPathShape,
+        vec![b::parens(vec![
+            b::bare("left"),
+            b::op("."),
+            b::bare("name"),
+            b::sp(),
+            b::bare("|"),
+            b::sp(),
+            b::bare("right"),
+            b::op("."),
+            b::bare("name"),
+        ])],
+        |tokens| {
+            let (group, children) = match &tokens[0] {
+                TokenNode::Delimited(delimited) => (delimited.span, &delimited.item.children),
+                other => panic!("expected a delimited token, got {:?}", other),
+            };
+
+            ColumnPathMembers::from_vec(vec![Member::Alternatives(
+                vec![
+                    hir::Expression::bare(children[0].span().until(children[2].span())),
+                    hir::Expression::bare(children[6].span().until(children[8].span())),
+                ],
+                group,
+            )])
+            .tagged(group)
+        },
+    );
+}
+
+#[test]
+fn test_evaluate_member_alternatives() {
+    // $it.(left.name | right.name)
+    let tokens = || {
+        vec![
+            b::var("it"),
+            b::op("."),
+            b::parens(vec![
+                b::bare("left"),
+                b::op("."),
+                b::bare("name"),
+                b::sp(),
+                b::bare("|"),
+                b::sp(),
+                b::bare("right"),
+                b::op("."),
+                b::bare("name"),
+            ]),
+        ]
+    };
+
+    let it = Value::row(indexmap! {
+        "left".into() => Value::row(indexmap! {}).tagged_unknown(),
+        "right".into() => Value::row(indexmap! {
+            "name".into() => Value::string("nu").tagged_unknown()
+        })
+        .tagged_unknown()
+    });
+
+    assert_eq!(
+        evaluate_tokens(tokens(), it.tagged_unknown()).unwrap().item,
+        Value::string("nu")
+    );
+
+    // neither source has the column
+    let it = Value::row(indexmap! {
+        "left".into() => Value::row(indexmap! {}).tagged_unknown()
+    });
+
+    assert!(evaluate_tokens(tokens(), it.tagged_unknown()).is_err());
+}
+
+#[test]
+fn test_evaluate_range_member_with_variable_bounds() {
+    // $it.rows[$it.start..$it.end]
+    let expr = |end: &str| {
+        let it = || hir::Expression::it_variable(Span::unknown(), Span::unknown());
+        let bound = |name: &str| {
+            hir::Expression::path(
+                it(),
+                vec![PathMember::string(name, Span::unknown())],
+                Span::unknown(),
+            )
+        };
+
+        hir::Expression::path(
+            it(),
+            vec![
+                PathMember::string("rows", Span::unknown()),
+                PathMember::computed_range(
+                    hir::Range::new(
+                        Some(bound("start")),
+                        Span::unknown(),
+                        Some(bound(end)),
+                        false,
+                    ),
+                    Span::unknown(),
+                ),
+            ],
+            Span::unknown(),
+        )
+    };
+
+    let it = Value::row(indexmap! {
+        "rows".into() => Value::Table((0..5).map(|n| Value::int(n).tagged_unknown()).collect())
+            .tagged_unknown(),
+        "start".into() => Value::int(1).tagged_unknown(),
+        "end".into() => Value::int(3).tagged_unknown(),
+        "name".into() => Value::string("nu").tagged_unknown()
+    })
+    .tagged_unknown();
+
+    let evaluate = |expr: hir::Expression| {
+        evaluate_baseline_expr(
+            &expr,
+            &CommandRegistry::empty(),
+            &Scope::it_value(it.clone()),
+            &Text::from(""),
+        )
+    };
+
+    assert_eq!(
+        table_items(evaluate(expr("end")).unwrap()),
+        vec![Value::int(1), Value::int(2)]
+    );
+
+    // the bounds have to be integers
+    assert!(evaluate(expr("name")).is_err());
+}
+
+#[test]
+fn test_parse_empty_member_alternative() {
+    let err = parse_error(
+        MemberShape,
+        vec![b::parens(vec![
+            b::bare("left"),
+            b::op("."),
+            b::bare("name"),
+            b::sp(),
+            b::bare("|"),
+            b::sp(),
+        ])],
+    );
+
+    match err.reason() {
+        ParseErrorReason::Mismatch { expected, actual } => {
+            assert_eq!(*expected, "member alternative");
+            assert_eq!(actual.item, "nothing");
+        }
+        other => panic!("expected a mismatch error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_operators_used() {
+    // $a == $b ?? $c
+    let expr = hir::Expression::infix(
+        hir::Expression::variable(Span::new(1, 2), Span::new(0, 2)),
+        Operator::Equal.spanned(Span::new(3, 5)),
+        hir::Expression::infix(
+            hir::Expression::variable(Span::new(7, 8), Span::new(6, 8)),
+            Operator::Coalesce.spanned(Span::new(9, 11)),
+            hir::Expression::variable(Span::new(13, 14), Span::new(12, 14)),
+        ),
+    );
+
+    assert_eq!(
+        expr.operators_used(),
+        vec![Operator::Equal, Operator::Coalesce]
+    );
+
+    // 1 + 2 * 3
+    let expr = hir::Expression::infix(
+        hir::Expression::number(BigInt::from(1), Span::new(0, 1)),
+        Operator::Plus.spanned(Span::new(2, 3)),
+        hir::Expression::infix(
+            hir::Expression::number(BigInt::from(2), Span::new(4, 5)),
+            Operator::Multiply.spanned(Span::new(6, 7)),
+            hir::Expression::number(BigInt::from(3), Span::new(8, 9)),
+        ),
+    );
+
+    assert_eq!(
+        expr.operators_used(),
+        vec![Operator::Plus, Operator::Multiply]
+    );
+
+    // $it.(a ?? b)
+    let expr = hir::Expression::path(
+        hir::Expression::it_variable(Span::new(1, 3), Span::new(0, 3)),
+        vec![PathMember::computed(
+            hir::Expression::infix(
+                hir::Expression::bare(Span::new(5, 6)),
+                Operator::Coalesce.spanned(Span::new(7, 9)),
+                hir::Expression::bare(Span::new(10, 11)),
+            ),
+            Span::new(4, 12),
+        )],
+        Span::new(0, 12),
+    );
+
+    assert_eq!(expr.operators_used(), vec![Operator::Coalesce]);
+
+    // a.(b ?? c)
+    let expr = hir::Expression::column_path(
+        vec![
+            Member::Bare(Span::new(0, 1)),
+            Member::Computed(
+                hir::Expression::infix(
+                    hir::Expression::bare(Span::new(3, 4)),
+                    Operator::Coalesce.spanned(Span::new(5, 7)),
+                    hir::Expression::bare(Span::new(8, 9)),
+                ),
+                Span::new(2, 10),
+            ),
+        ],
+        Span::new(0, 10),
+    );
+
+    assert_eq!(expr.operators_used(), vec![Operator::Coalesce]);
+
+    // $it.a.b
+    let expr = hir::Expression::path(
+        hir::Expression::it_variable(Span::new(1, 3), Span::new(0, 3)),
+        vec![
+            PathMember::string("a", Span::new(4, 5)),
+            PathMember::string("b", Span::new(6, 7)),
+        ],
+        Span::new(0, 7),
+    );
+
+    assert_eq!(expr.operators_used(), vec![]);
+}
+
+#[test]
+fn test_peek_infix() {
+    assert!(peek_infix(vec![b::sp(), b::op(">"), b::sp(), b::int(10)]));
+    assert!(!peek_infix(vec![b::sp(), b::bare("foo")]));
+    assert!(!peek_infix(vec![b::sp(), b::op("."), b::bare("foo")]));
+}
+
+#[test]
+fn test_peek_operator() {
+    let tokens = b::token_list(vec![b::var("it"), b::sp(), b::op(">"), b::sp(), b::int(5)]);
+    let (tokens, _) = b::build(tokens);
+
+    let tokens = tokens.expect_list();
+    let mut iterator = TokensIterator::all(tokens.item, tokens.span);
+
+    assert_eq!(iterator.peek_operator(), None);
+    iterator.next().unwrap().expect_var();
+
+    // the whitespace before the operator is skipped, but nothing is consumed
+    let operator = Operator::GreaterThan.spanned(tokens.item[2].span());
+    assert_eq!(iterator.peek_operator(), Some(operator));
+    assert_eq!(iterator.peek_operator(), Some(operator));
+    assert_eq!(iterator.pos(false), Some(1));
+
+    iterator.next().unwrap();
+    iterator.next().unwrap();
+    assert_eq!(iterator.peek_operator(), None);
+}
+
+#[test]
+fn test_variable_completions() {
+    let variables = vec!["name".to_string(), "nu".to_string(), "size".to_string()];
+    let source = Text::from("$n");
+
+    ExpandContext::with_empty(&source, |context| {
+        let context = context.with_variables(&variables);
+
+        assert_eq!(
+            context.variable_completions(Span::new(0, 2)),
+            vec!["$name".to_string(), "$nu".to_string()]
+        );
+        assert_eq!(
+            context.variable_completions(Span::new(0, 1)),
+            vec!["$name".to_string(), "$nu".to_string(), "$size".to_string()]
+        );
+    });
+
+    ExpandContext::with_empty(&source, |context| {
+        assert!(context.variable_completions(Span::new(0, 2)).is_empty());
+    });
+}
+
+#[test]
+fn test_peek_member() {
+    assert!(peek_member(vec![b::bare("name")]));
+    assert!(peek_member(vec![b::string("max ghz")]));
+    assert!(peek_member(vec![b::int(0)]));
+    assert!(!peek_member(vec![b::op("."), b::bare("name")]));
+    assert!(!peek_member(vec![b::var("it")]));
+}
+
+#[test]
+fn test_parse_error_into_shell_error() {
+    let span = Span::new(4, 5);
+
+    assert_eq!(
+        ShellError::from(ParseError::mismatch("column", "dot".spanned(span))),
+        ShellError::type_error("column", "dot".spanned(span))
+    );
+
+    assert_eq!(
+        ShellError::from(ParseError::unexpected_eof("column", span)),
+        ShellError::unexpected_eof("column", span)
+    );
+
+    let err = parse_error(
+        MemberShape,
+        vec![b::parens(vec![b::op("."), b::op("."), b::op(".")])],
+    );
+
+    let (expected, span) = match err.reason() {
+        ParseErrorReason::Eof { expected, span } => (*expected, *span),
+        other => panic!("expected an unexpected eof error, got {:?}", other),
+    };
+
+    assert_eq!(
+        ShellError::from(err),
+        ShellError::unexpected_eof(expected, span)
+    );
+}
+
+#[test]
+fn test_parse_command() {
+    parse_tokens(
+        ClassifiedCommandShape,
+        vec![b::bare("ls"), b::sp(), b::pattern("*.txt")],
+        |tokens| {
+            let bare = tokens[0].expect_bare();
+            let pat = tokens[2].expect_pattern();
+
+            let mut map = IndexMap::new();
+            map.insert("full".to_string(), NamedValue::AbsentSwitch);
+
+            ClassifiedCommand::Internal(InternalCommand::new(
+                "ls".to_string(),
+                Tag {
+                    span: bare,
+                    anchor: None,
+                },
+                hir::Call {
+                    head: Box::new(hir::RawExpression::Command(bare).spanned(bare)),
+                    positional: Some(vec![hir::Expression::pattern("*.txt", pat)]),
+                    named: Some(NamedArguments { named: map }),
+                }
+                .spanned(bare.until(pat)),
+            ))
+            // hir::Expression::path(
+            //     hir::Expression::variable(inner_var, outer_var),
+            //     vec!["cpu".tagged(bare)],
+            //     outer_var.until(bare),
+            // )
+        },
+    );
+}
+
+#[test]
+fn test_int_member_native_values() {
+    assert_eq!(int_member(3).as_usize(), Some(3));
+    assert_eq!(int_member(3).as_i64(), Some(3));
+    assert_eq!(int_member(-3).as_usize(), None);
+    assert_eq!(int_member(-3).as_i64(), Some(-3));
+}
+
+#[test]
+fn test_int_member_overflow() {
+    let huge = BigInt::from_str("100000000000000000000000000000").unwrap();
+
+    assert_eq!(int_member(huge.clone()).as_usize(), None);
+    assert_eq!(int_member(huge).as_i64(), None);
+}
+
+#[test]
+fn test_non_int_member_native_values() {
+    let bare = Member::Bare(Span::new(0, 4));
+    let string = Member::String(Span::new(0, 6), Span::new(1, 5));
+
+    assert_eq!(bare.as_usize(), None);
+    assert_eq!(bare.as_i64(), None);
+    assert_eq!(string.as_usize(), None);
+    assert_eq!(string.as_i64(), None);
+}
+
+#[test]
+fn test_member_accessors() {
+    let source = Text::from(r#"name "full name" 3 #2 ~ * user_*"#);
+
+    let bare = Member::Bare(Span::new(0, 4));
+    let string = Member::String(Span::new(5, 16), Span::new(6, 15));
+    let int = Member::Int(BigInt::from(3), Span::new(17, 18));
+    let column_number = Member::ColumnNumber(BigInt::from(2), Span::new(19, 21));
+    let entries = Member::Entries(Span::new(22, 23));
+    let wildcard = Member::Wildcard(Span::new(24, 25));
+    let glob = Member::Glob(Span::new(26, 32));
+    let splat = Member::Splat(Box::new(bare.clone()), Span::new(0, 4));
+    let computed = Member::Computed(hir::Expression::bare(Span::new(0, 4)), Span::new(0, 4));
+
+    assert_eq!(bare.as_string(&source), Some("name"));
+    assert_eq!(string.as_string(&source), Some("full name"));
+    assert_eq!(int.as_int(), Some(&BigInt::from(3)));
+
+    for member in &[
+        &bare,
+        &string,
+        &column_number,
+        &entries,
+        &wildcard,
+        &glob,
+        &splat,
+        &computed,
+    ] {
+        assert_eq!(member.as_int(), None, "{:?}", member);
+    }
+
+    for member in &[
+        &int,
+        &column_number,
+        &entries,
+        &wildcard,
+        &glob,
+        &splat,
+        &computed,
+    ] {
+        assert_eq!(member.as_string(&source), None, "{:?}", member);
+    }
+}
+
+#[test]
+fn test_unicode_escape_in_quoted_member() {
+    let source = Text::from(r#""\u{1F600}""#);
+    let member = Member::String(Span::new(0, 11), Span::new(1, 10));
+
+    assert_eq!(
+        member.to_path_member(&source),
+        PathMember::string("\u{1F600}", Span::new(0, 11))
+    );
+}
+
+#[test]
+fn test_render_short_path() {
+    let source = Text::from("a.b.c");
+
+    assert_eq!(bare_path(&source).render_truncated(&source, 3), "a.b.c");
+    assert_eq!(bare_path(&source).render_truncated(&source, 10), "a.b.c");
+}
+
+#[test]
+fn test_render_long_path() {
+    let source = Text::from("a.b.c.d.e.f.g");
+
+    assert_eq!(bare_path(&source).render_truncated(&source, 4), "a.b.….f.g");
+    assert_eq!(bare_path(&source).render_truncated(&source, 3), "a.b.….g");
+}
+
+#[test]
+fn test_kinds_used_in_path() {
+    // a.0."x"
+    let path = ColumnPathMembers::from_vec(vec![
+        Member::Bare(Span::new(0, 1)),
+        Member::Int(BigInt::from(0), Span::new(2, 3)),
+        Member::String(Span::new(4, 7), Span::new(5, 6)),
+    ])
+    .tagged(Span::new(0, 7));
+
+    let expected: HashSet<MemberKind> = vec![MemberKind::Bare, MemberKind::Int, MemberKind::String]
+        .into_iter()
+        .collect();
+
+    assert_eq!(path.kinds_used(), expected);
+}
+
+#[test]
+fn test_walk_passes_the_prefix() {
+    let source = Text::from("a.b.c");
+    let mut steps = vec![];
+
+    bare_path(&source).walk(|prefix, member| steps.push((prefix.len(), member.clone())));
+
+    assert_eq!(
+        steps,
+        vec![
+            (0, Member::Bare(Span::new(0, 1))),
+            (1, Member::Bare(Span::new(2, 3))),
+            (2, Member::Bare(Span::new(4, 5))),
+        ]
+    );
+}
+
+#[test]
+fn test_numeric_looking_bare_members_are_ambiguous() {
+    let source = Text::from("a.0x10.b.10");
+
+    assert_eq!(
+        bare_path(&source).has_ambiguous_members(&source),
+        vec![Span::new(2, 6), Span::new(9, 11)]
+    );
+
+    let source = Text::from("name.email");
+
+    assert_eq!(bare_path(&source).has_ambiguous_members(&source), vec![]);
+}
+
+#[test]
+fn test_similar_paths_share_a_signature() {
+    // a.0."x"
+    let first = ColumnPathMembers::from_vec(vec![
+        Member::Bare(Span::new(0, 1)),
+        Member::Int(BigInt::from(0), Span::new(2, 3)),
+        Member::String(Span::new(4, 7), Span::new(5, 6)),
+    ])
+    .tagged(Span::new(0, 7));
+
+    // b.1."y"
+    let second = ColumnPathMembers::from_vec(vec![
+        Member::Bare(Span::new(0, 1)),
+        Member::Int(BigInt::from(1), Span::new(2, 3)),
+        Member::String(Span::new(4, 7), Span::new(5, 6)),
+    ])
+    .tagged(Span::new(0, 7));
+
+    assert_eq!(first.signature(), "B.I.S");
+    assert_eq!(first.signature(), second.signature());
+}
+
+#[test]
+fn test_int_member_displays_its_value() {
+    assert_eq!(int_member(42).to_string(), "42");
+    assert_eq!(int_member(-7).to_string(), "-7");
+
+    let big = "123456789012345678901234567890".parse::<BigInt>().unwrap();
+    assert_eq!(int_member(big.clone()).to_string(), big.to_string());
+}
+
+#[test]
+fn test_members_describe_themselves_from_source() {
+    // name."first name".007
+    let source = Text::from(r#"name."first name".007"#);
+
+    assert_eq!(Member::Bare(Span::new(0, 4)).describe(&source), "name");
+    assert_eq!(
+        Member::String(Span::new(5, 17), Span::new(6, 16)).describe(&source),
+        "first name"
+    );
+    assert_eq!(
+        Member::Int(BigInt::from(7), Span::new(18, 21)).describe(&source),
+        "7"
+    );
+}
+
+#[test]
+fn test_member_repr_round_trips_with_text() {
+    // name."first\"name".007
+    let source = Text::from(r#"name."first\"name".007"#);
+
+    let bare = Member::Bare(Span::new(0, 4));
+    let string = Member::String(Span::new(5, 18), Span::new(6, 17));
+    let int = Member::Int(BigInt::from(7), Span::new(19, 22));
+
+    let repr = round_trip(&bare.to_repr(&source));
+    assert_eq!(repr.text(), Some("name"));
+    assert_eq!(Member::from(repr), bare);
+
+    let repr = round_trip(&string.to_repr(&source));
+    assert_eq!(repr.text(), Some("first\"name"));
+    assert_eq!(Member::from(repr), string);
+
+    let repr = round_trip(&int.to_repr(&source));
+    assert_eq!(repr.text(), None);
+    assert_eq!(Member::from(repr), int);
+}
+
+#[test]
+fn test_plain_members_deserialize_as_member_repr() {
+    let members = vec![
+        Member::Bare(Span::new(0, 4)),
+        Member::String(Span::new(5, 18), Span::new(6, 17)),
+        Member::Int(BigInt::from(7), Span::new(19, 22)),
+    ];
+
+    for member in members {
+        let json = serde_json::to_string(&member).unwrap();
+        let repr: MemberRepr = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(repr.text(), None);
+        assert_eq!(repr, MemberRepr::from(member));
+    }
+}
+
+#[test]
+fn test_bare_and_quoted_members_match() {
+    // name "name" "nme"
+    let source = Text::from(r#"name "name" "nme""#);
+    let bare = Member::Bare(Span::new(0, 4));
+    let quoted = Member::String(Span::new(5, 11), Span::new(6, 10));
+    let other = Member::String(Span::new(12, 17), Span::new(13, 16));
+
+    assert_ne!(bare, quoted);
+    assert!(bare.matches(&quoted, &source));
+    assert!(quoted.matches(&bare, &source));
+    assert!(!bare.matches(&other, &source));
+}
+
+#[test]
+fn test_member_completions_for_partial_member() {
+    // name.na
+    let source = Text::from("name.na");
+    let known = vec!["name".to_string(), "nation".to_string(), "size".to_string()];
+    let path = ColumnPathMembers::from_vec(vec![
+        Member::Bare(Span::new(0, 4)),
+        Member::Bare(Span::new(5, 7)),
+    ])
+    .tagged(Span::new(0, 7));
+
+    ExpandContext::with_empty(&source, |context| {
+        let context = context.with_known_columns(&known);
+
+        assert_eq!(
+            path.member_completions(&context),
+            vec!["name".to_string(), "nation".to_string()]
+        );
+    });
+}
+
+#[test]
+fn test_member_completions_after_trailing_dot() {
+    // name.
+    let source = Text::from("name.");
+    let known = vec!["name".to_string(), "size".to_string()];
+    let path =
+        ColumnPathMembers::from_vec(vec![Member::Bare(Span::new(0, 4))]).tagged(Span::new(0, 4));
+
+    ExpandContext::with_empty(&source, |context| {
+        let context = context.with_known_columns(&known);
+
+        assert_eq!(path.member_completions(&context), known);
+    });
+}
+
+#[test]
+fn test_display_path_quotes_only_when_needed() {
+    // a."b"."c d".007
+    let source = Text::from(r#"a."b"."c d".007"#);
+    let path = ColumnPathMembers::from_vec(vec![
+        Member::Bare(Span::new(0, 1)),
+        Member::String(Span::new(2, 5), Span::new(3, 4)),
+        Member::String(Span::new(6, 11), Span::new(7, 10)),
+        Member::Int(BigInt::from(7), Span::new(12, 15)),
+    ])
+    .tagged(Span::new(0, 15));
+
+    assert_eq!(path.display_path(&source), r#"a.b."c d".7"#);
+}
+
+#[test]
+fn test_display_path_escapes_quoted_names() {
+    // "a\\b"
+    let source = Text::from(r#""a\\b""#);
+    let path = ColumnPathMembers::from_vec(vec![Member::String(Span::new(0, 6), Span::new(1, 5))])
+        .tagged(Span::new(0, 6));
+
+    assert_eq!(path.display_path(&source), r#""a\\b""#);
+
+    // `say "hi"`
+    let source = Text::from(r#"`say "hi"`"#);
+    let path = ColumnPathMembers::from_vec(vec![Member::String(Span::new(0, 10), Span::new(1, 9))])
+        .tagged(Span::new(0, 10));
+
+    assert_eq!(path.display_path(&source), r#"`say "hi"`"#);
+}
+
+#[test]
+fn test_interned_names_share_storage() {
+    let source = Text::from("name name");
+    let first = Member::Bare(Span::new(0, 4));
+    let second = Member::Bare(Span::new(5, 9));
+    let interner = MemberInterner::new();
+
+    ExpandContext::with_empty(&source, |context| {
+        let plain = (
+            first.to_interned_path_member(&context),
+            second.to_interned_path_member(&context),
+        );
+
+        assert_eq!(member_name(&plain.0), member_name(&plain.1));
+        assert!(!member_name(&plain.0).shares_storage_with(member_name(&plain.1)));
+
+        let context = context.with_interner(&interner);
+        let interned = (
+            first.to_interned_path_member(&context),
+            second.to_interned_path_member(&context),
+        );
+
+        assert_eq!(interned, plain);
+        assert!(member_name(&interned.0).shares_storage_with(member_name(&interned.1)));
+    })
+}
+
+/// Build the tokens, then run `callback` with an iterator over them, a context for their
+/// source, and the built tokens themselves
+fn with_tokens<T>(
+    tokens: Vec<CurriedToken>,
+    callback: impl FnOnce(&mut TokensIterator<'_>, ExpandContext, &[TokenNode]) -> T,
+) -> T {
+    let tokens = b::token_list(tokens);
+    let (tokens, source) = b::build(tokens);
+
+    ExpandContext::with_empty(&Text::from(source), |context| {
+        let tokens = tokens.expect_list();
+        let mut iterator = TokensIterator::all(tokens.item, tokens.span);
+
+        callback(&mut iterator, context, tokens.item)
+    })
+}
+
+/// Lex `source` with the real lexer, then run `callback` with an iterator over the tokens
+/// and a context for the source
+fn with_lexed_tokens<T>(
+    source: &str,
+    callback: impl FnOnce(&mut TokensIterator<'_>, ExpandContext) -> T,
+) -> T {
+    let (_, tokens) = token_list(nom_input(source)).unwrap();
+
+    ExpandContext::with_empty(&Text::from(source), |context| {
+        let mut iterator = TokensIterator::all(&tokens.item, tokens.span);
+
+        callback(&mut iterator, context)
+    })
+}
+
+fn parse_tokens<T: Eq + HasSpan + Clone + Debug + 'static>(
+    shape: impl ExpandSyntax<Output = T>,
+    tokens: Vec<CurriedToken>,
+    expected: impl FnOnce(&[TokenNode]) -> T,
+) -> T {
+    with_tokens(tokens, |iterator, context, tokens| {
+        let expr = expand_syntax(&shape, iterator, &context);
+
+        let expr = match expr {
+            Ok(expr) => expr,
+            Err(err) => {
+                crate::cli::print_err(err.into(), &BasicHost, context.source().clone());
+                panic!("Parse failed");
+            }
+        };
+
+        assert_eq!(expr, expected(tokens));
+        expr
+    })
+}
+
+/// Expand a variable path, then evaluate it with `it` as `$it`
+fn evaluate_tokens(
+    tokens: Vec<CurriedToken>,
+    it: Tagged<Value>,
+) -> Result<Tagged<Value>, ShellError> {
+    with_tokens(tokens, |iterator, context, _| {
+        let expr = match expand_expr(&VariablePathShape, iterator, &context) {
+            Ok(expr) => expr,
+            Err(err) => panic!("expected {:?} to parse, got {:?}", context.source(), err),
+        };
+
+        evaluate_baseline_expr(
+            &expr,
+            &CommandRegistry::empty(),
+            &Scope::it_value(it),
+            context.source(),
+        )
+    })
+}
+
+/// The items of a table, without their tags
+fn table_items(value: Tagged<Value>) -> Vec<Value> {
+    match value.item {
+        Value::Table(items) => items.into_iter().map(|item| item.item).collect(),
+        other => panic!("expected a table, got {:?}", other),
+    }
+}
+
+/// Expand the head of an expression, then the continuation that follows it
+fn parse_continuation(
+    tokens: Vec<CurriedToken>,
+    expected: impl FnOnce(&[TokenNode]) -> ExpressionContinuation,
+) {
+    with_tokens(tokens, |iterator, context, tokens| {
+        if let Err(err) = expand_expr(&AnyExpressionStartShape, iterator, &context) {
+            panic!(
+                "expected the head of {:?} to parse, got {:?}",
+                context.source(),
+                err
+            );
+        }
+
+        let continuation = match expand_syntax(&ExpressionContinuationShape, iterator, &context) {
+            Ok(continuation) => continuation,
+            Err(err) => panic!("expected {:?} to continue, got {:?}", context.source(), err),
+        };
+
+        assert_eq!(continuation, expected(tokens));
+    })
+}
+
+/// Expand the head of an expression, then return the source text of the continuation that
+/// follows it
+fn continuation_source_text(tokens: Vec<CurriedToken>) -> String {
+    with_tokens(tokens, |iterator, context, _| {
+        expand_expr(&AnyExpressionStartShape, iterator, &context).unwrap();
+
+        match expand_syntax(&ExpressionContinuationShape, iterator, &context) {
+            Ok(continuation) => continuation.source_text(context.source()).to_string(),
+            Err(err) => panic!("expected {:?} to continue, got {:?}", context.source(), err),
+        }
+    })
+}
+
+fn parse_error<T: Debug + 'static>(
+    shape: impl ExpandSyntax<Output = T>,
+    tokens: Vec<CurriedToken>,
+) -> ParseError {
+    with_tokens(tokens, |iterator, context, _| {
+        match expand_syntax(&shape, iterator, &context) {
+            Ok(expr) => panic!("expected a parse error, got {:?}", expr),
+            Err(err) => err,
+        }
+    })
+}
+
+fn parse_warnings(known_columns: &[String], tokens: Vec<CurriedToken>) -> Vec<ParseWarning> {
+    let warnings = RefCell::new(vec![]);
+
+    with_tokens(tokens, |iterator, context, _| {
+        let context = context
+            .with_known_columns(known_columns)
+            .with_warnings(&warnings);
+
+        if let Err(err) = expand_syntax(&MemberShape, iterator, &context) {
+            panic!("expected the parse to succeed, got {:?}", err);
+        }
+    });
+
+    warnings.into_inner()
+}
+
+fn parse_column_error(known_columns: &[String], tokens: Vec<CurriedToken>) -> ParseError {
+    with_tokens(tokens, |iterator, context, _| {
+        let context = context.with_known_columns(known_columns);
+
+        match expand_syntax(&MemberShape, iterator, &context) {
+            Ok(member) => panic!("expected the parse to fail, got {:?}", member),
+            Err(err) => err,
+        }
+    })
+}
+
+fn parse_reserved_warnings(
+    reserved: Option<&[String]>,
+    tokens: Vec<CurriedToken>,
+) -> Vec<ParseWarning> {
+    let warnings = RefCell::new(vec![]);
+
+    with_tokens(tokens, |iterator, context, _| {
+        let mut context = context.with_warnings(&warnings);
+
+        if let Some(reserved) = reserved {
+            context = context.with_reserved_members(reserved);
+        }
+
+        if let Err(err) = expand_syntax(&MemberShape, iterator, &context) {
+            panic!("expected the parse to succeed, got {:?}", err);
+        }
+    });
+
+    warnings.into_inner()
+}
+
+#[cfg(not(coloring_in_tokens))]
+fn color_shapes(
+    shape: impl FallibleColorSyntax<Info = (), Input = ()>,
+    token_nodes: &mut TokensIterator<'_>,
+    context: &ExpandContext,
+) -> Vec<Spanned<FlatShape>> {
+    let mut shapes = vec![];
+    color_fallible_syntax(&shape, token_nodes, context, &mut shapes).unwrap();
+    shapes
+}
+
+#[cfg(coloring_in_tokens)]
+fn color_shapes(
+    shape: impl FallibleColorSyntax<Info = (), Input = ()>,
+    token_nodes: &mut TokensIterator<'_>,
+    context: &ExpandContext,
+) -> Vec<Spanned<FlatShape>> {
+    let (result, shapes) = token_nodes
+        .atomic_returning_shapes(|token_nodes| color_fallible_syntax(&shape, token_nodes, context));
+    result.unwrap();
+    shapes
+}
+
+fn expand_member(tokens: Vec<CurriedToken>) -> Member {
+    with_tokens(tokens, |iterator, context, _| {
+        match expand_syntax(&MemberShape, iterator, &context) {
+            Ok(member) => member,
+            Err(err) => panic!("expected {:?} to parse, got {:?}", context.source(), err),
+        }
+    })
+}
+
+fn expect_int_member_mismatch(text: &str) {
+    with_tokens(
+        vec![b::bare(text)],
+        |iterator, context, _| match expand_syntax(&IntMemberShape, iterator, &context) {
+            Err(err) => match err.reason() {
+                ParseErrorReason::Mismatch { expected, .. } => {
+                    assert_eq!(*expected, "integer member")
+                }
+                other => panic!("expected a mismatch error, got {:?}", other),
+            },
+            Ok(member) => panic!("expected {:?} not to parse, got {:?}", text, member),
+        },
+    );
+}
+
+fn expand_interpolation(parts: Vec<CurriedToken>) -> Result<hir::Expression, ParseError> {
+    with_tokens(vec![b::interpolation(parts)], |iterator, context, _| {
+        expand_expr(&AnyExpressionShape, iterator, &context)
+    })
+}
+
+fn synthetic_string(text: &str, span: Span) -> hir::Expression {
+    hir::RawExpression::Synthetic(hir::Synthetic::String(text.to_string())).spanned(span)
+}
+
+/// The span of the variable path in the tokens, along with the tokens' source
+fn expand_path_span(tokens: Vec<CurriedToken>) -> (Span, String) {
+    with_tokens(tokens, |iterator, context, _| {
+        match expand_expr(&VariablePathShape, iterator, &context) {
+            Ok(expr) => (expr.span, context.source().to_string()),
+            Err(err) => panic!("expected {:?} to parse, got {:?}", context.source(), err),
+        }
+    })
+}
+
+/// Peek for an infix operator, checking that the peek didn't move the cursor
+fn peek_infix(tokens: Vec<CurriedToken>) -> bool {
+    with_tokens(tokens, |iterator, context, _| {
+        let found = InfixShape::peek(iterator, &context);
+        assert_eq!(iterator.pos(false), Some(0));
+
+        found
+    })
+}
+
+fn peek_member(tokens: Vec<CurriedToken>) -> bool {
+    with_tokens(tokens, |iterator, context, _| {
+        let found = MemberShape::peek(iterator, &context);
+        assert_eq!(iterator.pos(false), Some(0));
+
+        found
+    })
+}
+
+/// The spans of a delimited `from..to` token, like `(1..5)` or `[0..3]`: the whole
+/// group, `from`, the two dots and `to`
+fn expect_range(token: &TokenNode) -> (Span, Span, Span, Span) {
+    match token {
+        TokenNode::Delimited(delimited) => {
+            let children = &delimited.item.children;
+
+            (
+                delimited.span,
+                children[0].span(),
+                children[1].span().until(children[2].span()),
+                children[3].span(),
+            )
+        }
+        other => panic!("expected a delimited token, got {:?}", other),
+    }
+}
+
+fn inner_string_span(span: Span) -> Span {
+    Span::new(span.start() + 1, span.end() - 1)
+}
+
+fn int_member(int: impl Into<BigInt>) -> Member {
+    Member::Int(int.into(), Span::new(0, 1))
+}
+
+fn bare_path(source: &str) -> Tagged<ColumnPathMembers> {
+    let mut start = 0;
+    let mut members = ColumnPathMembers::new();
+
+    for name in source.split('.') {
+        members.push(Member::Bare(Span::new(start, start + name.len())));
+        start += name.len() + 1;
+    }
+
+    members.tagged(Span::new(0, source.len()))
+}
+
+fn round_trip(repr: &MemberRepr) -> MemberRepr {
+    let json = serde_json::to_string(repr).unwrap();
+    serde_json::from_str(&json).unwrap()
+}
+
+fn member_name(member: &PathMember) -> &MemberName {
+    match &member.item {
+        RawPathMember::String(name) => name,
+        other => panic!("expected a string member, got {:?}", other),
+    }
 }
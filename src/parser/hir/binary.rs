@@ -14,6 +14,19 @@ pub struct Binary {
     left: Expression,
     op: Spanned<Operator>,
     right: Expression,
+    /// Whether the expression was written in parentheses, which closes it off from the
+    /// operators around it
+    #[new(default)]
+    parenthesized: bool,
+}
+
+impl Binary {
+    pub(crate) fn into_parenthesized(self) -> Binary {
+        Binary {
+            parenthesized: true,
+            ..self
+        }
+    }
 }
 
 impl fmt::Display for Binary {
@@ -1,24 +1,202 @@
-use crate::parser::hir::Expression;
+use crate::parser::hir::syntax_shape::TypeName;
+use crate::parser::hir::{Expression, Range};
 use crate::prelude::*;
 use crate::traits::{DebugDocBuilder as b, PrettyDebug};
 use derive_new::new;
-use getset::{Getters, MutGetters};
-use serde::{Deserialize, Serialize};
+use getset::Getters;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cell::RefCell;
+use std::collections::HashSet;
 use std::fmt;
+use std::ops::Deref;
+use std::sync::Arc;
+
+/// The name of a string path member. Names are reference counted, so a `MemberInterner`
+/// can hand out the same storage for every reference to a column.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct MemberName(Arc<str>);
+
+impl MemberName {
+    pub fn shares_storage_with(&self, other: &MemberName) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Deref for MemberName {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for MemberName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", &*self.0)
+    }
+}
+
+impl fmt::Display for MemberName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", &*self.0)
+    }
+}
+
+impl From<&str> for MemberName {
+    fn from(name: &str) -> MemberName {
+        MemberName(name.into())
+    }
+}
+
+impl From<String> for MemberName {
+    fn from(name: String) -> MemberName {
+        MemberName(name.into())
+    }
+}
+
+impl From<&String> for MemberName {
+    fn from(name: &String) -> MemberName {
+        MemberName(name[..].into())
+    }
+}
+
+impl From<&MemberName> for String {
+    fn from(name: &MemberName) -> String {
+        name.to_string()
+    }
+}
+
+impl Serialize for MemberName {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for MemberName {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<MemberName, D::Error> {
+        String::deserialize(deserializer).map(MemberName::from)
+    }
+}
+
+/// Shares the storage of repeated member names. Parsing with an interner on the
+/// `ExpandContext` means that a script referring to the same column many times only
+/// allocates its name once.
+#[derive(Debug, Default)]
+pub struct MemberInterner {
+    names: RefCell<HashSet<Arc<str>>>,
+}
+
+impl MemberInterner {
+    pub fn new() -> MemberInterner {
+        MemberInterner::default()
+    }
+
+    pub fn intern(&self, name: &str) -> MemberName {
+        let mut names = self.names.borrow_mut();
+
+        match names.get(name) {
+            Some(existing) => MemberName(existing.clone()),
+            None => {
+                let name: Arc<str> = name.into();
+                names.insert(name.clone());
+                MemberName(name)
+            }
+        }
+    }
+}
 
 #[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
 pub enum RawPathMember {
-    String(String),
+    String(MemberName),
     Int(BigInt),
+    /// An index that fits in an `i64`, which is cheaper to copy and compare than `Int`
+    SmallInt(i64),
+    /// `~`, the key-value pairs of a row
+    Entries,
+    /// `*`, every row of a table
+    Wildcard,
+    /// A pattern like `user_*`, the columns whose names match it
+    Glob(String),
+    /// `#2`, the column at a position in a row, counted from zero
+    ColumnNumber(BigInt),
+    /// `data...`, the value of a member with its nested tables flattened into it
+    Splat(Box<PathMember>),
+    /// `(a ?? b)`, the value of an expression evaluated against the value the member is
+    /// taken from
+    Computed(Box<Expression>),
+    /// `[.active]`, the rows of a table that a predicate holds for
+    Filter(Box<Expression>),
+    /// `(...$defaults)`, the value with the columns of a row that it doesn't have yet
+    Spread(Box<Expression>),
+    /// `(left.name | right.name)`, the value of the first expression that isn't nothing
+    Alternatives(Vec<Expression>),
+    /// A slice of a table, like `2..5`, `2..=5` or `2..`
+    Range {
+        from: BigInt,
+        to: Option<BigInt>,
+        inclusive: bool,
+    },
+    /// A slice of a table whose bounds have to be evaluated first, like `$a..$b`
+    ComputedRange(Box<Range>),
+    /// `name if .active`, the member's value if the predicate holds, and nothing otherwise
+    Conditional(Box<PathMember>, Box<Expression>),
+    /// `age as int`, the member's value converted to the type
+    Cast(Box<PathMember>, TypeName),
 }
 
 pub type PathMember = Spanned<RawPathMember>;
 
+impl RawPathMember {
+    /// The expressions inside the member, like the `a ?? b` in `(a ?? b)` or the
+    /// predicate of a conditional member. Most members don't have any.
+    pub fn expressions(&self) -> Vec<&Expression> {
+        match self {
+            RawPathMember::Computed(expr)
+            | RawPathMember::Filter(expr)
+            | RawPathMember::Spread(expr) => vec![expr.as_ref()],
+            RawPathMember::Alternatives(exprs) => exprs.iter().collect(),
+            RawPathMember::ComputedRange(range) => {
+                range.from().iter().chain(range.to().iter()).collect()
+            }
+            RawPathMember::Conditional(member, predicate) => {
+                let mut exprs = member.item.expressions();
+                exprs.push(predicate.as_ref());
+                exprs
+            }
+            RawPathMember::Splat(member) | RawPathMember::Cast(member, _) => {
+                member.item.expressions()
+            }
+            RawPathMember::String(_)
+            | RawPathMember::Int(_)
+            | RawPathMember::SmallInt(_)
+            | RawPathMember::ColumnNumber(_)
+            | RawPathMember::Entries
+            | RawPathMember::Wildcard
+            | RawPathMember::Glob(_)
+            | RawPathMember::Range { .. } => vec![],
+        }
+    }
+}
+
 impl PrettyDebug for &PathMember {
     fn pretty_debug(&self) -> DebugDocBuilder {
         match &self.item {
             RawPathMember::String(string) => b::primitive(format!("{:?}", string)),
             RawPathMember::Int(int) => b::primitive(format!("{}", int)),
+            RawPathMember::SmallInt(int) => b::primitive(format!("{}", int)),
+            RawPathMember::Entries => b::primitive("~"),
+            RawPathMember::Wildcard => b::primitive("*"),
+            RawPathMember::Glob(pattern) => b::primitive(pattern),
+            RawPathMember::ColumnNumber(..)
+            | RawPathMember::Splat(..)
+            | RawPathMember::Computed(..)
+            | RawPathMember::Filter(..)
+            | RawPathMember::Spread(..)
+            | RawPathMember::Alternatives(..)
+            | RawPathMember::Range { .. }
+            | RawPathMember::ComputedRange(..)
+            | RawPathMember::Conditional(..)
+            | RawPathMember::Cast(..) => b::primitive(format!("{}", self.item)),
         }
     }
 }
@@ -32,6 +210,12 @@ pub struct ColumnPath {
 }
 
 impl ColumnPath {
+    /// A path built outside of the parser, like by a command or a plugin. Members built
+    /// this way usually have unknown spans, so compare them by their items.
+    pub fn build(members: Vec<PathMember>) -> ColumnPath {
+        ColumnPath { members }
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = &PathMember> {
         self.members.iter()
     }
@@ -79,17 +263,131 @@ impl fmt::Display for RawPathMember {
         match self {
             RawPathMember::String(string) => write!(f, "{}", string),
             RawPathMember::Int(int) => write!(f, "{}", int),
+            RawPathMember::SmallInt(int) => write!(f, "{}", int),
+            RawPathMember::Entries => write!(f, "~"),
+            RawPathMember::Wildcard => write!(f, "*"),
+            RawPathMember::Glob(pattern) => write!(f, "{}", pattern),
+            RawPathMember::ColumnNumber(number) => write!(f, "#{}", number),
+            RawPathMember::Splat(member) => write!(f, "{}...", member.item),
+            RawPathMember::Computed(expr) => write!(f, "({})", expr),
+            RawPathMember::Filter(expr) => write!(f, "[{}]", expr),
+            RawPathMember::Spread(expr) => write!(f, "(...{})", expr),
+            RawPathMember::Alternatives(exprs) => {
+                let exprs: Vec<String> = exprs.iter().map(|expr| expr.to_string()).collect();
+                write!(f, "({})", exprs.join(" | "))
+            }
+            RawPathMember::Range {
+                from,
+                to,
+                inclusive,
+            } => {
+                write!(f, "{}{}", from, if *inclusive { "..=" } else { ".." })?;
+
+                match to {
+                    Some(to) => write!(f, "{}", to),
+                    None => Ok(()),
+                }
+            }
+            RawPathMember::ComputedRange(range) => write!(f, "{}", range),
+            RawPathMember::Conditional(member, predicate) => {
+                write!(f, "{} if {}", member.item, predicate)
+            }
+            RawPathMember::Cast(member, type_name) => {
+                write!(f, "{} as {}", member.item, type_name.as_str())
+            }
         }
     }
 }
 
 impl PathMember {
-    pub fn string(string: impl Into<String>, span: impl Into<Span>) -> PathMember {
+    pub fn string(string: impl Into<MemberName>, span: impl Into<Span>) -> PathMember {
         RawPathMember::String(string.into()).spanned(span.into())
     }
 
+    /// The member that a bare word `name` lowers into, for building a path without any
+    /// source to point into. The member's span is unknown.
+    pub fn bare_from(name: &str) -> PathMember {
+        PathMember::string(name, Span::unknown())
+    }
+
+    /// An index member, which is a `SmallInt` unless the index doesn't fit in an `i64`
     pub fn int(int: impl Into<BigInt>, span: impl Into<Span>) -> PathMember {
-        RawPathMember::Int(int.into()).spanned(span.into())
+        let int = int.into();
+
+        match int.to_i64() {
+            Some(int) => PathMember::small_int(int, span),
+            None => RawPathMember::Int(int).spanned(span.into()),
+        }
+    }
+
+    pub fn small_int(int: i64, span: impl Into<Span>) -> PathMember {
+        RawPathMember::SmallInt(int).spanned(span.into())
+    }
+
+    pub fn entries(span: impl Into<Span>) -> PathMember {
+        RawPathMember::Entries.spanned(span.into())
+    }
+
+    pub fn wildcard(span: impl Into<Span>) -> PathMember {
+        RawPathMember::Wildcard.spanned(span.into())
+    }
+
+    pub fn glob(pattern: impl Into<String>, span: impl Into<Span>) -> PathMember {
+        RawPathMember::Glob(pattern.into()).spanned(span.into())
+    }
+
+    pub fn column_number(number: impl Into<BigInt>, span: impl Into<Span>) -> PathMember {
+        RawPathMember::ColumnNumber(number.into()).spanned(span.into())
+    }
+
+    pub fn splat(member: PathMember, span: impl Into<Span>) -> PathMember {
+        RawPathMember::Splat(Box::new(member)).spanned(span.into())
+    }
+
+    pub fn computed(expr: Expression, span: impl Into<Span>) -> PathMember {
+        RawPathMember::Computed(Box::new(expr)).spanned(span.into())
+    }
+
+    pub fn filter(predicate: Expression, span: impl Into<Span>) -> PathMember {
+        RawPathMember::Filter(Box::new(predicate)).spanned(span.into())
+    }
+
+    pub fn spread(expr: Expression, span: impl Into<Span>) -> PathMember {
+        RawPathMember::Spread(Box::new(expr)).spanned(span.into())
+    }
+
+    pub fn alternatives(exprs: Vec<Expression>, span: impl Into<Span>) -> PathMember {
+        RawPathMember::Alternatives(exprs).spanned(span.into())
+    }
+
+    pub fn range(
+        from: impl Into<BigInt>,
+        to: Option<BigInt>,
+        inclusive: bool,
+        span: impl Into<Span>,
+    ) -> PathMember {
+        RawPathMember::Range {
+            from: from.into(),
+            to,
+            inclusive,
+        }
+        .spanned(span.into())
+    }
+
+    pub fn computed_range(range: Range, span: impl Into<Span>) -> PathMember {
+        RawPathMember::ComputedRange(Box::new(range)).spanned(span.into())
+    }
+
+    pub fn conditional(
+        member: PathMember,
+        predicate: Expression,
+        span: impl Into<Span>,
+    ) -> PathMember {
+        RawPathMember::Conditional(Box::new(member), Box::new(predicate)).spanned(span.into())
+    }
+
+    pub fn cast(member: PathMember, type_name: TypeName, span: impl Into<Span>) -> PathMember {
+        RawPathMember::Cast(Box::new(member), type_name).spanned(span.into())
     }
 }
 
@@ -98,37 +396,84 @@ impl FormatDebug for PathMember {
         match &self.item {
             RawPathMember::String(string) => f.say_str("member", &string),
             RawPathMember::Int(int) => f.say_block("member", |f| write!(f, "{}", int)),
+            RawPathMember::SmallInt(int) => f.say_block("member", |f| write!(f, "{}", int)),
+            RawPathMember::Entries => f.say_str("member", "~"),
+            RawPathMember::Wildcard => f.say_str("member", "*"),
+            RawPathMember::Glob(pattern) => f.say_str("member", pattern),
+            RawPathMember::ColumnNumber(..)
+            | RawPathMember::Splat(..)
+            | RawPathMember::Computed(..)
+            | RawPathMember::Filter(..)
+            | RawPathMember::Spread(..)
+            | RawPathMember::Alternatives(..)
+            | RawPathMember::Range { .. }
+            | RawPathMember::ComputedRange(..)
+            | RawPathMember::Conditional(..)
+            | RawPathMember::Cast(..) => f.say_str("member", self.item.to_string()),
+        }
+    }
+}
+
+/// A marker right after a path member, like the `?` in `$it.a?` or the `!` in `$it.b!`
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+pub enum MemberMarker {
+    /// `?`, the member may be missing
+    Optional,
+    /// `!`, the member must be present
+    Required,
+}
+
+impl MemberMarker {
+    pub fn as_str(&self) -> &str {
+        match self {
+            MemberMarker::Optional => "?",
+            MemberMarker::Required => "!",
         }
     }
 }
 
 #[derive(
-    Debug,
-    Clone,
-    Eq,
-    PartialEq,
-    Ord,
-    PartialOrd,
-    Hash,
-    Getters,
-    MutGetters,
-    Serialize,
-    Deserialize,
-    new,
+    Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Getters, Serialize, Deserialize, new,
 )]
 #[get = "pub(crate)"]
 pub struct Path {
     head: Expression,
-    #[get_mut = "pub(crate)"]
+    /// Only ever grown through `push_member`, which keeps `markers` in step with it
     tail: Vec<PathMember>,
+    /// A lenient path ends in `.?`, and evaluates to nothing instead of failing when a
+    /// member is missing
+    #[new(default)]
+    lenient: bool,
+    /// The marker after each member of the tail, if it has one. Empty when no member
+    /// has a marker.
+    ///
+    /// Markers live here rather than on `PathMember`, which is also what a `ColumnPath`
+    /// is made of. Commands and plugins build and compare column paths, and look columns
+    /// up by them, where a marker means nothing. Only evaluating a variable path, like
+    /// `$record.name?`, does anything with a marker, so column path arguments don't take
+    /// them.
+    #[new(default)]
+    markers: Vec<Option<MemberMarker>>,
+    /// A rooted path starts with a path separator, like `$it./a/b`, and is anchored to
+    /// the root of the value
+    #[new(default)]
+    rooted: bool,
 }
 
 impl fmt::Display for Path {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.head)?;
 
-        for entry in &self.tail {
-            write!(f, ".{}", entry.item)?;
+        for (i, entry) in self.tail.iter().enumerate() {
+            write!(f, "{}{}", self.separator_before(i), entry.item)?;
+
+            if let Some(marker) = self.marker(i) {
+                write!(f, "{}", marker.as_str())?;
+            }
+        }
+
+        if self.lenient {
+            write!(f, ".?")?;
         }
 
         Ok(())
@@ -136,6 +481,70 @@ impl fmt::Display for Path {
 }
 
 impl Path {
+    pub(crate) fn into_lenient(self) -> Path {
+        Path {
+            lenient: true,
+            ..self
+        }
+    }
+
+    pub(crate) fn into_rooted(self) -> Path {
+        Path {
+            rooted: true,
+            ..self
+        }
+    }
+
+    fn separator_before(&self, index: usize) -> &'static str {
+        if self.rooted && index == 0 {
+            "./"
+        } else {
+            "."
+        }
+    }
+
+    /// Attach a marker to each member of the tail. If none of the members has a marker,
+    /// the path is left unmarked.
+    pub(crate) fn with_markers(self, markers: Vec<Option<MemberMarker>>) -> Path {
+        if markers.iter().all(Option::is_none) {
+            return self;
+        }
+
+        Path { markers, ..self }
+    }
+
+    /// Add a member, and its marker if it has one, to the end of the tail
+    pub(crate) fn push_member(self, member: PathMember, marker: Option<MemberMarker>) -> Path {
+        let mut markers = self.markers;
+
+        if marker.is_some() || !markers.is_empty() {
+            markers.resize(self.tail.len(), None);
+            markers.push(marker);
+        }
+
+        let mut tail = self.tail;
+        tail.push(member);
+
+        Path {
+            tail,
+            markers,
+            ..self
+        }
+    }
+
+    /// The marker after the tail member at `index`, if it has one
+    pub fn marker(&self, index: usize) -> Option<MemberMarker> {
+        self.markers.get(index).and_then(|marker| *marker)
+    }
+
+    /// Whether a missing member at `index` short-circuits the rest of the path, so the
+    /// whole path evaluates to nothing. That's every member of a lenient path, and
+    /// otherwise only the members marked with `?`; the `c` in `$a.b?.c` must still be
+    /// present whenever `b` is.
+    pub fn short_circuits_at(&self, index: usize) -> bool {
+        self.lenient || self.marker(index) == Some(MemberMarker::Optional)
+    }
+
     pub(crate) fn parts(self) -> (Expression, Vec<PathMember>) {
         (self.head, self.tail)
     }
@@ -145,8 +554,16 @@ impl FormatDebug for Path {
     fn fmt_debug(&self, f: &mut DebugFormatter, source: &str) -> fmt::Result {
         write!(f, "{}", self.head.debug(source))?;
 
-        for part in &self.tail {
-            write!(f, ".{}", part.item)?;
+        for (i, part) in self.tail.iter().enumerate() {
+            write!(f, "{}{}", self.separator_before(i), part.item)?;
+
+            if let Some(marker) = self.marker(i) {
+                write!(f, "{}", marker.as_str())?;
+            }
+        }
+
+        if self.lenient {
+            write!(f, ".?")?;
         }
 
         Ok(())
@@ -0,0 +1,91 @@
+use crate::commands::classified::ClassifiedPipeline;
+use crate::parser::hir::Expression;
+use crate::prelude::*;
+
+use getset::Getters;
+use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+/// An expression piped into a pipeline, like `$it.users | each { ... }`. The commands
+/// after the `|` are classified by the pipeline parser, like a pipeline of their own.
+///
+/// A classified pipeline can't be ordered, hashed or serialized like the rest of the
+/// HIR. A pipe is ordered and hashed by its head and spans instead, which tell apart the
+/// pipes in one source, and it can't be serialized at all.
+#[derive(Debug, Clone, Eq, PartialEq, Getters)]
+#[get = "pub(crate)"]
+pub struct Pipe {
+    head: Expression,
+    pipe: Span,
+    pipeline: ClassifiedPipeline,
+}
+
+impl Pipe {
+    pub(crate) fn new(head: Expression, pipe: Span, pipeline: ClassifiedPipeline) -> Pipe {
+        Pipe {
+            head,
+            pipe,
+            pipeline,
+        }
+    }
+
+    fn key(&self) -> (&Expression, Span, Span) {
+        (&self.head, self.pipe, self.pipeline.span())
+    }
+}
+
+impl PartialOrd for Pipe {
+    fn partial_cmp(&self, other: &Pipe) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Pipe {
+    fn cmp(&self, other: &Pipe) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+#[allow(clippy::derive_hash_xor_eq)]
+impl Hash for Pipe {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state)
+    }
+}
+
+impl Serialize for Pipe {
+    fn serialize<S: Serializer>(&self, _serializer: S) -> Result<S::Ok, S::Error> {
+        Err(ser::Error::custom(
+            "a pipeline inside an expression can't be serialized",
+        ))
+    }
+}
+
+impl<'de> Deserialize<'de> for Pipe {
+    fn deserialize<D: Deserializer<'de>>(_deserializer: D) -> Result<Pipe, D::Error> {
+        Err(de::Error::custom(
+            "a pipeline inside an expression can't be deserialized",
+        ))
+    }
+}
+
+impl fmt::Display for Pipe {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "({} | {} commands)",
+            self.head,
+            self.pipeline.commands.item.len()
+        )
+    }
+}
+
+impl FormatDebug for Pipe {
+    fn fmt_debug(&self, f: &mut DebugFormatter, source: &str) -> fmt::Result {
+        write!(f, "{}", self.head.debug(source))?;
+        write!(f, " | ")?;
+        write!(f, "{}", self.pipeline.debug(source))
+    }
+}
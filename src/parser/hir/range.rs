@@ -0,0 +1,65 @@
+use crate::parser::hir::Expression;
+use crate::prelude::*;
+
+use derive_new::new;
+use getset::Getters;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(
+    Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Getters, Serialize, Deserialize, new,
+)]
+#[get = "pub(crate)"]
+pub struct Range {
+    /// `None` for a range without a lower bound, like `..5`
+    from: Option<Expression>,
+    dotdot: Span,
+    /// `None` for a range without an upper bound, like `2..`
+    to: Option<Expression>,
+    /// Whether the upper bound is part of the range, like in `2..=5`
+    inclusive: bool,
+}
+
+impl Range {
+    fn operator(&self) -> &'static str {
+        if self.inclusive {
+            "..="
+        } else {
+            ".."
+        }
+    }
+}
+
+impl fmt::Display for Range {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "(")?;
+
+        if let Some(from) = &self.from {
+            write!(f, "{}", from)?;
+        }
+
+        write!(f, "{}", self.operator())?;
+
+        if let Some(to) = &self.to {
+            write!(f, "{}", to)?;
+        }
+
+        write!(f, ")")
+    }
+}
+
+impl FormatDebug for Range {
+    fn fmt_debug(&self, f: &mut DebugFormatter, source: &str) -> fmt::Result {
+        if let Some(from) = &self.from {
+            write!(f, "{}", from.debug(source))?;
+        }
+
+        write!(f, "{}", self.operator())?;
+
+        if let Some(to) = &self.to {
+            write!(f, "{}", to.debug(source))?;
+        }
+
+        Ok(())
+    }
+}
@@ -8,7 +8,9 @@ use crate::commands::{
     ClassifiedCommand, Command,
 };
 use crate::parser::hir::expand_external_tokens::ExternalTokensShape;
+use crate::parser::hir::path::MemberInterner;
 use crate::parser::hir::syntax_shape::block::AnyBlockShape;
+pub(crate) use crate::parser::hir::syntax_shape::block::ConditionShape;
 use crate::parser::hir::tokens_iterator::Peeked;
 use crate::parser::parse_command::{parse_command_tail, CommandTailShape};
 use crate::parser::{hir, hir::TokensIterator, Operator, RawToken, TokenNode};
@@ -16,6 +18,7 @@ use crate::prelude::*;
 use derive_new::new;
 use getset::Getters;
 use serde::{Deserialize, Serialize};
+use std::cell::{Cell, RefCell};
 use std::fmt;
 use std::path::{Path, PathBuf};
 
@@ -27,14 +30,25 @@ pub(crate) use self::expression::file_path::FilePathShape;
 pub(crate) use self::expression::list::{BackoffColoringMode, ExpressionListShape};
 pub(crate) use self::expression::number::{IntShape, NumberShape};
 pub(crate) use self::expression::pattern::{BarePatternShape, PatternShape};
-pub(crate) use self::expression::string::StringShape;
-pub(crate) use self::expression::unit::UnitShape;
+pub(crate) use self::expression::range::{
+    expand_delimited_range, expand_range, expand_range_literal, PrimaryExpressionShape,
+    RangeExpression, RangeLiteralShape, RangeShape,
+};
+pub(crate) use self::expression::string::{StringInterpolationShape, StringShape};
+pub(crate) use self::expression::type_name::{TypeName, TypeNameShape};
+pub(crate) use self::expression::unary::{NotExpressionShape, UnaryNegationShape, UnaryNotShape};
+pub(crate) use self::expression::unit::{DurationShape, FilesizeShape, UnitShape};
 pub(crate) use self::expression::variable_path::{
-    ColorableDotShape, ColumnPathShape, DotShape, ExpressionContinuation,
-    ExpressionContinuationShape, Member, MemberShape, PathTailShape, VariablePathShape,
+    expand_relative_column_path, parse_column_path_from_tokens, ColorableDotShape,
+    ColumnPathMembers, ColumnPathShape, DotShape, ExpressionContinuation,
+    ExpressionContinuationShape, InfixShape, IntMemberShape, Member, MemberKind, MemberRepr,
+    MemberShape, PathTailShape, VariablePathShape, VariableShape,
+};
+pub(crate) use self::expression::{
+    continue_coloring_expression, continue_expression, continue_with, AnyExpressionShape,
+    AnyExpressionStartShape,
 };
-pub(crate) use self::expression::{continue_expression, AnyExpressionShape};
-pub(crate) use self::flat_shape::FlatShape;
+pub(crate) use self::flat_shape::{FlatShape, SemanticToken, SEMANTIC_TOKEN_TYPES};
 
 #[cfg(not(coloring_in_tokens))]
 use crate::parser::hir::tokens_iterator::debug::debug_tokens;
@@ -170,7 +184,10 @@ impl ExpandExpression for SyntaxShape {
                     tag,
                 } = column_path;
 
-                Ok(hir::Expression::column_path(column_path, tag.span))
+                Ok(hir::Expression::column_path(
+                    column_path.into_vec(),
+                    tag.span,
+                ))
             }
             SyntaxShape::Number => expand_expr(&NumberShape, token_nodes, context),
             SyntaxShape::Path => expand_expr(&FilePathShape, token_nodes, context),
@@ -196,6 +213,12 @@ impl std::fmt::Display for SyntaxShape {
     }
 }
 
+/// The name of the implicit variable, without its `$`, unless the context renames it
+pub const DEFAULT_IT_VARIABLE: &str = "it";
+
+/// How deeply computed and filter members can nest by default
+pub const DEFAULT_MEMBER_NESTING_LIMIT: usize = 64;
+
 #[derive(Getters, new)]
 pub struct ExpandContext<'context> {
     #[get = "pub(crate)"]
@@ -203,6 +226,32 @@ pub struct ExpandContext<'context> {
     #[get = "pub(crate)"]
     source: &'context Text,
     homedir: Option<PathBuf>,
+    #[new(default)]
+    interner: Option<&'context MemberInterner>,
+    #[new(default)]
+    known_columns: Option<&'context [String]>,
+    #[new(default)]
+    reserved_members: Option<&'context [String]>,
+    #[new(default)]
+    deprecated_members: Option<&'context [MemberKind]>,
+    #[new(default)]
+    variables: Option<&'context [String]>,
+    #[new(value = "DEFAULT_IT_VARIABLE")]
+    it_variable: &'context str,
+    #[new(default)]
+    path_separator: Option<char>,
+    #[new(default)]
+    relative_column_paths: bool,
+    #[new(default)]
+    strict_records: bool,
+    #[new(default)]
+    warnings: Option<&'context RefCell<Vec<ParseWarning>>>,
+    #[new(value = "DEFAULT_MEMBER_NESTING_LIMIT")]
+    member_nesting_limit: usize,
+    #[new(default)]
+    member_nesting: Cell<usize>,
+    #[new(default)]
+    member_nesting_error: Cell<Option<Span>>,
 }
 
 impl<'context> ExpandContext<'context> {
@@ -210,8 +259,127 @@ impl<'context> ExpandContext<'context> {
         self.homedir.as_ref().map(|h| h.as_path())
     }
 
+    /// Share the storage of repeated member names through `interner`
+    pub fn with_interner(mut self, interner: &'context MemberInterner) -> ExpandContext<'context> {
+        self.interner = Some(interner);
+        self
+    }
+
+    /// Parse against a known set of columns. Members that aren't in the set produce an
+    /// unknown column warning.
+    pub fn with_known_columns(mut self, columns: &'context [String]) -> ExpandContext<'context> {
+        self.known_columns = Some(columns);
+        self
+    }
+
+    /// Treat `names` as reserved member names, which have a special meaning and might be
+    /// intercepted instead of looked up. Members with one of these names produce a
+    /// reserved member warning.
+    pub fn with_reserved_members(mut self, names: &'context [String]) -> ExpandContext<'context> {
+        self.reserved_members = Some(names);
+        self
+    }
+
+    /// Treat the member forms in `kinds` as deprecated. They still parse, but each one
+    /// produces a deprecated member warning.
+    pub fn with_deprecated_members(
+        mut self,
+        kinds: &'context [MemberKind],
+    ) -> ExpandContext<'context> {
+        self.deprecated_members = Some(kinds);
+        self
+    }
+
+    /// Use `name`, without its `$`, as the implicit variable instead of `it`. `$it` is then
+    /// an ordinary variable.
+    pub fn with_it_variable(mut self, name: &'context str) -> ExpandContext<'context> {
+        self.it_variable = name;
+        self
+    }
+
+    /// Whether the variable name at `span`, without its `$`, is the implicit variable
+    pub(crate) fn is_it_variable(&self, span: Span) -> bool {
+        span.slice(self.source) == self.it_variable
+    }
+
+    /// The names of the variables in scope, without their `$`, for completing partial
+    /// variables. Parsing doesn't depend on them.
+    pub fn with_variables(mut self, names: &'context [String]) -> ExpandContext<'context> {
+        self.variables = Some(names);
+        self
+    }
+
+    /// The variables in scope that the partial variable at `span`, like `$na`, could be
+    /// completed to, with their `$`
+    pub fn variable_completions(&self, span: Span) -> Vec<String> {
+        let partial = span.slice(self.source);
+        let partial = match partial.starts_with('$') {
+            true => &partial[1..],
+            false => partial,
+        };
+
+        self.variables
+            .unwrap_or(&[])
+            .iter()
+            .filter(|name| name.starts_with(partial))
+            .map(|name| format!("${}", name))
+            .collect()
+    }
+
+    /// Split bare members on `separator` as well as on dots, so that `$it.a/b` addresses
+    /// `b` inside `a`. A leading separator, like in `$it./a/b`, anchors the path to the
+    /// root of the value.
+    pub fn with_path_separator(mut self, separator: char) -> ExpandContext<'context> {
+        self.path_separator = Some(separator);
+        self
+    }
+
+    /// Allow column paths that start with a dot, like `.name`, which address a member of
+    /// the implicit `$it`. Without it, a leading dot is an error.
+    pub fn with_relative_column_paths(mut self) -> ExpandContext<'context> {
+        self.relative_column_paths = true;
+        self
+    }
+
+    /// Treat integer members as column names, like the `0` in `$record.0`, for input
+    /// that's known to be a record rather than a list
+    pub fn with_strict_records(mut self) -> ExpandContext<'context> {
+        self.strict_records = true;
+        self
+    }
+
+    /// Collect recoverable warnings into `warnings`. Without it, warnings are dropped.
+    pub fn with_warnings(
+        mut self,
+        warnings: &'context RefCell<Vec<ParseWarning>>,
+    ) -> ExpandContext<'context> {
+        self.warnings = Some(warnings);
+        self
+    }
+
+    /// Limit how deeply computed and filter members can nest inside each other
+    pub fn with_member_nesting_limit(mut self, limit: usize) -> ExpandContext<'context> {
+        self.member_nesting_limit = limit;
+        self
+    }
+
+    pub(crate) fn warn(&self, warning: ParseWarning) {
+        if let Some(warnings) = self.warnings {
+            warnings.borrow_mut().push(warning);
+        }
+    }
+
+    /// The warnings being collected and how many there are so far, so that a checkpoint
+    /// can drop the warnings raised after it when it rolls back
+    pub(crate) fn warnings_checkpoint(
+        &self,
+    ) -> Option<(&'context RefCell<Vec<ParseWarning>>, usize)> {
+        self.warnings
+            .map(|warnings| (warnings, warnings.borrow().len()))
+    }
+
     #[cfg(test)]
-    pub fn with_empty(source: &Text, callback: impl FnOnce(ExpandContext)) {
+    pub fn with_empty<T>(source: &Text, callback: impl FnOnce(ExpandContext) -> T) -> T {
         let mut registry = CommandRegistry::new();
         registry.insert(
             "ls",
@@ -222,6 +390,19 @@ impl<'context> ExpandContext<'context> {
             registry: &registry,
             source,
             homedir: None,
+            interner: None,
+            known_columns: None,
+            reserved_members: None,
+            deprecated_members: None,
+            variables: None,
+            it_variable: DEFAULT_IT_VARIABLE,
+            path_separator: None,
+            relative_column_paths: false,
+            strict_records: false,
+            warnings: None,
+            member_nesting_limit: DEFAULT_MEMBER_NESTING_LIMIT,
+            member_nesting: Cell::new(0),
+            member_nesting_error: Cell::new(None),
         })
     }
 }
@@ -334,6 +515,23 @@ pub(crate) fn expand_expr<'a, 'b, T: ExpandExpression>(
     })
 }
 
+/// Expand `shape`, or expand `fallback` instead if `shape` doesn't match. A shape that
+/// fails partway through doesn't leave anything consumed, so `fallback` starts where
+/// `shape` did.
+pub(crate) fn expand_expr_or<'a, 'b, T: ExpandExpression>(
+    shape: &T,
+    token_nodes: &'b mut TokensIterator<'a>,
+    context: &ExpandContext,
+    fallback: impl FnOnce(&mut TokensIterator<'a>) -> Result<hir::Expression, ParseError>,
+) -> Result<hir::Expression, ParseError> {
+    match token_nodes.atomic_parse(context, |token_nodes| {
+        expand_expr(shape, token_nodes, context)
+    }) {
+        Ok(expr) => Ok(expr),
+        Err(_) => fallback(token_nodes),
+    }
+}
+
 #[cfg(coloring_in_tokens)]
 pub fn color_syntax<'a, 'b, T: ColorSyntax<Info = U, Input = ()>, U>(
     shape: &T,
@@ -492,6 +690,112 @@ pub fn color_fallible_syntax_with<'a, 'b, T: FallibleColorSyntax<Info = U, Input
     })
 }
 
+/// Where the shapes produced while coloring end up. This lets a shape write a single
+/// coloring body that works whether shapes are collected into a `Vec` or recorded
+/// directly in the `TokensIterator`.
+pub(crate) trait ColorCollector: Sized {
+    fn push(&mut self, token_nodes: &mut TokensIterator<'_>, shape: Spanned<FlatShape>);
+
+    fn color<T: FallibleColorSyntax<Info = U, Input = ()>, U>(
+        &mut self,
+        shape: &T,
+        token_nodes: &mut TokensIterator<'_>,
+        context: &ExpandContext,
+    ) -> Result<U, ShellError>;
+
+    fn color_with<T: FallibleColorSyntax<Info = U, Input = I>, U, I>(
+        &mut self,
+        shape: &T,
+        input: &I,
+        token_nodes: &mut TokensIterator<'_>,
+        context: &ExpandContext,
+    ) -> Result<U, ShellError>;
+
+    /// Run `block` atomically, keeping the shapes it produced only if it succeeds
+    fn atomic<T>(
+        &mut self,
+        token_nodes: &mut TokensIterator<'_>,
+        block: impl FnOnce(&mut TokensIterator<'_>, &mut Self) -> Result<T, ShellError>,
+    ) -> Result<T, ShellError>;
+}
+
+#[cfg(not(coloring_in_tokens))]
+impl ColorCollector for Vec<Spanned<FlatShape>> {
+    fn push(&mut self, _token_nodes: &mut TokensIterator<'_>, shape: Spanned<FlatShape>) {
+        Vec::push(self, shape)
+    }
+
+    fn color<T: FallibleColorSyntax<Info = U, Input = ()>, U>(
+        &mut self,
+        shape: &T,
+        token_nodes: &mut TokensIterator<'_>,
+        context: &ExpandContext,
+    ) -> Result<U, ShellError> {
+        color_fallible_syntax(shape, token_nodes, context, self)
+    }
+
+    fn color_with<T: FallibleColorSyntax<Info = U, Input = I>, U, I>(
+        &mut self,
+        shape: &T,
+        input: &I,
+        token_nodes: &mut TokensIterator<'_>,
+        context: &ExpandContext,
+    ) -> Result<U, ShellError> {
+        color_fallible_syntax_with(shape, input, token_nodes, context, self)
+    }
+
+    fn atomic<T>(
+        &mut self,
+        token_nodes: &mut TokensIterator<'_>,
+        block: impl FnOnce(&mut TokensIterator<'_>, &mut Self) -> Result<T, ShellError>,
+    ) -> Result<T, ShellError> {
+        let mut new_shapes = vec![];
+        let result = token_nodes.atomic(|token_nodes| block(token_nodes, &mut new_shapes))?;
+        self.extend(new_shapes);
+        Ok(result)
+    }
+}
+
+/// Records shapes directly in the `TokensIterator`, which rolls them back along with
+/// any uncommitted checkpoint
+#[cfg(coloring_in_tokens)]
+#[derive(Debug)]
+pub(crate) struct TokenShapes;
+
+#[cfg(coloring_in_tokens)]
+impl ColorCollector for TokenShapes {
+    fn push(&mut self, token_nodes: &mut TokensIterator<'_>, shape: Spanned<FlatShape>) {
+        token_nodes.color_shape(shape)
+    }
+
+    fn color<T: FallibleColorSyntax<Info = U, Input = ()>, U>(
+        &mut self,
+        shape: &T,
+        token_nodes: &mut TokensIterator<'_>,
+        context: &ExpandContext,
+    ) -> Result<U, ShellError> {
+        color_fallible_syntax(shape, token_nodes, context)
+    }
+
+    fn color_with<T: FallibleColorSyntax<Info = U, Input = I>, U, I>(
+        &mut self,
+        shape: &T,
+        input: &I,
+        token_nodes: &mut TokensIterator<'_>,
+        context: &ExpandContext,
+    ) -> Result<U, ShellError> {
+        color_fallible_syntax_with(shape, input, token_nodes, context)
+    }
+
+    fn atomic<T>(
+        &mut self,
+        token_nodes: &mut TokensIterator<'_>,
+        block: impl FnOnce(&mut TokensIterator<'_>, &mut Self) -> Result<T, ShellError>,
+    ) -> Result<T, ShellError> {
+        token_nodes.atomic(|token_nodes| block(token_nodes, self))
+    }
+}
+
 impl<T: ExpandExpression> ExpandSyntax for T {
     type Output = hir::Expression;
 
@@ -624,21 +928,21 @@ impl FallibleColorSyntax for BareShape {
         _context: &ExpandContext,
         shapes: &mut Vec<Spanned<FlatShape>>,
     ) -> Result<(), ShellError> {
-        token_nodes
-            .peek_any_token("word", |token| match token {
-                // If it's a bare token, color it
-                TokenNode::Token(Spanned {
-                    item: RawToken::Bare,
-                    span,
-                }) => {
-                    shapes.push((*input).spanned(*span));
-                    Ok(())
-                }
+        token_nodes.peek_any_token("word", |token| match token {
+            // If it's a bare token, color it
+            TokenNode::Token(Spanned {
+                item: RawToken::Bare,
+                span,
+            }) => {
+                shapes.push((*input).spanned(*span));
+                Ok(())
+            }
 
-                // otherwise, fail
-                other => Err(ParseError::mismatch("word", other.spanned_type_name())),
-            })
-            .map_err(|err| err.into())
+            // otherwise, fail
+            other => Err(ParseError::mismatch("word", other.spanned_type_name())),
+        })?;
+
+        Ok(())
     }
 }
 
@@ -1681,11 +1985,16 @@ pub fn spaced<T: ExpandExpression>(inner: T) -> SpacedExpression<T> {
     SpacedExpression { inner }
 }
 
-fn expand_variable(span: Span, token_span: Span, source: &Text) -> hir::Expression {
-    if span.slice(source) == "it" {
-        hir::Expression::it_variable(span, token_span)
-    } else {
-        hir::Expression::variable(span, token_span)
+fn expand_variable(span: Span, token_span: Span, context: &ExpandContext) -> hir::Expression {
+    if context.is_it_variable(span) {
+        return hir::Expression::it_variable(span, token_span);
+    }
+
+    match span.slice(context.source) {
+        "nu" => hir::Expression::nu_variable(span, token_span),
+        "env" => hir::Expression::env_variable(span, token_span),
+        "nothing" => hir::Expression::nothing(token_span),
+        _ => hir::Expression::variable(span, token_span),
     }
 }
 
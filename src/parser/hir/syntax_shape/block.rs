@@ -4,10 +4,10 @@ use crate::parser::hir::syntax_shape::FlatShape;
 use crate::parser::{
     hir,
     hir::syntax_shape::{
-        color_fallible_syntax, color_syntax_with, continue_expression, expand_expr, expand_syntax,
-        DelimitedShape, ExpandContext, ExpandExpression, ExpressionContinuationShape,
-        ExpressionListShape, FallibleColorSyntax, MemberShape, ParseError, PathTailShape,
-        VariablePathShape,
+        color_fallible_syntax, color_syntax_with, continue_expression, expand_expr, expand_expr_or,
+        expand_syntax, AnyExpressionStartShape, DelimitedShape, ExpandContext, ExpandExpression,
+        ExpressionContinuationShape, ExpressionListShape, FallibleColorSyntax, InfixShape,
+        MemberShape, ParseError, PathTailShape, VariablePathShape,
     },
     hir::tokens_iterator::TokensIterator,
     parse::token_tree::Delimiter,
@@ -227,6 +227,37 @@ impl ExpandExpression for ShorthandBlock {
     }
 }
 
+/// A condition like `size > 10`, as taken by `where`. The left side is a shorthand path,
+/// so a bare `size` means `$it.size`.
+#[derive(Debug, Copy, Clone)]
+pub struct ConditionShape;
+
+impl ExpandExpression for ConditionShape {
+    fn name(&self) -> &'static str {
+        "condition"
+    }
+
+    fn expand_expr<'a, 'b>(
+        &self,
+        token_nodes: &'b mut TokensIterator<'a>,
+        context: &ExpandContext,
+    ) -> Result<hir::Expression, ParseError> {
+        let path = expand_expr(&ShorthandPath, token_nodes, context)?;
+
+        let Spanned {
+            item: (_, operator, _),
+            ..
+        } = expand_syntax(&InfixShape, token_nodes, context)?;
+
+        let value = expand_expr(&AnyExpressionStartShape, token_nodes, context)?;
+
+        // Any further infix operators are folded in by precedence
+        let condition = hir::Expression::infix(path, operator, value);
+
+        Ok(continue_expression(condition, token_nodes, context))
+    }
+}
+
 /// A shorthand for `$it.foo."bar"`, used inside of a shorthand block
 #[derive(Debug, Copy, Clone)]
 pub struct ShorthandPath;
@@ -335,32 +366,27 @@ impl ExpandExpression for ShorthandPath {
         context: &ExpandContext,
     ) -> Result<hir::Expression, ParseError> {
         // if it's a variable path, that's the head part
-        let path = expand_expr(&VariablePathShape, token_nodes, context);
-
-        match path {
-            Ok(path) => return Ok(path),
-            Err(_) => {}
-        }
-
-        // Synthesize the head of the shorthand path (`<member>` -> `$it.<member>`)
-        let mut head = expand_expr(&ShorthandHeadShape, token_nodes, context)?;
+        expand_expr_or(&VariablePathShape, token_nodes, context, |token_nodes| {
+            // Synthesize the head of the shorthand path (`<member>` -> `$it.<member>`)
+            let mut head = expand_expr(&ShorthandHeadShape, token_nodes, context)?;
 
-        // Now that we've synthesized the head, of the path, proceed to expand the tail of the path
-        // like any other path.
-        let tail = expand_syntax(&PathTailShape, token_nodes, context);
+            // Now that we've synthesized the head, of the path, proceed to expand the tail of the path
+            // like any other path.
+            let tail = expand_syntax(&PathTailShape, token_nodes, context);
 
-        match tail {
-            Err(_) => return Ok(head),
-            Ok(Spanned { item: tail, .. }) => {
-                // For each member that `PathTailShape` expanded, join it onto the existing expression
-                // to form a new path
-                for member in tail {
-                    head = hir::Expression::dot_member(head, member);
+            match tail {
+                Err(_) => return Ok(head),
+                Ok(Spanned { item: tail, .. }) => {
+                    // For each member that `PathTailShape` expanded, join it onto the existing expression
+                    // to form a new path
+                    for member in tail {
+                        head = hir::Expression::dot_member(head, member);
+                    }
+
+                    Ok(head)
                 }
-
-                Ok(head)
             }
-        }
+        })
     }
 }
 
@@ -471,7 +497,7 @@ impl ExpandExpression for ShorthandHeadShape {
         context: &ExpandContext,
     ) -> Result<hir::Expression, ParseError> {
         let head = expand_syntax(&MemberShape, token_nodes, context)?;
-        let head = head.to_path_member(context.source);
+        let head = head.to_interned_path_member(context);
 
         // Synthesize an `$it` expression
         let it = synthetic_it();
@@ -4,7 +4,10 @@ pub(crate) mod file_path;
 pub(crate) mod list;
 pub(crate) mod number;
 pub(crate) mod pattern;
+pub(crate) mod range;
 pub(crate) mod string;
+pub(crate) mod type_name;
+pub(crate) mod unary;
 pub(crate) mod unit;
 pub(crate) mod variable_path;
 
@@ -12,7 +15,8 @@ use crate::parser::hir::syntax_shape::{
     color_delimited_square, color_fallible_syntax, color_fallible_syntax_with, expand_atom,
     expand_delimited_square, expand_expr, expand_syntax, AtomicToken, BareShape, ColorableDotShape,
     DotShape, ExpandContext, ExpandExpression, ExpandSyntax, ExpansionRule, ExpressionContinuation,
-    ExpressionContinuationShape, FallibleColorSyntax, FlatShape, ParseError,
+    ExpressionContinuationShape, FallibleColorSyntax, FlatShape, ParseError, RangeShape,
+    StringInterpolationShape, TestSyntax, UnaryNegationShape,
 };
 use crate::parser::{
     hir,
@@ -111,17 +115,37 @@ pub(crate) fn continue_expression(
             // If there's no continuation, return the head
             Err(_) => return head,
             // Otherwise, form a new expression by combining the head with the continuation
-            Ok(continuation) => match continuation {
-                // If the continuation is a `.member`, form a path with the new member
-                ExpressionContinuation::DotSuffix(_dot, member) => {
-                    head = Expression::dot_member(head, member);
-                }
+            Ok(continuation) => head = continue_with(head, continuation),
+        }
+    }
+}
 
-                // Otherwise, if the continuation is an infix suffix, form an infix expression
-                ExpressionContinuation::InfixSuffix(op, expr) => {
-                    head = Expression::infix(head, op, expr);
-                }
-            },
+/// Combine `head` with the continuation that follows it
+pub(crate) fn continue_with(
+    head: hir::Expression,
+    continuation: ExpressionContinuation,
+) -> hir::Expression {
+    match continuation {
+        // If the continuation is a `.member`, form a path with the new member
+        ExpressionContinuation::DotSuffix(_dot, member, marker) => {
+            Expression::marked_dot_member(head, member, marker)
+        }
+
+        // If the continuation is a `[index]`, form a path with the index as a member
+        ExpressionContinuation::IndexSuffix(index) => Expression::dot_member(head, index),
+
+        // Otherwise, if the continuation is an infix suffix, form an infix expression,
+        // respecting the precedence of any infix operators already in the head
+        ExpressionContinuation::InfixSuffix(op, expr) => {
+            Expression::infix_by_precedence(head, op, expr)
+        }
+
+        // If the continuation is an `is` suffix, test the head's type
+        ExpressionContinuation::IsSuffix(_is, type_name) => Expression::type_test(head, type_name),
+
+        // If the continuation is a `|` suffix, pipe the head into the pipeline
+        ExpressionContinuation::PipeSuffix(pipe, pipeline) => {
+            Expression::pipe(head, pipe, pipeline)
         }
     }
 }
@@ -187,6 +211,21 @@ impl ExpandExpression for AnyExpressionStartShape {
         token_nodes: &mut TokensIterator<'_>,
         context: &ExpandContext,
     ) -> Result<hir::Expression, ParseError> {
+        if StringInterpolationShape
+            .test(token_nodes, context)
+            .is_some()
+        {
+            return expand_expr(&StringInterpolationShape, token_nodes, context);
+        }
+
+        if UnaryNegationShape::peek(token_nodes, context) {
+            return expand_expr(&UnaryNegationShape, token_nodes, context);
+        }
+
+        if RangeShape::peek(token_nodes, context) {
+            return expand_expr(&RangeShape, token_nodes, context);
+        }
+
         let atom = expand_atom(token_nodes, "expression", context, ExpansionRule::new())?;
 
         match atom.item {
@@ -227,6 +266,22 @@ impl FallibleColorSyntax for AnyExpressionStartShape {
         context: &ExpandContext,
         shapes: &mut Vec<Spanned<FlatShape>>,
     ) -> Result<(), ShellError> {
+        if let Some(mut peeked) = StringInterpolationShape.test(token_nodes, context) {
+            if let Some(node) = peeked.commit() {
+                shapes.push(FlatShape::String.spanned(node.span()));
+            }
+
+            return Ok(());
+        }
+
+        if UnaryNegationShape::peek(token_nodes, context) {
+            return color_fallible_syntax(&UnaryNegationShape, token_nodes, context, shapes);
+        }
+
+        if RangeShape::peek(token_nodes, context) {
+            return color_fallible_syntax(&RangeShape, token_nodes, context, shapes);
+        }
+
         let atom = token_nodes.spanned(|token_nodes| {
             expand_atom(
                 token_nodes,
@@ -289,6 +344,23 @@ impl FallibleColorSyntax for AnyExpressionStartShape {
         token_nodes: &'b mut TokensIterator<'a>,
         context: &ExpandContext,
     ) -> Result<(), ShellError> {
+        if let Some(mut peeked) = StringInterpolationShape.test(token_nodes, context) {
+            if let Some(node) = peeked.commit() {
+                let span = node.span();
+                token_nodes.color_shape(FlatShape::String.spanned(span));
+            }
+
+            return Ok(());
+        }
+
+        if UnaryNegationShape::peek(token_nodes, context) {
+            return color_fallible_syntax(&UnaryNegationShape, token_nodes, context);
+        }
+
+        if RangeShape::peek(token_nodes, context) {
+            return color_fallible_syntax(&RangeShape, token_nodes, context);
+        }
+
         let atom = token_nodes.spanned(|token_nodes| {
             expand_atom(
                 token_nodes,
@@ -1,6 +1,7 @@
 use crate::parser::hir::syntax_shape::{
-    expand_syntax, expression::expand_file_path, parse_single_node, BarePathShape,
-    BarePatternShape, ExpandContext, UnitShape,
+    expand_range_literal, expand_syntax, expand_variable, expression::expand_file_path,
+    parse_single_node, BarePathShape, BarePatternShape, DurationShape, ExpandContext,
+    FilesizeShape, UnitShape,
 };
 use crate::parser::{
     hir,
@@ -168,7 +169,7 @@ impl<'tokens> SpannedAtomicToken<'tokens> {
             }
             AtomicToken::String { body } => Expression::string(*body, self.span),
             AtomicToken::ItVariable { name } => Expression::it_variable(*name, self.span),
-            AtomicToken::Variable { name } => Expression::variable(*name, self.span),
+            AtomicToken::Variable { name } => expand_variable(*name, self.span, context),
             AtomicToken::ExternalCommand { command } => {
                 Expression::external_command(*command, self.span)
             }
@@ -179,7 +180,9 @@ impl<'tokens> SpannedAtomicToken<'tokens> {
             ),
             AtomicToken::Word { text } => Expression::string(*text, *text),
             AtomicToken::SquareDelimited { .. } => unimplemented!("into_hir"),
-            AtomicToken::ParenDelimited { .. } => unimplemented!("into_hir"),
+            AtomicToken::ParenDelimited { nodes, .. } => {
+                return expand_range_literal(nodes, self.span, context)
+            }
             AtomicToken::BraceDelimited { .. } => unimplemented!("into_hir"),
             AtomicToken::Pipeline { .. } => unimplemented!("into_hir"),
         })
@@ -510,17 +513,37 @@ fn expand_atom_inner<'me, 'content>(
     // as a size.
     match rule.treat_size_as_word {
         true => {}
-        false => match expand_syntax(&UnitShape, token_nodes, context) {
-            // If the head of the stream isn't a valid unit, we'll try to parse
-            // it again next as a word
-            Err(_) => {}
+        false => {
+            // Durations with spelled-out units, like `5min`
+            if let Ok(Spanned {
+                item: (number, unit),
+                span,
+            }) = expand_syntax(&DurationShape, token_nodes, context)
+            {
+                return Ok(AtomicToken::Size { number, unit }.spanned(span));
+            }
 
-            // But if it was a valid unit, we're done here
-            Ok(Spanned {
+            // File sizes, including binary units like `1gib`
+            if let Ok(Spanned {
                 item: (number, unit),
                 span,
-            }) => return Ok(AtomicToken::Size { number, unit }.spanned(span)),
-        },
+            }) = expand_syntax(&FilesizeShape, token_nodes, context)
+            {
+                return Ok(AtomicToken::Size { number, unit }.spanned(span));
+            }
+
+            match expand_syntax(&UnitShape, token_nodes, context) {
+                // If the head of the stream isn't a valid unit, we'll try to parse
+                // it again next as a word
+                Err(_) => {}
+
+                // But if it was a valid unit, we're done here
+                Ok(Spanned {
+                    item: (number, unit),
+                    span,
+                }) => return Ok(AtomicToken::Size { number, unit }.spanned(span)),
+            }
+        }
     }
 
     match rule.separate_members {
@@ -613,6 +636,25 @@ fn expand_atom_inner<'me, 'content>(
             .spanned(span));
         }
 
+        // ( ... )
+        TokenNode::Delimited(Spanned {
+            item:
+                DelimitedNode {
+                    delimiter: Delimiter::Paren,
+                    spans,
+                    children,
+                },
+            span,
+        }) => {
+            peeked.commit();
+            let span = *span;
+            return Ok(AtomicToken::ParenDelimited {
+                nodes: children,
+                span: *spans,
+            }
+            .spanned(span));
+        }
+
         TokenNode::Flag(Spanned {
             item:
                 Flag {
@@ -689,7 +731,7 @@ fn expand_atom_inner<'me, 'content>(
             RawToken::Number(number) => AtomicToken::Number { number }.spanned(token_span),
             RawToken::Operator(_) => AtomicToken::Operator { text: token_span }.spanned(token_span),
             RawToken::String(body) => AtomicToken::String { body }.spanned(token_span),
-            RawToken::Variable(name) if name.slice(context.source) == "it" => {
+            RawToken::Variable(name) if context.is_it_variable(name) => {
                 AtomicToken::ItVariable { name }.spanned(token_span)
             }
             RawToken::Variable(name) => AtomicToken::Variable { name }.spanned(token_span),
@@ -5,8 +5,8 @@ use crate::parser::{
     hir,
     hir::syntax_shape::{
         color_fallible_syntax, color_syntax, expand_atom, expand_expr, maybe_spaced, spaced,
-        AnyExpressionShape, ColorSyntax, ExpandContext, ExpandSyntax, ExpansionRule,
-        MaybeSpaceShape, SpaceShape,
+        ColorSyntax, ExpandContext, ExpandSyntax, ExpansionRule, MaybeSpaceShape,
+        NotExpressionShape, SpaceShape,
     },
     hir::TokensIterator,
 };
@@ -42,7 +42,7 @@ impl ExpandSyntax for ExpressionListShape {
             return Ok(exprs.spanned(start));
         }
 
-        let expr = expand_expr(&maybe_spaced(AnyExpressionShape), token_nodes, context)?;
+        let expr = expand_expr(&maybe_spaced(NotExpressionShape), token_nodes, context)?;
 
         exprs.push(expr);
 
@@ -52,7 +52,7 @@ impl ExpandSyntax for ExpressionListShape {
                 return Ok(exprs.spanned(start.until(end)));
             }
 
-            let expr = expand_expr(&spaced(AnyExpressionShape), token_nodes, context)?;
+            let expr = expand_expr(&spaced(NotExpressionShape), token_nodes, context)?;
 
             exprs.push(expr);
         }
@@ -103,7 +103,7 @@ impl ColorSyntax for ExpressionListShape {
                 }
             } else {
                 // Try to color the head of the stream as an expression
-                match color_fallible_syntax(&AnyExpressionShape, token_nodes, context, shapes) {
+                match color_fallible_syntax(&NotExpressionShape, token_nodes, context, shapes) {
                     // If no expression was found, switch to backoff coloring mode
                     Err(_) => {
                         backoff = true;
@@ -176,7 +176,7 @@ impl ColorSyntax for ExpressionListShape {
                 }
             } else {
                 // Try to color the head of the stream as an expression
-                match color_fallible_syntax(&AnyExpressionShape, token_nodes, context) {
+                match color_fallible_syntax(&NotExpressionShape, token_nodes, context) {
                     // If no expression was found, switch to backoff coloring mode
                     Err(_) => {
                         backoff = true;
@@ -26,7 +26,7 @@ impl ExpandExpression for NumberShape {
         parse_single_node(token_nodes, "Number", |token, token_span, err| {
             Ok(match token {
                 RawToken::GlobPattern | RawToken::Operator(..) => return Err(err.error()),
-                RawToken::Variable(tag) if tag.slice(context.source) == "it" => {
+                RawToken::Variable(tag) if context.is_it_variable(tag) => {
                     hir::Expression::it_variable(tag, token_span)
                 }
                 RawToken::ExternalCommand(tag) => {
@@ -130,7 +130,7 @@ impl ExpandExpression for IntShape {
                 RawToken::GlobPattern | RawToken::Operator(..) | RawToken::ExternalWord => {
                     return Err(err.error())
                 }
-                RawToken::Variable(span) if span.slice(context.source) == "it" => {
+                RawToken::Variable(span) if context.is_it_variable(span) => {
                     hir::Expression::it_variable(span, token_span)
                 }
                 RawToken::ExternalCommand(span) => {
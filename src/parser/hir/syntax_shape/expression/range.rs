@@ -0,0 +1,345 @@
+use crate::parser::hir::syntax_shape::expression::variable_path::expand_range_equals;
+use crate::parser::hir::syntax_shape::{
+    continue_expression, expand_atom, expand_expr, expand_syntax, AtomicToken, DotShape,
+    ExpandContext, ExpandExpression, ExpansionRule, FallibleColorSyntax, FlatShape, IntMemberShape,
+    ParseError, VariableShape,
+};
+use crate::parser::{hir, hir::TokensIterator, TokenNode};
+use crate::prelude::*;
+
+/// An expression that can head a path. For now, this is a parenthesized range literal
+/// like `(1..5)`, followed by any continuations, so `(1..5).0` is a path whose head is
+/// the range.
+#[derive(Debug, Copy, Clone)]
+pub struct PrimaryExpressionShape;
+
+impl ExpandExpression for PrimaryExpressionShape {
+    fn name(&self) -> &'static str {
+        "primary expression"
+    }
+
+    fn expand_expr<'a, 'b>(
+        &self,
+        token_nodes: &mut TokensIterator<'_>,
+        context: &ExpandContext,
+    ) -> Result<hir::Expression, ParseError> {
+        let head = expand_expr(&RangeLiteralShape, token_nodes, context)?;
+
+        Ok(continue_expression(head, token_nodes, context))
+    }
+}
+
+/// A parenthesized range literal with integer or variable bounds, like `(1..5)`,
+/// `($start..=$end)` or `(..5)`
+#[derive(Debug, Copy, Clone)]
+pub struct RangeLiteralShape;
+
+impl ExpandExpression for RangeLiteralShape {
+    fn name(&self) -> &'static str {
+        "range literal"
+    }
+
+    fn expand_expr<'a, 'b>(
+        &self,
+        token_nodes: &mut TokensIterator<'_>,
+        context: &ExpandContext,
+    ) -> Result<hir::Expression, ParseError> {
+        token_nodes.atomic_parse(context, |token_nodes| {
+            let atom = expand_atom(token_nodes, "range literal", context, ExpansionRule::new())?;
+
+            match atom.item {
+                AtomicToken::ParenDelimited { nodes, .. } => {
+                    expand_range_literal(nodes, atom.span, context)
+                }
+                other => Err(ParseError::mismatch(
+                    "range literal",
+                    other.type_name().spanned(atom.span),
+                )),
+            }
+        })
+    }
+}
+
+/// Expand the children of a parenthesized range literal. The resulting expression
+/// spans the parentheses. The range is evaluated into a table, so it needs an upper
+/// bound.
+pub fn expand_range_literal(
+    children: &Vec<TokenNode>,
+    span: Span,
+    context: &ExpandContext,
+) -> Result<hir::Expression, ParseError> {
+    let range = expand_delimited_range(children, span, context)?.bounded()?;
+
+    Ok(range.into_expr(span))
+}
+
+/// Expand the children of a delimited range, like the `1..5` in `(1..5)` or `[1..5]`.
+/// The range must fill the delimiters.
+pub(crate) fn expand_delimited_range(
+    children: &Vec<TokenNode>,
+    span: Span,
+    context: &ExpandContext,
+) -> Result<RangeExpression, ParseError> {
+    let mut tokens = TokensIterator::new(&children, span, false);
+
+    let range = match expand_range(&mut tokens, context)? {
+        Some(range) => range,
+        None => return Err(tokens.peek_non_ws().type_error("range")),
+    };
+
+    if !tokens.at_end_possible_ws() {
+        return Err(tokens.peek_non_ws().type_error("end of range"));
+    }
+
+    Ok(range)
+}
+
+/// A range in expression position, like `1..10`, `1..=10`, `$start..$end` or `..5`. The
+/// range is evaluated into a table, so it needs an upper bound, and `5..` is an error.
+/// The range must end at whitespace or the end of the expression, so `5..name` is still
+/// a word.
+#[derive(Debug, Copy, Clone)]
+pub struct RangeShape;
+
+impl RangeShape {
+    /// Whether the next tokens are a range. Nothing is consumed.
+    pub fn peek(token_nodes: &mut TokensIterator<'_>, context: &ExpandContext) -> bool {
+        let checkpoint = token_nodes.checkpoint(context);
+
+        expand_range_expression(checkpoint.iterator, context).is_ok()
+    }
+}
+
+impl ExpandExpression for RangeShape {
+    fn name(&self) -> &'static str {
+        "range"
+    }
+
+    fn expand_expr<'a, 'b>(
+        &self,
+        token_nodes: &mut TokensIterator<'_>,
+        context: &ExpandContext,
+    ) -> Result<hir::Expression, ParseError> {
+        token_nodes.atomic_parse(context, |token_nodes| {
+            let range = expand_range_expression(token_nodes, context)?.bounded()?;
+            let span = range.span();
+
+            Ok(range.into_expr(span))
+        })
+    }
+}
+
+#[cfg(not(coloring_in_tokens))]
+impl FallibleColorSyntax for RangeShape {
+    type Info = ();
+    type Input = ();
+
+    fn color_syntax<'a, 'b>(
+        &self,
+        _input: &(),
+        token_nodes: &'b mut TokensIterator<'a>,
+        context: &ExpandContext,
+        shapes: &mut Vec<Spanned<FlatShape>>,
+    ) -> Result<(), ShellError> {
+        let range = expand_range_expression(token_nodes, context)?;
+        shapes.extend(range.shapes());
+
+        Ok(())
+    }
+}
+
+#[cfg(coloring_in_tokens)]
+impl FallibleColorSyntax for RangeShape {
+    type Info = ();
+    type Input = ();
+
+    fn name(&self) -> &'static str {
+        "RangeShape"
+    }
+
+    fn color_syntax<'a, 'b>(
+        &self,
+        _input: &(),
+        token_nodes: &'b mut TokensIterator<'a>,
+        context: &ExpandContext,
+    ) -> Result<(), ShellError> {
+        let range = expand_range_expression(token_nodes, context)?;
+
+        for shape in range.shapes() {
+            token_nodes.color_shape(shape);
+        }
+
+        Ok(())
+    }
+}
+
+/// The parts of a range, like `1..10`, `$start..=$end`, `..5` or `5..`. Ranges in
+/// expression position, in parentheses, in brackets and after a dot are all expanded
+/// into this.
+pub(crate) struct RangeExpression {
+    from: Option<hir::Expression>,
+    dotdot: Span,
+    /// The `=` of an inclusive range
+    equals: Option<Span>,
+    to: Option<hir::Expression>,
+}
+
+impl RangeExpression {
+    pub(crate) fn span(&self) -> Span {
+        let start = match &self.from {
+            Some(from) => from.span,
+            None => self.dotdot,
+        };
+
+        let end = match &self.to {
+            Some(to) => to.span,
+            None => self.dotdot,
+        };
+
+        start.until(end)
+    }
+
+    /// Whether the range has a lower bound, like the `2` in `2..5`
+    pub(crate) fn has_lower_bound(&self) -> bool {
+        self.from.is_some()
+    }
+
+    /// The range, or an error if it has no upper bound, like `5..`
+    pub(crate) fn bounded(self) -> Result<RangeExpression, ParseError> {
+        match self.to {
+            Some(_) => Ok(self),
+            None => Err(ParseError::mismatch(
+                "range with an upper bound",
+                "open range".spanned(self.span()),
+            )),
+        }
+    }
+
+    pub(crate) fn into_expr(self, span: Span) -> hir::Expression {
+        hir::Expression::range_with_bounds(
+            self.from,
+            self.dotdot,
+            self.to,
+            self.equals.is_some(),
+            span,
+        )
+    }
+
+    pub(crate) fn shapes(&self) -> Vec<Spanned<FlatShape>> {
+        let mut shapes = vec![];
+
+        if let Some(from) = &self.from {
+            shapes.push(bound_shape(from).spanned(from.span));
+        }
+
+        shapes.push(FlatShape::Dot.spanned(self.dotdot));
+
+        if let Some(equals) = self.equals {
+            shapes.push(FlatShape::Operator.spanned(equals));
+        }
+
+        if let Some(to) = &self.to {
+            shapes.push(bound_shape(to).spanned(to.span));
+        }
+
+        shapes
+    }
+}
+
+fn bound_shape(bound: &hir::Expression) -> FlatShape {
+    match &bound.item {
+        hir::RawExpression::Variable(hir::Variable::It(_)) => FlatShape::ItVariable,
+        hir::RawExpression::Variable(_) => FlatShape::Variable,
+        _ => FlatShape::Int,
+    }
+}
+
+/// Expand a range like `1..10`, `$start..=$end`, `..5` or `5..`. If the next tokens
+/// aren't a range, nothing is consumed and this returns `Ok(None)`. A `..` without any
+/// bounds isn't a range. Once the dots are seen, an inclusive range without an upper
+/// bound is an error. Whatever follows the range is left alone.
+pub(crate) fn expand_range(
+    token_nodes: &mut TokensIterator<'_>,
+    context: &ExpandContext,
+) -> Result<Option<RangeExpression>, ParseError> {
+    let checkpoint = token_nodes.checkpoint(context);
+
+    let from = expand_range_bound(checkpoint.iterator, context);
+
+    let first_dot = match expand_syntax(&DotShape, checkpoint.iterator, context) {
+        Ok(dot) => dot,
+        Err(_) => return Ok(None),
+    };
+
+    let second_dot = match expand_syntax(&DotShape, checkpoint.iterator, context) {
+        Ok(dot) => dot,
+        Err(_) => return Ok(None),
+    };
+
+    let equals = expand_range_equals(checkpoint.iterator, context);
+    let to = expand_range_bound(checkpoint.iterator, context);
+
+    match (&from, equals, &to) {
+        (None, _, None) => return Ok(None),
+        (_, Some(equals), None) => {
+            return Err(ParseError::mismatch(
+                "end of inclusive range",
+                "nothing".spanned(equals),
+            ));
+        }
+        _ => {}
+    }
+
+    checkpoint.commit();
+
+    Ok(Some(RangeExpression {
+        from,
+        dotdot: first_dot.until(second_dot),
+        equals,
+        to,
+    }))
+}
+
+/// Expand a range in expression position, which must end at whitespace or the end of
+/// the expression
+fn expand_range_expression(
+    token_nodes: &mut TokensIterator<'_>,
+    context: &ExpandContext,
+) -> Result<RangeExpression, ParseError> {
+    let range = match expand_range(token_nodes, context)? {
+        Some(range) => range,
+        None => return Err(token_nodes.peek_non_ws().type_error("range")),
+    };
+
+    let peeked = token_nodes.peek_any();
+
+    match peeked.node {
+        None | Some(TokenNode::Whitespace(_)) => {}
+        Some(_) => return Err(peeked.type_error("end of range")),
+    }
+
+    Ok(range)
+}
+
+/// One end of a range: an integer, or a variable like the `$start` in `$start..10`,
+/// which is evaluated along with the range. If the next token is neither, nothing is
+/// consumed.
+fn expand_range_bound(
+    tokens: &mut TokensIterator<'_>,
+    context: &ExpandContext,
+) -> Option<hir::Expression> {
+    {
+        let checkpoint = tokens.checkpoint(context);
+
+        if let Ok(int) = expand_syntax(&IntMemberShape, checkpoint.iterator, context) {
+            checkpoint.commit();
+            return Some(int.to_expr());
+        }
+    }
+
+    let checkpoint = tokens.checkpoint(context);
+    let variable = expand_expr(&VariableShape, checkpoint.iterator, context).ok()?;
+    checkpoint.commit();
+
+    Some(variable)
+}
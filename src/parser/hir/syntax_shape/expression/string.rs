@@ -1,9 +1,10 @@
 use crate::parser::hir::syntax_shape::{
-    expand_atom, expand_variable, parse_single_node, AtomicToken, ExpandContext, ExpandExpression,
-    ExpansionRule, FallibleColorSyntax, FlatShape, ParseError, TestSyntax,
+    expand_atom, expand_expr, expand_syntax, expand_variable, parse_single_node, AtomicToken,
+    ExpandContext, ExpandExpression, ExpansionRule, FallibleColorSyntax, FlatShape,
+    NotExpressionShape, ParseError, TestSyntax, WhitespaceShape,
 };
 use crate::parser::hir::tokens_iterator::Peeked;
-use crate::parser::{hir, hir::TokensIterator, RawToken};
+use crate::parser::{hir, hir::TokensIterator, DelimitedNode, Delimiter, RawToken, TokenNode};
 use crate::prelude::*;
 
 #[derive(Debug, Copy, Clone)]
@@ -89,7 +90,7 @@ impl ExpandExpression for StringShape {
                 RawToken::GlobPattern | RawToken::Operator(..) | RawToken::ExternalWord => {
                     return Err(err.error())
                 }
-                RawToken::Variable(span) => expand_variable(span, token_span, &context.source),
+                RawToken::Variable(span) => expand_variable(span, token_span, context),
                 RawToken::ExternalCommand(span) => {
                     hir::Expression::external_command(span, token_span)
                 }
@@ -115,3 +116,138 @@ impl TestSyntax for StringShape {
         }
     }
 }
+
+/// An interpolated string, like `$"Hello ($name)"`, which expands to the concatenation
+/// of its literal segments and the values of its parenthesized expressions
+#[derive(Debug, Copy, Clone)]
+pub struct StringInterpolationShape;
+
+impl ExpandExpression for StringInterpolationShape {
+    fn name(&self) -> &'static str {
+        "string interpolation"
+    }
+
+    fn expand_expr<'a, 'b>(
+        &self,
+        token_nodes: &mut TokensIterator<'_>,
+        context: &ExpandContext,
+    ) -> Result<hir::Expression, ParseError> {
+        let peeked = token_nodes.peek_any().not_eof("string interpolation")?;
+
+        let (children, span) = match peeked.node {
+            TokenNode::Delimited(Spanned {
+                item:
+                    DelimitedNode {
+                        delimiter: Delimiter::Interpolation,
+                        children,
+                        ..
+                    },
+                span,
+            }) => (children, *span),
+            _ => return Err(peeked.type_error("string interpolation")),
+        };
+
+        let parts = children
+            .iter()
+            .map(|child| expand_interpolation_part(child, context))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        peeked.commit();
+        Ok(hir::Expression::interpolation(parts, span))
+    }
+}
+
+impl TestSyntax for StringInterpolationShape {
+    fn test<'a, 'b>(
+        &self,
+        token_nodes: &'b mut TokensIterator<'a>,
+        _context: &ExpandContext,
+    ) -> Option<Peeked<'a, 'b>> {
+        let peeked = token_nodes.peek_any();
+
+        match peeked.node {
+            Some(TokenNode::Delimited(Spanned {
+                item:
+                    DelimitedNode {
+                        delimiter: Delimiter::Interpolation,
+                        ..
+                    },
+                ..
+            })) => Some(peeked),
+            _ => None,
+        }
+    }
+}
+
+fn expand_interpolation_part(
+    part: &TokenNode,
+    context: &ExpandContext,
+) -> Result<hir::Expression, ParseError> {
+    match part {
+        TokenNode::Token(Spanned {
+            item: RawToken::String(_),
+            span,
+        }) => {
+            // The lexer leaves a `(` without a matching `)` in a segment of its own
+            match unescape_interpolation_segment(span.slice(context.source)) {
+                Some(text) => {
+                    Ok(hir::RawExpression::Synthetic(hir::Synthetic::String(text)).spanned(*span))
+                }
+                None => Err(ParseError::mismatch(
+                    "string interpolation",
+                    "unterminated expression".spanned(*span),
+                )),
+            }
+        }
+
+        TokenNode::Delimited(Spanned {
+            item:
+                DelimitedNode {
+                    delimiter: Delimiter::Paren,
+                    children,
+                    ..
+                },
+            span,
+        }) => {
+            let mut tokens = TokensIterator::new(children, *span, false);
+
+            // whitespace just inside the parens is allowed
+            let _ = expand_syntax(&WhitespaceShape, &mut tokens, context);
+            let expr = expand_expr(&NotExpressionShape, &mut tokens, context)?;
+
+            if !tokens.at_end_possible_ws() {
+                return Err(tokens
+                    .peek_non_ws()
+                    .type_error("end of interpolated expression"));
+            }
+
+            Ok(expr)
+        }
+
+        other => Err(ParseError::mismatch(
+            "string interpolation",
+            other.type_name().spanned(other.span()),
+        )),
+    }
+}
+
+/// Unescapes the literal text of an interpolation segment. `\\` is a literal backslash and
+/// `\(` a literal paren; any other backslash is kept as-is. Returns `None` if the segment
+/// contains an unescaped `(`.
+fn unescape_interpolation_segment(text: &str) -> Option<String> {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.peek() {
+                Some('\\') | Some('(') => out.push(chars.next().unwrap()),
+                _ => out.push('\\'),
+            },
+            '(' => return None,
+            c => out.push(c),
+        }
+    }
+
+    Some(out)
+}
@@ -0,0 +1,104 @@
+use crate::parser::hir::syntax_shape::{ExpandContext, ExpandSyntax, ParseError};
+use crate::parser::{hir::TokensIterator, RawToken, TokenNode};
+use crate::prelude::*;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
+pub enum TypeName {
+    Int,
+    Decimal,
+    String,
+    Bool,
+    Date,
+    Path,
+    Bytes,
+}
+
+impl TypeName {
+    pub fn as_str(&self) -> &str {
+        match *self {
+            TypeName::Int => "int",
+            TypeName::Decimal => "decimal",
+            TypeName::String => "string",
+            TypeName::Bool => "bool",
+            TypeName::Date => "date",
+            TypeName::Path => "path",
+            TypeName::Bytes => "bytes",
+        }
+    }
+
+    /// Whether `value` is of this type
+    pub(crate) fn matches(&self, value: &Value) -> bool {
+        match (self, value) {
+            (TypeName::Int, Value::Primitive(Primitive::Int(_)))
+            | (TypeName::Decimal, Value::Primitive(Primitive::Decimal(_)))
+            | (TypeName::String, Value::Primitive(Primitive::String(_)))
+            | (TypeName::Bool, Value::Primitive(Primitive::Boolean(_)))
+            | (TypeName::Date, Value::Primitive(Primitive::Date(_)))
+            | (TypeName::Path, Value::Primitive(Primitive::Path(_)))
+            | (TypeName::Bytes, Value::Primitive(Primitive::Bytes(_))) => true,
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for TypeName {
+    type Err = ();
+    fn from_str(input: &str) -> Result<Self, <Self as std::str::FromStr>::Err> {
+        match input {
+            "int" => Ok(TypeName::Int),
+            "decimal" => Ok(TypeName::Decimal),
+            "string" => Ok(TypeName::String),
+            "bool" => Ok(TypeName::Bool),
+            "date" => Ok(TypeName::Date),
+            "path" => Ok(TypeName::Path),
+            "bytes" => Ok(TypeName::Bytes),
+            _ => Err(()),
+        }
+    }
+}
+
+impl FormatDebug for Spanned<TypeName> {
+    fn fmt_debug(&self, f: &mut DebugFormatter, source: &str) -> fmt::Result {
+        write!(f, "{}", self.span.slice(source))
+    }
+}
+
+/// The name of a type, like `int` or `string`, as a single bare word
+#[derive(Debug, Copy, Clone)]
+pub struct TypeNameShape;
+
+impl ExpandSyntax for TypeNameShape {
+    type Output = Spanned<TypeName>;
+
+    fn name(&self) -> &'static str {
+        "type name"
+    }
+
+    fn expand_syntax<'a, 'b>(
+        &self,
+        token_nodes: &'b mut TokensIterator<'a>,
+        context: &ExpandContext,
+    ) -> Result<Spanned<TypeName>, ParseError> {
+        let peeked = token_nodes.peek_any().not_eof("type name")?;
+
+        let span = match peeked.node {
+            TokenNode::Token(Spanned {
+                item: RawToken::Bare,
+                span,
+            }) => *span,
+            _ => return Err(peeked.type_error("type name")),
+        };
+
+        let name = span.slice(context.source);
+
+        let type_name = match TypeName::from_str(name) {
+            Err(_) => return Err(ParseError::mismatch("type name", name.spanned(span))),
+            Ok(type_name) => type_name,
+        };
+
+        peeked.commit();
+        Ok(type_name.spanned(span))
+    }
+}
@@ -0,0 +1,420 @@
+use crate::parser::hir::syntax_shape::{
+    color_fallible_syntax, continue_coloring_expression, continue_expression, continue_with,
+    expand_expr, expand_syntax, AnyExpressionShape, AnyExpressionStartShape, ExpandContext,
+    ExpandExpression, ExpressionContinuation, ExpressionContinuationShape, FallibleColorSyntax,
+    FlatShape, ParseError, WhitespaceShape,
+};
+use crate::parser::{
+    hir, hir::TokensIterator, DelimitedNode, Delimiter, Operator, RawToken, TokenNode,
+};
+use crate::prelude::*;
+
+/// A `-` directly in front of an expression, like `-$x` or `-(1 + 2)`. A `-` followed by
+/// whitespace is a subtraction instead, and a `-` in front of a digit was already lexed
+/// as the sign of a number.
+#[derive(Debug, Copy, Clone)]
+pub struct UnaryNegationShape;
+
+impl UnaryNegationShape {
+    /// Whether the next token is a `-` directly followed by an expression. Nothing is
+    /// consumed.
+    pub fn peek(token_nodes: &mut TokensIterator<'_>, context: &ExpandContext) -> bool {
+        let checkpoint = token_nodes.checkpoint(context);
+
+        expand_negation_operator(checkpoint.iterator).is_ok()
+    }
+}
+
+impl ExpandExpression for UnaryNegationShape {
+    fn name(&self) -> &'static str {
+        "negation"
+    }
+
+    fn expand_expr<'a, 'b>(
+        &self,
+        token_nodes: &mut TokensIterator<'_>,
+        context: &ExpandContext,
+    ) -> Result<hir::Expression, ParseError> {
+        token_nodes.atomic_parse(context, |token_nodes| {
+            let op = expand_negation_operator(token_nodes)?;
+            let expr = expand_unary_operand(token_nodes, context)?;
+
+            Ok(hir::Expression::unary(Operator::Negate.spanned(op), expr))
+        })
+    }
+}
+
+#[cfg(not(coloring_in_tokens))]
+impl FallibleColorSyntax for UnaryNegationShape {
+    type Info = ();
+    type Input = ();
+
+    fn color_syntax<'a, 'b>(
+        &self,
+        _input: &(),
+        token_nodes: &'b mut TokensIterator<'a>,
+        context: &ExpandContext,
+        shapes: &mut Vec<Spanned<FlatShape>>,
+    ) -> Result<(), ShellError> {
+        let op = expand_negation_operator(token_nodes)?;
+        shapes.push(FlatShape::Operator.spanned(op));
+
+        color_fallible_syntax(&AnyExpressionStartShape, token_nodes, context, shapes)
+    }
+}
+
+#[cfg(coloring_in_tokens)]
+impl FallibleColorSyntax for UnaryNegationShape {
+    type Info = ();
+    type Input = ();
+
+    fn name(&self) -> &'static str {
+        "UnaryNegationShape"
+    }
+
+    fn color_syntax<'a, 'b>(
+        &self,
+        _input: &(),
+        token_nodes: &'b mut TokensIterator<'a>,
+        context: &ExpandContext,
+    ) -> Result<(), ShellError> {
+        let op = expand_negation_operator(token_nodes)?;
+        token_nodes.color_shape(FlatShape::Operator.spanned(op));
+
+        color_fallible_syntax(&AnyExpressionStartShape, token_nodes, context)
+    }
+}
+
+/// An expression in a position that can only hold an expression, like a block, a
+/// parenthesized expression or a member filter, rather than a command argument. Only
+/// here can it start with `not`, so `echo not foo` still passes the word `not` to `echo`.
+#[derive(Debug, Copy, Clone)]
+pub struct NotExpressionShape;
+
+impl ExpandExpression for NotExpressionShape {
+    fn name(&self) -> &'static str {
+        "expression"
+    }
+
+    fn expand_expr<'a, 'b>(
+        &self,
+        token_nodes: &mut TokensIterator<'_>,
+        context: &ExpandContext,
+    ) -> Result<hir::Expression, ParseError> {
+        if !UnaryNotShape::peek(token_nodes, context) {
+            return expand_expr(&AnyExpressionShape, token_nodes, context);
+        }
+
+        let head = expand_expr(&UnaryNotShape, token_nodes, context)?;
+
+        Ok(continue_expression(head, token_nodes, context))
+    }
+}
+
+#[cfg(not(coloring_in_tokens))]
+impl FallibleColorSyntax for NotExpressionShape {
+    type Info = ();
+    type Input = ();
+
+    fn color_syntax<'a, 'b>(
+        &self,
+        _input: &(),
+        token_nodes: &'b mut TokensIterator<'a>,
+        context: &ExpandContext,
+        shapes: &mut Vec<Spanned<FlatShape>>,
+    ) -> Result<(), ShellError> {
+        if !UnaryNotShape::peek(token_nodes, context) {
+            return color_fallible_syntax(&AnyExpressionShape, token_nodes, context, shapes);
+        }
+
+        color_fallible_syntax(&UnaryNotShape, token_nodes, context, shapes)?;
+
+        // it's fine for there to be no continuation
+        let _ = continue_coloring_expression(token_nodes, context, shapes);
+
+        Ok(())
+    }
+}
+
+#[cfg(coloring_in_tokens)]
+impl FallibleColorSyntax for NotExpressionShape {
+    type Info = ();
+    type Input = ();
+
+    fn name(&self) -> &'static str {
+        "NotExpressionShape"
+    }
+
+    fn color_syntax<'a, 'b>(
+        &self,
+        _input: &(),
+        token_nodes: &'b mut TokensIterator<'a>,
+        context: &ExpandContext,
+    ) -> Result<(), ShellError> {
+        if !UnaryNotShape::peek(token_nodes, context) {
+            return color_fallible_syntax(&AnyExpressionShape, token_nodes, context);
+        }
+
+        color_fallible_syntax(&UnaryNotShape, token_nodes, context)?;
+
+        // it's fine for there to be no continuation
+        let _ = continue_coloring_expression(token_nodes, context);
+
+        Ok(())
+    }
+}
+
+/// The word `not` followed by whitespace and an expression, like `not $flag` or
+/// `not ($a == $b)`. It binds more loosely than comparisons, so `not $a == $b` negates
+/// `$a == $b`, but more tightly than `&&` and `||`.
+#[derive(Debug, Copy, Clone)]
+pub struct UnaryNotShape;
+
+impl UnaryNotShape {
+    /// Whether the next tokens are `not`, whitespace and the start of an expression.
+    /// Nothing is consumed.
+    pub fn peek(token_nodes: &mut TokensIterator<'_>, context: &ExpandContext) -> bool {
+        let checkpoint = token_nodes.checkpoint(context);
+
+        expand_not_operator(checkpoint.iterator, context).is_ok()
+    }
+}
+
+impl ExpandExpression for UnaryNotShape {
+    fn name(&self) -> &'static str {
+        "not"
+    }
+
+    fn expand_expr<'a, 'b>(
+        &self,
+        token_nodes: &mut TokensIterator<'_>,
+        context: &ExpandContext,
+    ) -> Result<hir::Expression, ParseError> {
+        token_nodes.atomic_parse(context, |token_nodes| {
+            let op = expand_not_operator(token_nodes, context)?;
+            let expr = expand_not_operand(token_nodes, context)?;
+
+            Ok(hir::Expression::unary(Operator::Not.spanned(op), expr))
+        })
+    }
+}
+
+#[cfg(not(coloring_in_tokens))]
+impl FallibleColorSyntax for UnaryNotShape {
+    type Info = ();
+    type Input = ();
+
+    fn color_syntax<'a, 'b>(
+        &self,
+        _input: &(),
+        token_nodes: &'b mut TokensIterator<'a>,
+        context: &ExpandContext,
+        shapes: &mut Vec<Spanned<FlatShape>>,
+    ) -> Result<(), ShellError> {
+        let op = expand_syntax(&NotWordShape, token_nodes, context)?;
+        shapes.push(FlatShape::operator(Operator::Not).spanned(op));
+
+        color_fallible_syntax(&WhitespaceShape, token_nodes, context, shapes)?;
+        color_fallible_syntax(&AnyExpressionStartShape, token_nodes, context, shapes)
+    }
+}
+
+#[cfg(coloring_in_tokens)]
+impl FallibleColorSyntax for UnaryNotShape {
+    type Info = ();
+    type Input = ();
+
+    fn name(&self) -> &'static str {
+        "UnaryNotShape"
+    }
+
+    fn color_syntax<'a, 'b>(
+        &self,
+        _input: &(),
+        token_nodes: &'b mut TokensIterator<'a>,
+        context: &ExpandContext,
+    ) -> Result<(), ShellError> {
+        let op = expand_syntax(&NotWordShape, token_nodes, context)?;
+        token_nodes.color_shape(FlatShape::operator(Operator::Not).spanned(op));
+
+        color_fallible_syntax(&WhitespaceShape, token_nodes, context)?;
+        color_fallible_syntax(&AnyExpressionStartShape, token_nodes, context)
+    }
+}
+
+/// The bare word `not`
+#[derive(Debug, Copy, Clone)]
+struct NotWordShape;
+
+impl ExpandSyntax for NotWordShape {
+    type Output = Span;
+
+    fn name(&self) -> &'static str {
+        "not"
+    }
+
+    fn expand_syntax<'a, 'b>(
+        &self,
+        token_nodes: &'b mut TokensIterator<'a>,
+        context: &ExpandContext,
+    ) -> Result<Span, ParseError> {
+        let peeked = token_nodes.peek_any().not_eof("not")?;
+
+        let span = match peeked.node {
+            TokenNode::Token(Spanned {
+                item: RawToken::Bare,
+                span,
+            }) if span.slice(context.source) == "not" => *span,
+            _ => return Err(peeked.type_error("not")),
+        };
+
+        peeked.commit();
+        Ok(span)
+    }
+}
+
+/// Consume `not` and the whitespace after it, as long as an operand follows. A `not`
+/// followed by an infix operator, like in `not == 1`, is an ordinary word.
+fn expand_not_operator(
+    token_nodes: &mut TokensIterator<'_>,
+    context: &ExpandContext,
+) -> Result<Span, ParseError> {
+    let span = expand_syntax(&NotWordShape, token_nodes, context)?;
+    expand_syntax(&WhitespaceShape, token_nodes, context)?;
+
+    match token_nodes.peek_any().node {
+        None
+        | Some(TokenNode::Token(Spanned {
+            item: RawToken::Operator(_),
+            ..
+        })) => Err(ParseError::mismatch("not", "word".spanned(span))),
+        Some(_) => Ok(span),
+    }
+}
+
+/// The operand of `not`: an expression up to the first operator that binds more loosely
+/// than `not`, so in `not $a == $b && $c`, it's `$a == $b`. A `|` pipes the whole `not`
+/// expression, so it ends the operand too.
+fn expand_not_operand(
+    token_nodes: &mut TokensIterator<'_>,
+    context: &ExpandContext,
+) -> Result<hir::Expression, ParseError> {
+    let mut expr = if UnaryNotShape::peek(token_nodes, context) {
+        expand_expr(&UnaryNotShape, token_nodes, context)?
+    } else if let Some(expr) = expand_parenthesized_operand(token_nodes, context)? {
+        expr
+    } else {
+        expand_expr(&AnyExpressionStartShape, token_nodes, context)?
+    };
+
+    loop {
+        let checkpoint = token_nodes.checkpoint(context);
+
+        match expand_syntax(&ExpressionContinuationShape, checkpoint.iterator, context) {
+            Ok(ExpressionContinuation::InfixSuffix(ref op, _))
+                if op.item.precedence() <= Operator::Not.precedence() =>
+            {
+                return Ok(expr)
+            }
+            // a pipe takes the whole `not` expression
+            Ok(ExpressionContinuation::PipeSuffix(..)) => return Ok(expr),
+            Ok(continuation) => {
+                checkpoint.commit();
+                expr = continue_with(expr, continuation);
+            }
+            Err(_) => return Ok(expr),
+        }
+    }
+}
+
+/// Consume a `-` operator, as long as it's directly followed by another token
+fn expand_negation_operator(token_nodes: &mut TokensIterator<'_>) -> Result<Span, ParseError> {
+    let peeked = token_nodes.peek_any().not_eof("negation")?;
+
+    let span = match peeked.node {
+        TokenNode::Token(Spanned {
+            item: RawToken::Operator(Operator::Minus),
+            span,
+        }) => *span,
+        _ => return Err(peeked.type_error("negation")),
+    };
+
+    peeked.commit();
+
+    match token_nodes.peek_any().node {
+        None | Some(TokenNode::Whitespace(_)) => {
+            Err(ParseError::mismatch("negation", "operator".spanned(span)))
+        }
+        Some(_) => Ok(span),
+    }
+}
+
+/// The expression after `-`. A parenthesized operand is a whole expression, like the
+/// `1 + 2` in `-(1 + 2)`.
+fn expand_unary_operand(
+    token_nodes: &mut TokensIterator<'_>,
+    context: &ExpandContext,
+) -> Result<hir::Expression, ParseError> {
+    if let Some(expr) = expand_parenthesized_operand(token_nodes, context)? {
+        return Ok(expr);
+    }
+
+    let mut expr = expand_expr(&AnyExpressionStartShape, token_nodes, context)?;
+
+    // `-$x.size` negates the size, so members bind tighter than the operator
+    loop {
+        let mut checkpoint = token_nodes.checkpoint(context);
+
+        match expand_syntax(&ExpressionContinuationShape, checkpoint.iterator, context) {
+            Ok(ExpressionContinuation::DotSuffix(_dot, member, marker)) => {
+                checkpoint.commit();
+                expr = hir::Expression::marked_dot_member(expr, member, marker);
+            }
+            Ok(ExpressionContinuation::IndexSuffix(index)) => {
+                checkpoint.commit();
+                expr = hir::Expression::dot_member(expr, index);
+            }
+            _ => return Ok(expr),
+        }
+    }
+}
+
+/// A parenthesized operand of a prefix operator, which is a whole expression, like the
+/// `1 + 2` in `-(1 + 2)`. The expression spans the parentheses, and stays grouped when
+/// an operator follows it, like the `* 3` in `not (1 + 2) * 3`. If the next token isn't
+/// parenthesized, nothing is consumed and this returns `Ok(None)`.
+fn expand_parenthesized_operand(
+    token_nodes: &mut TokensIterator<'_>,
+    context: &ExpandContext,
+) -> Result<Option<hir::Expression>, ParseError> {
+    let mut peeked = token_nodes.peek_any();
+
+    let (children, span) = match peeked.node {
+        Some(TokenNode::Delimited(Spanned {
+            item:
+                DelimitedNode {
+                    delimiter: Delimiter::Paren,
+                    children,
+                    ..
+                },
+            span,
+        })) => (children, *span),
+        _ => return Ok(None),
+    };
+
+    peeked.commit();
+
+    let mut tokens = TokensIterator::new(children, span, false);
+
+    // whitespace just inside the parens is allowed
+    let _ = expand_syntax(&WhitespaceShape, &mut tokens, context);
+    let expr = expand_expr(&NotExpressionShape, &mut tokens, context)?;
+
+    if !tokens.at_end_possible_ws() {
+        return Err(tokens
+            .peek_non_ws()
+            .type_error("end of parenthesized expression"));
+    }
+
+    Ok(Some(hir::Expression::parenthesized(expr, span)))
+}
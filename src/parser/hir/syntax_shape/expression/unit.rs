@@ -59,7 +59,108 @@ impl ExpandSyntax for UnitShape {
     }
 }
 
-fn unit_size(input: &str, bare_span: Span) -> IResult<&str, (Spanned<RawNumber>, Spanned<Unit>)> {
+/// A duration literal, like `5min` or `3sec`. The time unit must directly follow the
+/// number, so `5 min` isn't a duration. Durations are whole seconds, so sub-second units
+/// like `ms` aren't accepted rather than silently rounding to zero.
+#[derive(Debug, Copy, Clone)]
+pub struct DurationShape;
+
+impl ExpandSyntax for DurationShape {
+    type Output = Spanned<(Spanned<RawNumber>, Spanned<Unit>)>;
+
+    fn name(&self) -> &'static str {
+        "duration"
+    }
+
+    fn expand_syntax<'a, 'b>(
+        &self,
+        token_nodes: &'b mut TokensIterator<'a>,
+        context: &ExpandContext,
+    ) -> Result<Spanned<(Spanned<RawNumber>, Spanned<Unit>)>, ParseError> {
+        expand_unit_literal(token_nodes, context, "duration", duration_unit)
+    }
+}
+
+/// A file size literal, like `10kb` or `1gib`. Decimal units like `kb` are powers of
+/// 1000, and binary units like `kib` are powers of 1024. The unit must directly follow
+/// the number.
+#[derive(Debug, Copy, Clone)]
+pub struct FilesizeShape;
+
+impl ExpandSyntax for FilesizeShape {
+    type Output = Spanned<(Spanned<RawNumber>, Spanned<Unit>)>;
+
+    fn name(&self) -> &'static str {
+        "filesize"
+    }
+
+    fn expand_syntax<'a, 'b>(
+        &self,
+        token_nodes: &'b mut TokensIterator<'a>,
+        context: &ExpandContext,
+    ) -> Result<Spanned<(Spanned<RawNumber>, Spanned<Unit>)>, ParseError> {
+        expand_unit_literal(token_nodes, context, "filesize", filesize_unit)
+    }
+}
+
+/// Expand a bare word made of a number and a unit suffix that `to_unit` accepts.
+/// `expected_literal` names the literal, for errors.
+fn expand_unit_literal(
+    token_nodes: &mut TokensIterator<'_>,
+    context: &ExpandContext,
+    expected_literal: &'static str,
+    to_unit: fn(Spanned<&str>) -> Result<Unit, ParseError>,
+) -> Result<Spanned<(Spanned<RawNumber>, Spanned<Unit>)>, ParseError> {
+    let peeked = token_nodes.peek_any().not_eof(expected_literal)?;
+
+    let span = match peeked.node {
+        TokenNode::Token(Spanned {
+            item: RawToken::Bare,
+            span,
+        }) => *span,
+        _ => return Err(peeked.type_error(expected_literal)),
+    };
+
+    let (suffix, number) = match unit_number(span.slice(context.source), span) {
+        Ok((suffix, number)) if !suffix.is_empty() => (suffix, number),
+        _ => return Err(ParseError::mismatch(expected_literal, "word".spanned(span))),
+    };
+
+    let unit_span = Span::new(number.span.end(), span.end());
+
+    let unit = to_unit(suffix.spanned(unit_span))?;
+
+    peeked.commit();
+    Ok((number, unit.spanned(unit_span)).spanned(span))
+}
+
+fn duration_unit(suffix: Spanned<&str>) -> Result<Unit, ParseError> {
+    Ok(match suffix.item {
+        "sec" => Unit::Second,
+        "min" => Unit::Minute,
+        "hr" => Unit::Hour,
+        "day" => Unit::Day,
+        "wk" => Unit::Week,
+        "ns" | "us" | "ms" => return Err(ParseError::sub_second_duration(suffix.span)),
+        _ => return Err(ParseError::mismatch("duration unit", suffix)),
+    })
+}
+
+fn filesize_unit(suffix: Spanned<&str>) -> Result<Unit, ParseError> {
+    Ok(match &suffix.item.to_ascii_lowercase()[..] {
+        "b" => Unit::Byte,
+        "kb" => Unit::Kilobyte,
+        "mb" => Unit::Megabyte,
+        "gb" => Unit::Gigabyte,
+        "kib" => Unit::Kibibyte,
+        "mib" => Unit::Mebibyte,
+        "gib" => Unit::Gibibyte,
+        _ => return Err(ParseError::mismatch("filesize unit", suffix)),
+    })
+}
+
+/// The number at the start of a unit literal, along with the rest of the input
+fn unit_number(input: &str, bare_span: Span) -> IResult<&str, Spanned<RawNumber>> {
     let (input, digits) = digit1(input)?;
 
     let (input, dot) = opt(tag("."))(input)?;
@@ -85,6 +186,12 @@ fn unit_size(input: &str, bare_span: Span) -> IResult<&str, (Spanned<RawNumber>,
         ),
     };
 
+    Ok((input, number))
+}
+
+fn unit_size(input: &str, bare_span: Span) -> IResult<&str, (Spanned<RawNumber>, Spanned<Unit>)> {
+    let (input, number) = unit_number(input, bare_span)?;
+
     let (input, unit) = all_consuming(alt((
         value(Unit::Byte, alt((tag("B"), tag("b")))),
         value(Unit::Kilobyte, alt((tag("KB"), tag("kb"), tag("Kb")))),
@@ -1,16 +1,36 @@
-use crate::parser::hir::path::PathMember;
+use crate::commands::classified::ClassifiedPipeline;
+use crate::parser::hir::path::{MemberMarker, PathMember};
+#[cfg(coloring_in_tokens)]
+use crate::parser::hir::syntax_shape::TokenShapes;
 use crate::parser::hir::syntax_shape::{
-    color_fallible_syntax, color_fallible_syntax_with, expand_atom, expand_expr, expand_syntax,
-    parse_single_node, AnyExpressionShape, AtomicToken, BareShape, ExpandContext, ExpandExpression,
-    ExpandSyntax, ExpansionRule, FallibleColorSyntax, FlatShape, ParseError, Peeked, SkipSyntax,
-    StringShape, TestSyntax, WhitespaceShape,
+    color_delimited_square, color_fallible_syntax, expand_atom, expand_delimited_range,
+    expand_expr, expand_range, expand_syntax, parse_single_node, spaced, AnyExpressionShape,
+    AnyExpressionStartShape, AtomicToken, BareShape, ColorCollector, ExpandContext,
+    ExpandExpression, ExpandSyntax, ExpansionRule, FallibleColorSyntax, FlatShape,
+    NotExpressionShape, ParseError, Peeked, PipelineShape, RangeExpression, SemanticToken,
+    SkipSyntax, StringShape, TestSyntax, TypeName, TypeNameShape, WhitespaceShape,
+};
+use crate::parser::parse::pipeline::PipelineElement;
+use crate::parser::parse::token_tree_builder::TokenTreeBuilder;
+use crate::parser::parse::tokens::parse_int;
+use crate::parser::{
+    hir, hir::Expression, hir::TokensIterator, DelimitedNode, Delimiter, Operator, RawNumber,
+    RawToken, TokenNode,
 };
-use crate::parser::{hir, hir::Expression, hir::TokensIterator, Operator, RawNumber, RawToken};
 use crate::prelude::*;
 use serde::Serialize;
+use smallvec::{smallvec, SmallVec};
+use std::collections::HashSet;
 use std::fmt;
 use std::str::FromStr;
 
+/// The members of a column path. Most paths only have a few members, so they're stored
+/// inline rather than in a separate allocation.
+pub type ColumnPathMembers = SmallVec<[Member; 4]>;
+
+/// The members of a path's tail, stored inline like `ColumnPathMembers`
+pub type PathTailMembers = SmallVec<[PathMember; 4]>;
+
 #[derive(Debug, Copy, Clone)]
 pub struct VariablePathShape;
 
@@ -34,21 +54,210 @@ impl ExpandExpression for VariablePathShape {
         let start = head.span;
         let mut end = start;
         let mut tail: Vec<PathMember> = vec![];
+        let mut markers: Vec<Option<MemberMarker>> = vec![];
+        let mut lenient: Option<Span> = None;
+        let mut rooted = false;
 
         loop {
-            match DotShape.skip(token_nodes, context) {
+            let dot = match expand_syntax(&DotShape, token_nodes, context) {
                 Err(_) => break,
-                Ok(_) => {}
+                Ok(dot) => dot,
+            };
+
+            if let Ok(second) = expand_syntax(&DotShape, token_nodes, context) {
+                return Err(ParseError::repeated_dot(dot, second));
+            }
+
+            if let Ok(question) = expand_syntax(&QuestionMarkShape, token_nodes, context) {
+                lenient = Some(question);
+                break;
             }
 
             let member = expand_syntax(&MemberShape, token_nodes, context)?;
-            let member = member.to_path_member(context.source);
+            let (root, mut members) = split_member(member, context)?;
 
-            end = member.span;
-            tail.push(member);
+            if let Some(root) = root {
+                // only the first member can anchor the path
+                if !tail.is_empty() {
+                    return Err(ParseError::mismatch("member", "separator".spanned(root)));
+                }
+
+                rooted = true;
+            }
+
+            if let Some(type_name) = expand_member_cast(token_nodes, context)? {
+                if let Some(last) = members.pop() {
+                    let span = last.span().until(type_name.span);
+                    members.push(Member::Cast(Box::new(last), type_name.item, span));
+                }
+            }
+
+            // a splat flattens whatever the path has addressed, and a predicate runs to the
+            // end of the path, so either one ends the path
+            let mut ends_path = false;
+
+            if let Some(splat) = expand_ellipsis(token_nodes, context) {
+                if let Some(last) = members.pop() {
+                    let span = last.span().until(splat);
+                    members.push(Member::Splat(Box::new(last), span));
+                }
+
+                ends_path = true;
+            } else if let Some(predicate) = expand_member_condition(token_nodes, context)? {
+                if let Some(last) = members.pop() {
+                    let span = last.span().until(predicate.span);
+                    members.push(Member::Conditional(Box::new(last), predicate, span));
+                }
+
+                ends_path = true;
+            }
+
+            let mut members: Vec<PathMember> = members
+                .iter()
+                .map(|member| member.to_interned_path_member(context))
+                .collect();
+
+            if ends_path {
+                // nothing can follow the end of the path, not even a marker or an index
+                markers.extend(members.iter().map(|_| None));
+                end = members.last().map(|last| last.span).unwrap_or(end);
+                tail.extend(members);
+                break;
+            }
+
+            // each member can have its own marker, which is part of the member's span
+            let mut marker = expand_syntax(&MemberMarkerShape, token_nodes, context).ok();
+
+            if let Some(index) = expand_member_index(token_nodes, context)? {
+                // a marker right before an index could apply to either the member or the
+                // index, so it has to go after the index instead
+                if let Some(marker) = &marker {
+                    return Err(ParseError::ambiguous_marker(marker.span));
+                }
+
+                members.push(index_path_member(&index, context));
+                marker = expand_syntax(&MemberMarkerShape, token_nodes, context).ok();
+            }
+
+            if let (Some(marker), Some(last)) = (&marker, members.last_mut()) {
+                last.span = last.span.until(marker.span);
+            }
+
+            for _ in 1..members.len() {
+                markers.push(None);
+            }
+
+            markers.push(marker.map(|marker| marker.item));
+
+            if let Some(last) = members.last() {
+                end = last.span;
+            }
+
+            tail.extend(members);
+        }
+
+        let path = match lenient {
+            None => hir::Expression::path(head, tail, start.until(end)),
+            Some(question) => {
+                // `.?` applies to the whole path, so nothing can come after it
+                if let Ok(dot) = expand_syntax(&DotShape, token_nodes, context) {
+                    return Err(ParseError::mismatch(
+                        "end of path after `.?`",
+                        "dot".spanned(dot),
+                    ));
+                }
+
+                hir::Expression::lenient_path(head, tail, start.until(question))
+            }
+        };
+
+        let path = path.with_markers(markers);
+
+        if rooted {
+            Ok(path.map_path(hir::Path::into_rooted))
+        } else {
+            Ok(path)
+        }
+    }
+}
+
+/// Split a bare member on the context's path separator, like `a/b` into `a` and `b`,
+/// returning the span of a leading separator if there is one. Without a path
+/// separator, or for any other kind of member, the member is left alone.
+fn split_member(
+    member: Member,
+    context: &ExpandContext,
+) -> Result<(Option<Span>, Vec<Member>), ParseError> {
+    let (separator, span) = match (context.path_separator, &member) {
+        (Some(separator), Member::Bare(span)) => (separator, *span),
+        _ => return Ok((None, vec![member])),
+    };
+
+    let text = span.slice(context.source);
+    let separator_len = separator.len_utf8();
+    let mut root = None;
+    let mut members = vec![];
+    let mut offset = span.start();
+
+    for (i, part) in text.split(separator).enumerate() {
+        let part_span = Span::new(offset, offset + part.len());
+        let separator_span = Span::new(part_span.end(), part_span.end() + separator_len);
+        offset = separator_span.end();
+
+        if !part.is_empty() {
+            members.push(Member::Bare(part_span));
+        } else if i == 0 {
+            root = Some(separator_span);
+        } else {
+            // an empty member, like in `a//b` or `a/`
+            return Err(ParseError::mismatch(
+                "member",
+                "separator".spanned(Span::new(
+                    part_span.start() - separator_len,
+                    part_span.start(),
+                )),
+            ));
+        }
+    }
+
+    Ok((root, members))
+}
+
+impl VariablePathShape {
+    /// Produce LSP semantic tokens for the variable path at the cursor, using the same
+    /// shapes that coloring the path would produce. If there's no variable path at the
+    /// cursor, no tokens are produced.
+    #[cfg(not(coloring_in_tokens))]
+    pub fn semantic_tokens(
+        &self,
+        token_nodes: &mut TokensIterator<'_>,
+        context: &ExpandContext,
+    ) -> Vec<SemanticToken> {
+        let mut shapes = vec![];
+
+        match color_fallible_syntax(self, token_nodes, context, &mut shapes) {
+            Ok(()) => SemanticToken::from_shapes(&shapes),
+            Err(_) => vec![],
         }
+    }
 
-        Ok(hir::Expression::path(head, tail, start.until(end)))
+    /// Produce LSP semantic tokens for the variable path at the cursor, using the same
+    /// shapes that coloring the path would produce. If there's no variable path at the
+    /// cursor, no tokens are produced.
+    #[cfg(coloring_in_tokens)]
+    pub fn semantic_tokens(
+        &self,
+        token_nodes: &mut TokensIterator<'_>,
+        context: &ExpandContext,
+    ) -> Vec<SemanticToken> {
+        let (result, shapes) = token_nodes.atomic_returning_shapes(|token_nodes| {
+            color_fallible_syntax(self, token_nodes, context)
+        });
+
+        match result {
+            Ok(()) => SemanticToken::from_shapes(&shapes),
+            Err(_) => vec![],
+        }
     }
 }
 
@@ -64,32 +273,7 @@ impl FallibleColorSyntax for VariablePathShape {
         context: &ExpandContext,
         shapes: &mut Vec<Spanned<FlatShape>>,
     ) -> Result<(), ShellError> {
-        token_nodes.atomic(|token_nodes| {
-            // If the head of the token stream is not a variable, fail
-            color_fallible_syntax(&VariableShape, token_nodes, context, shapes)?;
-
-            loop {
-                // look for a dot at the head of a stream
-                let dot = color_fallible_syntax_with(
-                    &ColorableDotShape,
-                    &FlatShape::Dot,
-                    token_nodes,
-                    context,
-                    shapes,
-                );
-
-                // if there's no dot, we're done
-                match dot {
-                    Err(_) => break,
-                    Ok(_) => {}
-                }
-
-                // otherwise, look for a member, and if you don't find one, fail
-                color_fallible_syntax(&MemberShape, token_nodes, context, shapes)?;
-            }
-
-            Ok(())
-        })
+        color_variable_path(token_nodes, context, shapes)
     }
 }
 
@@ -108,39 +292,47 @@ impl FallibleColorSyntax for VariablePathShape {
         token_nodes: &'b mut TokensIterator<'a>,
         context: &ExpandContext,
     ) -> Result<(), ShellError> {
-        token_nodes.atomic(|token_nodes| {
-            // If the head of the token stream is not a variable, fail
-            color_fallible_syntax(&VariableShape, token_nodes, context)?;
-
-            loop {
-                // look for a dot at the head of a stream
-                let dot = color_fallible_syntax_with(
-                    &ColorableDotShape,
-                    &FlatShape::Dot,
-                    token_nodes,
-                    context,
-                );
-
-                // if there's no dot, we're done
-                match dot {
-                    Err(_) => break,
-                    Ok(_) => {}
-                }
+        color_variable_path(token_nodes, context, &mut TokenShapes)
+    }
+}
 
-                // otherwise, look for a member, and if you don't find one, fail
-                color_fallible_syntax(&MemberShape, token_nodes, context)?;
-            }
+fn color_variable_path(
+    token_nodes: &mut TokensIterator<'_>,
+    context: &ExpandContext,
+    shapes: &mut impl ColorCollector,
+) -> Result<(), ShellError> {
+    // If the head of the token stream is not a variable, fail
+    shapes.color(&VariableShape, token_nodes, context)?;
 
-            Ok(())
-        })
+    loop {
+        // look for a dot at the head of a stream
+        let dot = shapes.color_with(&ColorableDotShape, &FlatShape::Dot, token_nodes, context);
+
+        // if there's no dot, we're done
+        match dot {
+            Err(_) => break,
+            Ok(_) => {}
+        }
+
+        // otherwise, look for a member. A dot without one keeps its color, since it's
+        // usually a member that hasn't been typed yet, like in `$config.`
+        let member = shapes.atomic(token_nodes, |token_nodes, shapes| {
+            shapes.color(&MemberShape, token_nodes, context)
+        });
+
+        if member.is_err() {
+            break;
+        }
     }
+
+    Ok(())
 }
 
 #[derive(Debug, Copy, Clone)]
 pub struct PathTailShape;
 
 #[cfg(not(coloring_in_tokens))]
-/// The failure mode of `PathTailShape` is a dot followed by a non-member
+/// A dot followed by a non-member ends the tail, but keeps its color
 impl FallibleColorSyntax for PathTailShape {
     type Info = ();
     type Input = ();
@@ -152,28 +344,12 @@ impl FallibleColorSyntax for PathTailShape {
         context: &ExpandContext,
         shapes: &mut Vec<Spanned<FlatShape>>,
     ) -> Result<(), ShellError> {
-        token_nodes.atomic(|token_nodes| loop {
-            let result = color_fallible_syntax_with(
-                &ColorableDotShape,
-                &FlatShape::Dot,
-                token_nodes,
-                context,
-                shapes,
-            );
-
-            match result {
-                Err(_) => return Ok(()),
-                Ok(_) => {}
-            }
-
-            // If we've seen a dot but not a member, fail
-            color_fallible_syntax(&MemberShape, token_nodes, context, shapes)?;
-        })
+        color_path_tail(token_nodes, context, shapes)
     }
 }
 
 #[cfg(coloring_in_tokens)]
-/// The failure mode of `PathTailShape` is a dot followed by a non-member
+/// A dot followed by a non-member ends the tail, but keeps its color
 impl FallibleColorSyntax for PathTailShape {
     type Info = ();
     type Input = ();
@@ -188,23 +364,32 @@ impl FallibleColorSyntax for PathTailShape {
         token_nodes: &'b mut TokensIterator<'a>,
         context: &ExpandContext,
     ) -> Result<(), ShellError> {
-        token_nodes.atomic(|token_nodes| loop {
-            let result = color_fallible_syntax_with(
-                &ColorableDotShape,
-                &FlatShape::Dot,
-                token_nodes,
-                context,
-            );
+        color_path_tail(token_nodes, context, &mut TokenShapes)
+    }
+}
 
-            match result {
-                Err(_) => return Ok(()),
-                Ok(_) => {}
-            }
+fn color_path_tail(
+    token_nodes: &mut TokensIterator<'_>,
+    context: &ExpandContext,
+    shapes: &mut impl ColorCollector,
+) -> Result<(), ShellError> {
+    // A dot without a member fails the loop, but keeps its color, since it's usually a
+    // member that hasn't been typed yet. It's the end of the tail either way.
+    let _ = token_nodes.best_effort(|token_nodes| loop {
+        let result = shapes.color_with(&ColorableDotShape, &FlatShape::Dot, token_nodes, context);
+
+        match result {
+            Err(_) => return Ok(()),
+            Ok(_) => {}
+        }
 
-            // If we've seen a dot but not a member, fail
-            color_fallible_syntax(&MemberShape, token_nodes, context)?;
-        })
-    }
+        // If we've seen a dot but not a member, fail
+        shapes.atomic(token_nodes, |token_nodes, shapes| {
+            shapes.color(&MemberShape, token_nodes, context)
+        })?;
+    });
+
+    Ok(())
 }
 
 impl FormatDebug for Spanned<Vec<PathMember>> {
@@ -221,7 +406,7 @@ impl FormatDebug for Spanned<Vec<PathMember>> {
 }
 
 impl ExpandSyntax for PathTailShape {
-    type Output = Spanned<Vec<PathMember>>;
+    type Output = Spanned<PathTailMembers>;
 
     fn name(&self) -> &'static str {
         "path continuation"
@@ -233,7 +418,7 @@ impl ExpandSyntax for PathTailShape {
         context: &ExpandContext,
     ) -> Result<Self::Output, ParseError> {
         let mut end: Option<Span> = None;
-        let mut tail: Vec<PathMember> = vec![];
+        let mut tail = PathTailMembers::new();
 
         loop {
             match DotShape.skip(token_nodes, context) {
@@ -242,7 +427,7 @@ impl ExpandSyntax for PathTailShape {
             }
 
             let member = expand_syntax(&MemberShape, token_nodes, context)?;
-            let member = member.to_path_member(context.source);
+            let member = member.to_interned_path_member(context);
             end = Some(member.span);
             tail.push(member);
         }
@@ -258,21 +443,36 @@ impl ExpandSyntax for PathTailShape {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ExpressionContinuation {
-    DotSuffix(Span, PathMember),
+    /// A `.member`, with the marker right after the member if it has one
+    DotSuffix(Span, PathMember, Option<MemberMarker>),
+    /// A `[index]` right after the expression, like the `[0]` in `$list[0]`
+    IndexSuffix(PathMember),
     InfixSuffix(Spanned<Operator>, Expression),
+    IsSuffix(Span, Spanned<TypeName>),
+    /// A ` | command`, which pipes the expression into the pipeline after the `|`
+    PipeSuffix(Span, ClassifiedPipeline),
 }
 
 impl FormatDebug for ExpressionContinuation {
     fn fmt_debug(&self, f: &mut DebugFormatter, source: &str) -> fmt::Result {
         match self {
-            ExpressionContinuation::DotSuffix(dot, rest) => {
+            ExpressionContinuation::DotSuffix(dot, rest, _) => {
                 f.say_str("dot suffix", dot.until(rest.span).slice(source))
             }
+            ExpressionContinuation::IndexSuffix(index) => {
+                f.say_str("index suffix", index.span.slice(source))
+            }
             ExpressionContinuation::InfixSuffix(operator, expr) => {
                 f.say_str("infix suffix", operator.span.until(expr.span).slice(source))
             }
+            ExpressionContinuation::IsSuffix(is, type_name) => {
+                f.say_str("is suffix", is.until(type_name.span).slice(source))
+            }
+            ExpressionContinuation::PipeSuffix(pipe, pipeline) => {
+                f.say_str("pipe suffix", pipe.until(pipeline.span()).slice(source))
+            }
         }
     }
 }
@@ -280,14 +480,24 @@ impl FormatDebug for ExpressionContinuation {
 impl HasSpan for ExpressionContinuation {
     fn span(&self) -> Span {
         match self {
-            ExpressionContinuation::DotSuffix(dot, column) => dot.until(column.span),
+            ExpressionContinuation::DotSuffix(dot, column, _) => dot.until(column.span),
+            ExpressionContinuation::IndexSuffix(index) => index.span,
             ExpressionContinuation::InfixSuffix(operator, expression) => {
                 operator.span.until(expression.span)
             }
+            ExpressionContinuation::IsSuffix(is, type_name) => is.until(type_name.span),
+            ExpressionContinuation::PipeSuffix(pipe, pipeline) => pipe.until(pipeline.span()),
         }
     }
 }
 
+impl ExpressionContinuation {
+    /// The source text the continuation was parsed from, like `.name` or `+ 1`
+    pub fn source_text<'source>(&self, source: &'source Text) -> &'source str {
+        self.span().slice(source)
+    }
+}
+
 /// An expression continuation
 #[derive(Debug, Copy, Clone)]
 pub struct ExpressionContinuationShape;
@@ -304,32 +514,60 @@ impl ExpandSyntax for ExpressionContinuationShape {
         token_nodes: &mut TokensIterator<'_>,
         context: &ExpandContext,
     ) -> Result<ExpressionContinuation, ParseError> {
-        // Try to expand a `.`
-        let dot = expand_syntax(&DotShape, token_nodes, context);
+        // If `is` and a type name are next, it's a type test
+        if let Some((is, type_name)) = expand_type_suffix(token_nodes, context, "is")? {
+            return Ok(ExpressionContinuation::IsSuffix(is, type_name));
+        }
 
-        match dot {
-            // If a `.` was matched, it's a `Path`, and we expect a `Member` next
-            Ok(dot) => {
-                let syntax = expand_syntax(&MemberShape, token_nodes, context)?;
-                let member = syntax.to_path_member(context.source);
+        // If whitespace and a `|` are next, the expression is piped into the rest of the
+        // tokens, which the pipeline parser expands like any other pipeline
+        if let Some(pipe) = expand_pipe(token_nodes, context) {
+            let pipeline = expand_piped_pipeline(token_nodes, context, pipe)?;
 
-                Ok(ExpressionContinuation::DotSuffix(dot, member))
-            }
+            return Ok(ExpressionContinuation::PipeSuffix(pipe, pipeline));
+        }
 
-            // Otherwise, we expect an infix operator and an expression next
-            Err(_) => {
-                let (_, op, _) = expand_syntax(&InfixShape, token_nodes, context)?.item;
-                let next = expand_expr(&AnyExpressionShape, token_nodes, context)?;
+        // If whitespace and an operator are next, we expect an infix operator and an
+        // expression
+        if InfixShape::peek(token_nodes, context) {
+            let (_, op, _) = expand_syntax(&InfixShape, token_nodes, context)?.item;
+            let next = expand_expr(&AnyExpressionStartShape, token_nodes, context)?;
 
-                Ok(ExpressionContinuation::InfixSuffix(op, next))
-            }
+            return Ok(ExpressionContinuation::InfixSuffix(op, next));
+        }
+
+        // If a `[` is right after the expression, it's an index, just like one after a member
+        if let Some(index) = expand_member_index(token_nodes, context)? {
+            return Ok(ExpressionContinuation::IndexSuffix(index_path_member(
+                &index, context,
+            )));
+        }
+
+        // Otherwise, it's a `Path`, and we expect a `.` and a `Member` next, which can be
+        // followed by a marker that's part of the member's span
+        let dot = expand_syntax(&DotShape, token_nodes, context)?;
+        let syntax = expand_syntax(&MemberShape, token_nodes, context)?;
+        let mut member = syntax.to_interned_path_member(context);
+        let marker = expand_syntax(&MemberMarkerShape, token_nodes, context).ok();
+
+        if let Some(marker) = &marker {
+            member.span = member.span.until(marker.span);
         }
+
+        Ok(ExpressionContinuation::DotSuffix(
+            dot,
+            member,
+            marker.map(|marker| marker.item),
+        ))
     }
 }
 
 pub enum ContinuationInfo {
     Dot,
+    Index,
     Infix,
+    TypeTest,
+    Pipe,
 }
 
 #[cfg(not(coloring_in_tokens))]
@@ -344,44 +582,7 @@ impl FallibleColorSyntax for ExpressionContinuationShape {
         context: &ExpandContext,
         shapes: &mut Vec<Spanned<FlatShape>>,
     ) -> Result<ContinuationInfo, ShellError> {
-        token_nodes.atomic(|token_nodes| {
-            // Try to expand a `.`
-            let dot = color_fallible_syntax_with(
-                &ColorableDotShape,
-                &FlatShape::Dot,
-                token_nodes,
-                context,
-                shapes,
-            );
-
-            match dot {
-                Ok(_) => {
-                    // we found a dot, so let's keep looking for a member; if no member was found, fail
-                    color_fallible_syntax(&MemberShape, token_nodes, context, shapes)?;
-
-                    Ok(ContinuationInfo::Dot)
-                }
-                Err(_) => {
-                    let mut new_shapes = vec![];
-                    let result = token_nodes.atomic(|token_nodes| {
-                        // we didn't find a dot, so let's see if we're looking at an infix. If not found, fail
-                        color_fallible_syntax(&InfixShape, token_nodes, context, &mut new_shapes)?;
-
-                        // now that we've seen an infix shape, look for any expression. If not found, fail
-                        color_fallible_syntax(
-                            &AnyExpressionShape,
-                            token_nodes,
-                            context,
-                            &mut new_shapes,
-                        )?;
-
-                        Ok(ContinuationInfo::Infix)
-                    })?;
-                    shapes.extend(new_shapes);
-                    Ok(result)
-                }
-            }
-        })
+        color_expression_continuation(token_nodes, context, shapes)
     }
 }
 
@@ -400,70 +601,154 @@ impl FallibleColorSyntax for ExpressionContinuationShape {
         token_nodes: &'b mut TokensIterator<'a>,
         context: &ExpandContext,
     ) -> Result<ContinuationInfo, ShellError> {
-        token_nodes.atomic(|token_nodes| {
-            // Try to expand a `.`
-            let dot = color_fallible_syntax_with(
-                &ColorableDotShape,
-                &FlatShape::Dot,
-                token_nodes,
-                context,
-            );
+        color_expression_continuation(token_nodes, context, &mut TokenShapes)
+    }
+}
 
-            match dot {
-                Ok(_) => {
-                    // we found a dot, so let's keep looking for a member; if no member was found, fail
-                    color_fallible_syntax(&MemberShape, token_nodes, context)?;
+fn color_expression_continuation(
+    token_nodes: &mut TokensIterator<'_>,
+    context: &ExpandContext,
+    shapes: &mut impl ColorCollector,
+) -> Result<ContinuationInfo, ShellError> {
+    token_nodes.atomic(|token_nodes| {
+        // Try to expand a `.`
+        let dot = shapes.color_with(&ColorableDotShape, &FlatShape::Dot, token_nodes, context);
 
-                    Ok(ContinuationInfo::Dot)
-                }
-                Err(_) => {
-                    let result = token_nodes.atomic(|token_nodes| {
-                        // we didn't find a dot, so let's see if we're looking at an infix. If not found, fail
-                        color_fallible_syntax(&InfixShape, token_nodes, context)?;
+        match dot {
+            Ok(_) => {
+                // we found a dot, so let's keep looking for a member; if no member was found, fail
+                shapes.color(&MemberShape, token_nodes, context)?;
 
-                        // now that we've seen an infix shape, look for any expression. If not found, fail
-                        color_fallible_syntax(&AnyExpressionShape, token_nodes, context)?;
+                Ok(ContinuationInfo::Dot)
+            }
+            Err(_) => {
+                // we didn't find a dot, so let's see if we're looking at an index
+                if shapes.color(&IndexShape, token_nodes, context).is_ok() {
+                    return Ok(ContinuationInfo::Index);
+                }
 
-                        Ok(ContinuationInfo::Infix)
-                    })?;
+                // or at an `is` type test
+                if shapes
+                    .color_with(&TypeSuffixShape, &"is", token_nodes, context)
+                    .is_ok()
+                {
+                    return Ok(ContinuationInfo::TypeTest);
+                }
 
-                    Ok(result)
+                // or at a pipe into a pipeline
+                if shapes.color(&PipeSuffixShape, token_nodes, context).is_ok() {
+                    return Ok(ContinuationInfo::Pipe);
                 }
+
+                shapes.atomic(token_nodes, |token_nodes, shapes| {
+                    // we didn't find a dot, so let's see if we're looking at an infix. If not found, fail
+                    shapes.color(&InfixShape, token_nodes, context)?;
+
+                    // now that we've seen an infix shape, look for any expression. If not found, fail
+                    shapes.color(&AnyExpressionShape, token_nodes, context)?;
+
+                    Ok(ContinuationInfo::Infix)
+                })
             }
-        })
-    }
+        }
+    })
 }
 
+/// A ` <keyword> <type>` suffix, like ` is int`. The keyword is the input.
 #[derive(Debug, Copy, Clone)]
-pub struct VariableShape;
+pub struct TypeSuffixShape;
+
+#[cfg(not(coloring_in_tokens))]
+impl FallibleColorSyntax for TypeSuffixShape {
+    type Info = ();
+    type Input = &'static str;
+
+    fn color_syntax<'a, 'b>(
+        &self,
+        keyword: &&'static str,
+        token_nodes: &'b mut TokensIterator<'a>,
+        context: &ExpandContext,
+        outer_shapes: &mut Vec<Spanned<FlatShape>>,
+    ) -> Result<(), ShellError> {
+        let checkpoint = token_nodes.checkpoint(context);
+        let mut shapes = vec![];
+
+        color_fallible_syntax(&WhitespaceShape, checkpoint.iterator, context, &mut shapes)?;
+
+        let keyword_span =
+            parse_single_node(
+                checkpoint.iterator,
+                *keyword,
+                |token, span, err| match token {
+                    RawToken::Bare if span.slice(context.source) == *keyword => Ok(span),
+                    _ => Err(err.error()),
+                },
+            )?;
+        shapes.push(FlatShape::Operator.spanned(keyword_span));
+
+        color_fallible_syntax(&WhitespaceShape, checkpoint.iterator, context, &mut shapes)?;
+
+        let type_name = expand_syntax(&TypeNameShape, checkpoint.iterator, context)?;
+        shapes.push(FlatShape::Word.spanned(type_name.span));
+
+        outer_shapes.extend(shapes);
+        checkpoint.commit();
+        Ok(())
+    }
+}
+
+#[cfg(coloring_in_tokens)]
+impl FallibleColorSyntax for TypeSuffixShape {
+    type Info = ();
+    type Input = &'static str;
 
-impl ExpandExpression for VariableShape {
     fn name(&self) -> &'static str {
-        "variable"
+        "TypeSuffixShape"
     }
 
-    fn expand_expr<'a, 'b>(
+    fn color_syntax<'a, 'b>(
         &self,
-        token_nodes: &mut TokensIterator<'_>,
+        keyword: &&'static str,
+        token_nodes: &'b mut TokensIterator<'a>,
         context: &ExpandContext,
-    ) -> Result<hir::Expression, ParseError> {
-        parse_single_node(token_nodes, "variable", |token, token_tag, err| {
-            Ok(match token {
-                RawToken::Variable(tag) => {
-                    if tag.slice(context.source) == "it" {
-                        hir::Expression::it_variable(tag, token_tag)
-                    } else {
-                        hir::Expression::variable(tag, token_tag)
-                    }
-                }
-                _ => return Err(err.error()),
-            })
-        })
+    ) -> Result<(), ShellError> {
+        let checkpoint = token_nodes.checkpoint(context);
+
+        color_fallible_syntax(&WhitespaceShape, checkpoint.iterator, context)?;
+
+        let keyword_span =
+            parse_single_node(
+                checkpoint.iterator,
+                *keyword,
+                |token, span, err| match token {
+                    RawToken::Bare if span.slice(context.source) == *keyword => Ok(span),
+                    _ => Err(err.error()),
+                },
+            )?;
+
+        checkpoint
+            .iterator
+            .color_shape(FlatShape::Operator.spanned(keyword_span));
+
+        color_fallible_syntax(&WhitespaceShape, checkpoint.iterator, context)?;
+
+        let type_name = expand_syntax(&TypeNameShape, checkpoint.iterator, context)?;
+
+        checkpoint
+            .iterator
+            .color_shape(FlatShape::Word.spanned(type_name.span));
+
+        checkpoint.commit();
+        Ok(())
     }
 }
 
+/// A ` | <pipeline>` suffix, which pipes the expression before it into a pipeline
+#[derive(Debug, Copy, Clone)]
+pub struct PipeSuffixShape;
+
 #[cfg(not(coloring_in_tokens))]
-impl FallibleColorSyntax for VariableShape {
+impl FallibleColorSyntax for PipeSuffixShape {
     type Info = ();
     type Input = ();
 
@@ -472,41 +757,42 @@ impl FallibleColorSyntax for VariableShape {
         _input: &(),
         token_nodes: &'b mut TokensIterator<'a>,
         context: &ExpandContext,
-        shapes: &mut Vec<Spanned<FlatShape>>,
+        outer_shapes: &mut Vec<Spanned<FlatShape>>,
     ) -> Result<(), ShellError> {
-        let atom = expand_atom(
-            token_nodes,
-            "variable",
-            context,
-            ExpansionRule::permissive(),
-        );
+        let checkpoint = token_nodes.checkpoint(context);
+        let mut shapes = vec![];
 
-        let atom = match atom {
-            Err(err) => return Err(err.into()),
-            Ok(atom) => atom,
+        let _ = color_fallible_syntax(&WhitespaceShape, checkpoint.iterator, context, &mut shapes);
+
+        let pipe = match expand_pipe(checkpoint.iterator, context) {
+            Some(pipe) => pipe,
+            None => return Err(checkpoint.iterator.peek_non_ws().type_error("pipe").into()),
         };
 
-        match &atom.item {
-            AtomicToken::Variable { .. } => {
-                shapes.push(FlatShape::Variable.spanned(atom.span));
-                Ok(())
-            }
-            AtomicToken::ItVariable { .. } => {
-                shapes.push(FlatShape::ItVariable.spanned(atom.span));
-                Ok(())
-            }
-            _ => Err(ShellError::type_error("variable", atom.spanned_type_name())),
-        }
+        // the pipeline colors the `|` along with its commands
+        let pipeline = rest_as_pipeline(checkpoint.iterator, context, pipe);
+        let span = pipeline.span();
+        let nodes = vec![pipeline];
+
+        checkpoint
+            .iterator
+            .child((&nodes[..]).spanned(span), |token_nodes| {
+                color_fallible_syntax(&PipelineShape, token_nodes, context, &mut shapes)
+            })?;
+
+        outer_shapes.extend(shapes);
+        checkpoint.commit();
+        Ok(())
     }
 }
 
 #[cfg(coloring_in_tokens)]
-impl FallibleColorSyntax for VariableShape {
+impl FallibleColorSyntax for PipeSuffixShape {
     type Info = ();
     type Input = ();
 
     fn name(&self) -> &'static str {
-        "VariableShape"
+        "PipeSuffixShape"
     }
 
     fn color_syntax<'a, 'b>(
@@ -515,37 +801,298 @@ impl FallibleColorSyntax for VariableShape {
         token_nodes: &'b mut TokensIterator<'a>,
         context: &ExpandContext,
     ) -> Result<(), ShellError> {
-        let atom = expand_atom(
-            token_nodes,
-            "variable",
-            context,
-            ExpansionRule::permissive(),
-        );
+        let checkpoint = token_nodes.checkpoint(context);
 
-        let atom = match atom {
-            Err(err) => return Err(err.into()),
-            Ok(atom) => atom,
+        let _ = color_fallible_syntax(&WhitespaceShape, checkpoint.iterator, context);
+
+        let pipe = match expand_pipe(checkpoint.iterator, context) {
+            Some(pipe) => pipe,
+            None => return Err(checkpoint.iterator.peek_non_ws().type_error("pipe").into()),
         };
 
-        match &atom.item {
-            AtomicToken::Variable { .. } => {
-                token_nodes.color_shape(FlatShape::Variable.spanned(atom.span));
-                Ok(())
-            }
-            AtomicToken::ItVariable { .. } => {
-                token_nodes.color_shape(FlatShape::ItVariable.spanned(atom.span));
-                Ok(())
-            }
-            _ => Err(ParseError::mismatch("variable", atom.type_name().spanned(atom.span)).into()),
-        }
-    }
-}
+        // the pipeline colors the `|` along with its commands
+        let pipeline = rest_as_pipeline(checkpoint.iterator, context, pipe);
+        let span = pipeline.span();
+        let nodes = vec![pipeline];
 
-#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
-pub enum Member {
+        checkpoint
+            .iterator
+            .child((&nodes[..]).spanned(span), |token_nodes| {
+                color_fallible_syntax(&PipelineShape, token_nodes, context)
+            })?;
+
+        checkpoint.commit();
+        Ok(())
+    }
+}
+
+/// Expand a ` |` that pipes the expression before it into a pipeline, returning the
+/// span of the `|`. Inside a member group, a `|` separates alternatives instead, so
+/// nothing is consumed and the group deals with it.
+fn expand_pipe(token_nodes: &mut TokensIterator<'_>, context: &ExpandContext) -> Option<Span> {
+    if context.member_nesting.get() > 0 {
+        return None;
+    }
+
+    expand_pipe_token(token_nodes, context)
+}
+
+/// Expand the rest of the tokens after the `|` at `pipe` with the pipeline parser, so
+/// the commands are classified just like the ones in a pipeline of their own
+fn expand_piped_pipeline(
+    token_nodes: &mut TokensIterator<'_>,
+    context: &ExpandContext,
+    pipe: Span,
+) -> Result<ClassifiedPipeline, ParseError> {
+    let pipeline = rest_as_pipeline(token_nodes, context, pipe);
+    let span = pipeline.span();
+    let nodes = vec![pipeline];
+
+    token_nodes.child((&nodes[..]).spanned(span), |token_nodes| {
+        expand_syntax(&PipelineShape, token_nodes, context)
+    })
+}
+
+/// Consume the rest of the tokens after the `|` at `pipe`, and split them at each
+/// further `|` into a pipeline token, the way the lexer splits a line. Inside parentheses,
+/// a `|` is lexed as a bare word rather than as part of a pipeline.
+fn rest_as_pipeline(
+    token_nodes: &mut TokensIterator<'_>,
+    context: &ExpandContext,
+    pipe: Span,
+) -> TokenNode {
+    let mut parts = vec![];
+    let mut part_pipe = pipe;
+    let mut part_tokens = vec![];
+
+    loop {
+        let node = match token_nodes.peek_any().commit() {
+            None => break,
+            Some(node) => node.clone(),
+        };
+
+        match pipe_token_span(&node, context) {
+            Some(next_pipe) => {
+                parts.push(pipeline_part(part_pipe, part_tokens));
+                part_pipe = next_pipe;
+                part_tokens = vec![];
+            }
+            None => part_tokens.push(node),
+        }
+    }
+
+    parts.push(pipeline_part(part_pipe, part_tokens));
+
+    let span = pipe.until(parts[parts.len() - 1].span);
+    TokenTreeBuilder::spanned_pipeline(parts, span)
+}
+
+/// One command of a pipeline, with the `|` before it
+fn pipeline_part(pipe: Span, tokens: Vec<TokenNode>) -> Spanned<PipelineElement> {
+    let tokens_span = match (tokens.first(), tokens.last()) {
+        (Some(first), Some(last)) => first.span().until(last.span()),
+        _ => pipe,
+    };
+
+    PipelineElement::new(Some(pipe), tokens.spanned(tokens_span)).spanned(pipe.until(tokens_span))
+}
+
+/// The span of `node` if it's a `|`
+fn pipe_token_span(node: &TokenNode, context: &ExpandContext) -> Option<Span> {
+    match node {
+        TokenNode::Token(Spanned {
+            item: RawToken::Bare,
+            span,
+        }) if span.slice(context.source) == "|" => Some(*span),
+        _ => None,
+    }
+}
+
+/// Expand a `|`, optionally preceded by whitespace, returning its span. If the next
+/// token isn't a `|`, nothing is consumed.
+fn expand_pipe_token(
+    token_nodes: &mut TokensIterator<'_>,
+    context: &ExpandContext,
+) -> Option<Span> {
+    let checkpoint = token_nodes.checkpoint(context);
+
+    let _ = expand_syntax(&WhitespaceShape, checkpoint.iterator, context);
+
+    let pipe = parse_single_node(
+        checkpoint.iterator,
+        "pipe",
+        |token, span, err| match token {
+            RawToken::Bare if span.slice(context.source) == "|" => Ok(span),
+            _ => Err(err.error()),
+        },
+    )
+    .ok()?;
+
+    checkpoint.commit();
+    Some(pipe)
+}
+
+/// Whether a `|`, optionally preceded by whitespace, is next. Nothing is consumed.
+fn at_pipe_token(token_nodes: &mut TokensIterator<'_>, context: &ExpandContext) -> bool {
+    let checkpoint = token_nodes.checkpoint(context);
+    expand_pipe_token(checkpoint.iterator, context).is_some()
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct VariableShape;
+
+impl ExpandExpression for VariableShape {
+    fn name(&self) -> &'static str {
+        "variable"
+    }
+
+    fn expand_expr<'a, 'b>(
+        &self,
+        token_nodes: &mut TokensIterator<'_>,
+        context: &ExpandContext,
+    ) -> Result<hir::Expression, ParseError> {
+        parse_single_node(token_nodes, "variable", |token, token_tag, err| {
+            Ok(match token {
+                RawToken::Variable(tag) if context.is_it_variable(tag) => {
+                    hir::Expression::it_variable(tag, token_tag)
+                }
+                RawToken::Variable(tag) => match tag.slice(context.source) {
+                    "nu" => hir::Expression::nu_variable(tag, token_tag),
+                    "env" => hir::Expression::env_variable(tag, token_tag),
+                    "nothing" => hir::Expression::nothing(token_tag),
+                    _ => hir::Expression::variable(tag, token_tag),
+                },
+                _ => return Err(err.error()),
+            })
+        })
+    }
+}
+
+#[cfg(not(coloring_in_tokens))]
+impl FallibleColorSyntax for VariableShape {
+    type Info = ();
+    type Input = ();
+
+    fn color_syntax<'a, 'b>(
+        &self,
+        _input: &(),
+        token_nodes: &'b mut TokensIterator<'a>,
+        context: &ExpandContext,
+        shapes: &mut Vec<Spanned<FlatShape>>,
+    ) -> Result<(), ShellError> {
+        color_variable(token_nodes, context, shapes)
+    }
+}
+
+#[cfg(coloring_in_tokens)]
+impl FallibleColorSyntax for VariableShape {
+    type Info = ();
+    type Input = ();
+
+    fn name(&self) -> &'static str {
+        "VariableShape"
+    }
+
+    fn color_syntax<'a, 'b>(
+        &self,
+        _input: &(),
+        token_nodes: &'b mut TokensIterator<'a>,
+        context: &ExpandContext,
+    ) -> Result<(), ShellError> {
+        color_variable(token_nodes, context, &mut TokenShapes)
+    }
+}
+
+fn color_variable(
+    token_nodes: &mut TokensIterator<'_>,
+    context: &ExpandContext,
+    shapes: &mut impl ColorCollector,
+) -> Result<(), ShellError> {
+    let atom = expand_atom(
+        token_nodes,
+        "variable",
+        context,
+        ExpansionRule::permissive(),
+    )?;
+
+    match &atom.item {
+        AtomicToken::Variable { .. } => {
+            shapes.push(token_nodes, FlatShape::Variable.spanned(atom.span));
+            Ok(())
+        }
+        AtomicToken::ItVariable { .. } => {
+            shapes.push(token_nodes, FlatShape::ItVariable.spanned(atom.span));
+            Ok(())
+        }
+        _ => Err(ShellError::type_error("variable", atom.spanned_type_name())),
+    }
+}
+
+/// A member of a column path, as it was written. The derived `Eq` and `Hash` are
+/// structural, so `name` and `"name"` are different members. Use `Member::matches` to
+/// compare members by the column they name.
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+pub enum Member {
     String(/* outer */ Span, /* inner */ Span),
     Int(BigInt, Span),
     Bare(Span),
+    Conditional(Box<Member>, hir::Expression, Span),
+    Cast(Box<Member>, TypeName, Span),
+    Splat(Box<Member>, Span),
+    ColumnNumber(BigInt, Span),
+    Computed(hir::Expression, Span),
+    Filter(hir::Expression, Span),
+    Spread(hir::Expression, Span),
+    Range(hir::Expression, Span),
+    Entries(Span),
+    /// `*`, every row of a table
+    Wildcard(Span),
+    /// A bare word with a `*` or a `?` in it, like `user_*`, which matches column names
+    Glob(Span),
+    Alternatives(Vec<hir::Expression>, Span),
+}
+
+/// The kind of a `Member`, without its spans or payload
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+pub enum MemberKind {
+    String,
+    Int,
+    Bare,
+    Conditional,
+    Cast,
+    Splat,
+    ColumnNumber,
+    Computed,
+    Filter,
+    Spread,
+    Range,
+    Entries,
+    Wildcard,
+    Glob,
+    Alternatives,
+}
+
+impl MemberKind {
+    /// A one-letter stand-in for the kind, used by path signatures
+    pub fn placeholder(&self) -> &'static str {
+        match self {
+            MemberKind::String => "S",
+            MemberKind::Int => "I",
+            MemberKind::Bare => "B",
+            MemberKind::Conditional => "C",
+            MemberKind::Cast => "T",
+            MemberKind::Splat => "F",
+            MemberKind::ColumnNumber => "N",
+            MemberKind::Computed => "X",
+            MemberKind::Filter => "W",
+            MemberKind::Spread => "P",
+            MemberKind::Range => "R",
+            MemberKind::Entries => "E",
+            MemberKind::Wildcard => "G",
+            MemberKind::Glob => "M",
+            MemberKind::Alternatives => "A",
+        }
+    }
 }
 
 impl ShellTypeName for Member {
@@ -554,16 +1101,110 @@ impl ShellTypeName for Member {
             Member::String(_, _) => "string",
             Member::Int(_, _) => "integer",
             Member::Bare(_) => "word",
+            Member::Conditional(..) => "conditional member",
+            Member::Cast(..) => "cast member",
+            Member::Splat(..) => "splat member",
+            Member::ColumnNumber(..) => "column number",
+            Member::Computed(..) => "computed member",
+            Member::Filter(..) => "filter member",
+            Member::Spread(..) => "spread member",
+            Member::Range(..) => "range member",
+            Member::Alternatives(..) => "alternatives member",
+            Member::Entries(..) => "entries member",
+            Member::Wildcard(..) => "wildcard member",
+            Member::Glob(..) => "glob member",
         }
     }
 }
 
 impl Member {
+    pub fn kind(&self) -> MemberKind {
+        match self {
+            Member::String(..) => MemberKind::String,
+            Member::Int(..) => MemberKind::Int,
+            Member::Bare(..) => MemberKind::Bare,
+            Member::Conditional(..) => MemberKind::Conditional,
+            Member::Cast(..) => MemberKind::Cast,
+            Member::Splat(..) => MemberKind::Splat,
+            Member::ColumnNumber(..) => MemberKind::ColumnNumber,
+            Member::Computed(..) => MemberKind::Computed,
+            Member::Filter(..) => MemberKind::Filter,
+            Member::Spread(..) => MemberKind::Spread,
+            Member::Range(..) => MemberKind::Range,
+            Member::Entries(..) => MemberKind::Entries,
+            Member::Wildcard(..) => MemberKind::Wildcard,
+            Member::Glob(..) => MemberKind::Glob,
+            Member::Alternatives(..) => MemberKind::Alternatives,
+        }
+    }
+
+    /// Lower the member into a `PathMember`
     pub fn to_path_member(&self, source: &Text) -> PathMember {
         match self {
-            Member::String(outer, inner) => PathMember::string(inner.slice(source), *outer),
-            Member::Int(int, span) => PathMember::int(int.clone(), *span),
-            Member::Bare(span) => PathMember::string(span.slice(source), *span),
+            Member::String(outer, inner) => {
+                PathMember::string(unescaped_or_raw(*outer, *inner, source), *outer)
+            }
+            Member::Int(int, span) => match int.to_i64() {
+                Some(int) => PathMember::small_int(int, *span),
+                None => PathMember::int(int.clone(), *span),
+            },
+            Member::ColumnNumber(number, span) => PathMember::column_number(number.clone(), *span),
+            Member::Bare(span) => PathMember::string(bare_member_name(*span, source), *span),
+            Member::Entries(span) => PathMember::entries(*span),
+            Member::Wildcard(span) => PathMember::wildcard(*span),
+            Member::Glob(span) => PathMember::glob(span.slice(source), *span),
+            Member::Range(expr, span) => range_path_member(expr, *span),
+            Member::Computed(expr, span) => PathMember::computed(expr.clone(), *span),
+            Member::Filter(predicate, span) => PathMember::filter(predicate.clone(), *span),
+            Member::Spread(expr, span) => PathMember::spread(expr.clone(), *span),
+            Member::Alternatives(exprs, span) => PathMember::alternatives(exprs.clone(), *span),
+            Member::Splat(member, span) => PathMember::splat(member.to_path_member(source), *span),
+            Member::Conditional(member, predicate, span) => {
+                PathMember::conditional(member.to_path_member(source), predicate.clone(), *span)
+            }
+            Member::Cast(member, type_name, span) => {
+                PathMember::cast(member.to_path_member(source), *type_name, *span)
+            }
+        }
+    }
+
+    /// Like `to_path_member`, but names come from the context's interner, if it has one
+    pub fn to_interned_path_member(&self, context: &ExpandContext) -> PathMember {
+        let interner = match context.interner {
+            None => return self.to_path_member(context.source),
+            Some(interner) => interner,
+        };
+
+        match self {
+            Member::String(outer, inner) => PathMember::string(
+                interner.intern(&unescaped_or_raw(*outer, *inner, context.source)),
+                *outer,
+            ),
+            Member::Bare(span) => PathMember::string(
+                interner.intern(&bare_member_name(*span, context.source)),
+                *span,
+            ),
+            Member::Splat(member, span) => {
+                PathMember::splat(member.to_interned_path_member(context), *span)
+            }
+            Member::Conditional(member, predicate, span) => PathMember::conditional(
+                member.to_interned_path_member(context),
+                predicate.clone(),
+                *span,
+            ),
+            Member::Cast(member, type_name, span) => {
+                PathMember::cast(member.to_interned_path_member(context), *type_name, *span)
+            }
+            Member::Int(..)
+            | Member::ColumnNumber(..)
+            | Member::Computed(..)
+            | Member::Filter(..)
+            | Member::Spread(..)
+            | Member::Range(..)
+            | Member::Entries(..)
+            | Member::Wildcard(..)
+            | Member::Glob(..)
+            | Member::Alternatives(..) => self.to_path_member(context.source),
         }
     }
 }
@@ -574,26 +1215,189 @@ impl FormatDebug for Member {
             Member::String(outer, _) => write!(f, "{}", outer.slice(source)),
             Member::Int(_, int) => write!(f, "{}", int.slice(source)),
             Member::Bare(bare) => write!(f, "{}", bare.slice(source)),
+            Member::Conditional(_, _, span) => write!(f, "{}", span.slice(source)),
+            Member::Cast(_, _, span) => write!(f, "{}", span.slice(source)),
+            Member::Splat(_, span) => write!(f, "{}", span.slice(source)),
+            Member::ColumnNumber(_, span) => write!(f, "{}", span.slice(source)),
+            Member::Computed(_, span) => write!(f, "{}", span.slice(source)),
+            Member::Filter(_, span) => write!(f, "{}", span.slice(source)),
+            Member::Spread(_, span) => write!(f, "{}", span.slice(source)),
+            Member::Range(_, span) => write!(f, "{}", span.slice(source)),
+            Member::Entries(span) => write!(f, "{}", span.slice(source)),
+            Member::Wildcard(span) => write!(f, "{}", span.slice(source)),
+            Member::Glob(span) => write!(f, "{}", span.slice(source)),
+            Member::Alternatives(_, span) => write!(f, "{}", span.slice(source)),
+        }
+    }
+}
+
+/// Renders the member without its source, so members that are only spans, like bare
+/// words and strings, are rendered as their spans. Use `Member::describe` to render
+/// them from the source.
+impl fmt::Display for Member {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Member::String(outer, _) => {
+                write!(f, "String{{ {}..{} }}", outer.start(), outer.end())
+            }
+            Member::Int(int, _) => write!(f, "{}", int),
+            Member::Bare(span) => write!(f, "Bare{{ {}..{} }}", span.start(), span.end()),
+            Member::Conditional(member, predicate, _) => write!(f, "{} if {}", member, predicate),
+            Member::Cast(member, type_name, _) => write!(f, "{} as {}", member, type_name.as_str()),
+            Member::Splat(member, _) => write!(f, "{}...", member),
+            Member::ColumnNumber(number, _) => write!(f, "#{}", number),
+            Member::Computed(expr, _) => write!(f, "({})", expr),
+            Member::Filter(expr, _) => write!(f, "[{}]", expr),
+            Member::Spread(expr, _) => write!(f, "(...{})", expr),
+            Member::Range(expr, _) => write!(f, "{}", expr),
+            Member::Entries(_) => write!(f, "~"),
+            Member::Wildcard(_) => write!(f, "*"),
+            Member::Glob(span) => write!(f, "Glob{{ {}..{} }}", span.start(), span.end()),
+            Member::Alternatives(exprs, _) => write!(
+                f,
+                "({})",
+                exprs.iter().map(|expr| expr.to_string()).join(" | ")
+            ),
         }
     }
 }
 
+/// The serialized form of a `Member`. A member's spans only mean something against the
+/// source it was parsed from, so string and bare members can carry their resolved text
+/// as well, to make a deserialized path readable without the source. A member
+/// serialized without the text, like a plain `Member`, still deserializes.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct MemberRepr {
+    #[serde(flatten)]
+    member: Member,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
+}
+
+impl MemberRepr {
+    pub fn member(&self) -> &Member {
+        &self.member
+    }
+
+    /// The member's resolved text, if it was serialized with it
+    pub fn text(&self) -> Option<&str> {
+        self.text.as_ref().map(|text| &text[..])
+    }
+}
+
+impl From<Member> for MemberRepr {
+    fn from(member: Member) -> MemberRepr {
+        MemberRepr { member, text: None }
+    }
+}
+
+impl From<MemberRepr> for Member {
+    fn from(repr: MemberRepr) -> Member {
+        repr.member
+    }
+}
+
 impl HasSpan for Member {
     fn span(&self) -> Span {
         match self {
             Member::String(outer, ..) => *outer,
             Member::Int(_, int) => *int,
             Member::Bare(name) => *name,
+            Member::Conditional(_, _, span) => *span,
+            Member::Cast(_, _, span) => *span,
+            Member::Splat(_, span) => *span,
+            Member::ColumnNumber(_, span) => *span,
+            Member::Computed(_, span) => *span,
+            Member::Filter(_, span) => *span,
+            Member::Spread(_, span) => *span,
+            Member::Range(_, span) => *span,
+            Member::Entries(span) => *span,
+            Member::Wildcard(span) => *span,
+            Member::Glob(span) => *span,
+            Member::Alternatives(_, span) => *span,
         }
     }
 }
 
 impl Member {
+    /// The member as a user would refer to it in a message: a string member by its
+    /// name, without quotes, an integer member by its value, and anything else as it
+    /// was written
+    pub fn describe(&self, source: &Text) -> String {
+        match self {
+            Member::String(outer, inner) => unescaped_or_raw(*outer, *inner, source),
+            Member::Int(int, _) => int.to_string(),
+            other => other.span().slice(source).to_string(),
+        }
+    }
+
+    /// The serialized form of the member, with the resolved text of a string or bare
+    /// member
+    pub fn to_repr(&self, source: &Text) -> MemberRepr {
+        let text = match self {
+            Member::String(outer, inner) => Some(unescaped_or_raw(*outer, *inner, source)),
+            Member::Bare(span) => Some(bare_member_name(*span, source)),
+            _ => None,
+        };
+
+        MemberRepr {
+            member: self.clone(),
+            text,
+        }
+    }
+
     pub fn to_expr(&self) -> hir::Expression {
         match self {
             Member::String(outer, inner) => hir::Expression::string(*inner, *outer),
-            Member::Int(number, span) => hir::Expression::number(number.clone(), *span),
-            Member::Bare(span) => hir::Expression::string(*span, *span),
+            Member::Int(number, span) | Member::ColumnNumber(number, span) => {
+                hir::Expression::number(number.clone(), *span)
+            }
+            Member::Bare(span)
+            | Member::Entries(span)
+            | Member::Wildcard(span)
+            | Member::Glob(span) => hir::Expression::string(*span, *span),
+            Member::Conditional(member, _, _)
+            | Member::Cast(member, _, _)
+            | Member::Splat(member, _) => member.to_expr(),
+            Member::Computed(expr, _)
+            | Member::Filter(expr, _)
+            | Member::Spread(expr, _)
+            | Member::Range(expr, _) => expr.clone(),
+            Member::Alternatives(exprs, span) => hir::Expression::list(exprs.clone(), *span),
+        }
+    }
+
+    /// The integer of an integer member, written without padding or a `+` sign, like
+    /// `7` for both `$it.007` and `$it.7`. Other members don't have one.
+    pub fn canonical_int_string(&self) -> Option<String> {
+        match self {
+            Member::Int(int, _) => Some(int.to_string()),
+            _ => None,
+        }
+    }
+
+    /// The expressions inside the member, like the predicate of a conditional member or
+    /// the expression of a computed member
+    pub(crate) fn expressions(&self) -> Vec<&hir::Expression> {
+        match self {
+            Member::String(..)
+            | Member::Int(..)
+            | Member::Bare(..)
+            | Member::ColumnNumber(..)
+            | Member::Entries(..)
+            | Member::Wildcard(..)
+            | Member::Glob(..) => vec![],
+            Member::Conditional(member, predicate, _) => {
+                let mut expressions = member.expressions();
+                expressions.push(predicate);
+                expressions
+            }
+            Member::Cast(member, _, _) | Member::Splat(member, _) => member.expressions(),
+            Member::Computed(expr, _)
+            | Member::Filter(expr, _)
+            | Member::Spread(expr, _)
+            | Member::Range(expr, _) => vec![expr],
+            Member::Alternatives(exprs, _) => exprs.iter().collect(),
         }
     }
 
@@ -602,15 +1406,181 @@ impl Member {
             Member::String(outer, _inner) => *outer,
             Member::Int(_, span) => *span,
             Member::Bare(span) => *span,
+            Member::Conditional(_, _, span) => *span,
+            Member::Cast(_, _, span) => *span,
+            Member::Splat(_, span) => *span,
+            Member::ColumnNumber(_, span) => *span,
+            Member::Computed(_, span) => *span,
+            Member::Filter(_, span) => *span,
+            Member::Spread(_, span) => *span,
+            Member::Range(_, span) => *span,
+            Member::Entries(span) => *span,
+            Member::Wildcard(span) => *span,
+            Member::Glob(span) => *span,
+            Member::Alternatives(_, span) => *span,
+        }
+    }
+
+    /// The value of an integer member, or `None` if the member is not an integer
+    pub fn as_int(&self) -> Option<&BigInt> {
+        match self {
+            Member::Int(int, _) => Some(int),
+            _ => None,
+        }
+    }
+
+    /// The name of a string or bare member as it was written, without the quotes of a
+    /// string, or `None` for any other member. Escapes aren't decoded, so use
+    /// `to_path_member` for the name a member looks up.
+    pub fn as_string<'source>(&self, source: &'source Text) -> Option<&'source str> {
+        match self {
+            Member::String(_, inner) => Some(inner.slice(source)),
+            Member::Bare(span) => Some(span.slice(source)),
+            _ => None,
+        }
+    }
+
+    /// The value of an integer member as a `usize`, or `None` if the member is not
+    /// an integer or doesn't fit
+    pub fn as_usize(&self) -> Option<usize> {
+        match self {
+            Member::Int(int, _) => int.to_usize(),
+            _ => None,
+        }
+    }
+
+    /// The value of an integer member as an `i64`, or `None` if the member is not
+    /// an integer or doesn't fit
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Member::Int(int, _) => int.to_i64(),
+            _ => None,
+        }
+    }
+
+    /// Whether two members name the same column, like `name` and `"name"`, or the same
+    /// row, like `7` and `007`. Members whose meaning depends on an expression only
+    /// match if they're structurally equal.
+    pub fn matches(&self, other: &Member, source: &Text) -> bool {
+        fn is_literal(member: &Member) -> bool {
+            match member {
+                Member::String(..)
+                | Member::Bare(..)
+                | Member::Int(..)
+                | Member::ColumnNumber(..) => true,
+                _ => false,
+            }
+        }
+
+        if is_literal(self) && is_literal(other) {
+            self.to_path_member(source).item == other.to_path_member(source).item
+        } else {
+            self == other
+        }
+    }
+}
+
+/// Decode the escapes in the body of a quoted member. `\u{...}` is a unicode escape
+/// with one to six hex digits and `\\` is a backslash. Any other backslash is kept as
+/// it is.
+fn unescape_member(text: &str, span: Span) -> Result<String, ParseError> {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+
+        match chars.peek() {
+            Some((_, '\\')) => {
+                chars.next();
+                out.push('\\');
+            }
+            Some((_, 'u')) => {
+                chars.next();
+
+                // the end of the escape, starting just past the `\u`
+                let mut end = start + 2;
+                let mut digits = String::new();
+                let mut closed = false;
+
+                if let Some((_, '{')) = chars.peek() {
+                    chars.next();
+                    end += 1;
+
+                    while let Some((i, c)) = chars.next() {
+                        end = i + c.len_utf8();
+
+                        if c == '}' {
+                            closed = true;
+                            break;
+                        }
+
+                        digits.push(c);
+                    }
+                }
+
+                let decoded = if closed
+                    && (1..=6).contains(&digits.len())
+                    && digits.chars().all(|c| c.is_ascii_hexdigit())
+                {
+                    u32::from_str_radix(&digits, 16)
+                        .ok()
+                        .and_then(std::char::from_u32)
+                } else {
+                    None
+                };
+
+                match decoded {
+                    Some(c) => out.push(c),
+                    None => {
+                        let escape = Span::new(span.start() + start, span.start() + end);
+
+                        return Err(ParseError::mismatch(
+                            "unicode escape",
+                            text[start..end].spanned(escape),
+                        ));
+                    }
+                }
+            }
+            _ => out.push('\\'),
         }
     }
+
+    Ok(out)
+}
+
+/// The name of a quoted member. A member wrapped in backticks is taken literally, so
+/// `` `a\b` `` names the column `a\b`; other quotes decode escapes with `unescape_member`.
+fn quoted_member_name(outer: Span, inner: Span, source: &Text) -> Result<String, ParseError> {
+    let text = inner.slice(source);
+
+    if outer.slice(source).starts_with('`') {
+        Ok(text.to_string())
+    } else {
+        unescape_member(text, inner)
+    }
+}
+
+/// Members are validated when they're parsed, so a member that fails to unescape here
+/// was built by hand; use its text as it is
+fn unescaped_or_raw(outer: Span, inner: Span, source: &Text) -> String {
+    quoted_member_name(outer, inner, source).unwrap_or_else(|_| inner.slice(source).to_string())
+}
+
+/// The name of a bare member. An escaped dot, like the one in `a\.b`, is part of the name,
+/// without its backslash.
+fn bare_member_name(span: Span, source: &Text) -> String {
+    span.slice(source).replace("\\.", ".")
 }
 
 enum ColumnPathState {
     Initial,
     LeadingDot(Span),
-    Dot(Span, Vec<Member>, Span),
-    Member(Span, Vec<Member>),
+    Dot(Span, ColumnPathMembers, Span),
+    Member(Span, ColumnPathMembers),
     Error(ParseError),
 }
 
@@ -618,11 +1588,8 @@ impl ColumnPathState {
     pub fn dot(self, dot: Span) -> ColumnPathState {
         match self {
             ColumnPathState::Initial => ColumnPathState::LeadingDot(dot),
-            ColumnPathState::LeadingDot(_) => {
-                ColumnPathState::Error(ParseError::mismatch("column", "dot".spanned(dot)))
-            }
-            ColumnPathState::Dot(..) => {
-                ColumnPathState::Error(ParseError::mismatch("column", "dot".spanned(dot)))
+            ColumnPathState::LeadingDot(previous) | ColumnPathState::Dot(_, _, previous) => {
+                ColumnPathState::Error(ParseError::repeated_dot(previous, dot))
             }
             ColumnPathState::Member(tag, members) => ColumnPathState::Dot(tag, members, dot),
             ColumnPathState::Error(err) => ColumnPathState::Error(err),
@@ -631,9 +1598,9 @@ impl ColumnPathState {
 
     pub fn member(self, member: Member) -> ColumnPathState {
         match self {
-            ColumnPathState::Initial => ColumnPathState::Member(member.span(), vec![member]),
+            ColumnPathState::Initial => ColumnPathState::Member(member.span(), smallvec![member]),
             ColumnPathState::LeadingDot(tag) => {
-                ColumnPathState::Member(tag.until(member.span()), vec![member])
+                ColumnPathState::Member(tag.until(member.span()), smallvec![member])
             }
 
             ColumnPathState::Dot(tag, mut tags, _) => {
@@ -650,7 +1617,24 @@ impl ColumnPathState {
         }
     }
 
-    pub fn into_path(self, next: Peeked) -> Result<Tagged<Vec<Member>>, ParseError> {
+    /// An index right after another member, like the `[0..3]` in `name[0..3]`
+    pub fn index(self, index: Spanned<Member>) -> ColumnPathState {
+        match self {
+            ColumnPathState::Member(tag, mut members) => {
+                ColumnPathState::Member(tag.until(index.span), {
+                    members.push(index.item);
+                    members
+                })
+            }
+            ColumnPathState::Error(err) => ColumnPathState::Error(err),
+            _ => ColumnPathState::Error(ParseError::mismatch(
+                "member",
+                index.item.type_name().spanned(index.span),
+            )),
+        }
+    }
+
+    pub fn into_path(self, next: Peeked) -> Result<Tagged<ColumnPathMembers>, ParseError> {
         match self {
             ColumnPathState::Initial => Err(next.type_error("column path")),
             ColumnPathState::LeadingDot(dot) => {
@@ -668,15 +1652,47 @@ impl ColumnPathState {
 pub fn expand_column_path<'a, 'b>(
     token_nodes: &'b mut TokensIterator<'a>,
     context: &ExpandContext,
-) -> Result<Tagged<Vec<Member>>, ParseError> {
+) -> Result<Tagged<ColumnPathMembers>, ParseError> {
+    let (_, path) = expand_column_path_with_leading_dot(token_nodes, context)?;
+
+    Ok(path)
+}
+
+/// Like `expand_column_path`, but also returns the leading dot of a relative column path,
+/// like the one in `.name`, if there is one
+fn expand_column_path_with_leading_dot<'a, 'b>(
+    token_nodes: &'b mut TokensIterator<'a>,
+    context: &ExpandContext,
+) -> Result<(Option<Span>, Tagged<ColumnPathMembers>), ParseError> {
     let mut state = ColumnPathState::Initial;
+    let mut leading_dot = None;
+
+    if context.relative_column_paths {
+        if let Ok(dot) = expand_syntax(&DotShape, token_nodes, context) {
+            leading_dot = Some(dot);
+            state = state.dot(dot);
+        }
+    }
 
     loop {
         let member = expand_syntax(&MemberShape, token_nodes, context);
 
         match member {
             Err(_) => break,
-            Ok(member) => state = state.member(member),
+            Ok(member) => {
+                if let Some(splat) = expand_ellipsis(token_nodes, context) {
+                    // a splat flattens whatever the path has addressed, so it ends the path
+                    let span = member.span().until(splat);
+                    state = state.member(Member::Splat(Box::new(member), span));
+                    break;
+                }
+
+                state = state.member(member);
+
+                if let Some(index) = expand_member_index(token_nodes, context)? {
+                    state = state.index(index);
+                }
+            }
         }
 
         let dot = expand_syntax(&DotShape, token_nodes, context);
@@ -687,55 +1703,307 @@ pub fn expand_column_path<'a, 'b>(
         }
     }
 
-    state.into_path(token_nodes.peek_non_ws())
+    let path = state.into_path(token_nodes.peek_non_ws())?;
+
+    Ok((leading_dot, path))
 }
 
-#[derive(Debug, Copy, Clone)]
-pub struct ColumnPathShape;
+/// Expand a column path that starts with a dot, like `.name`, into a path whose head is
+/// the implicit `$it`. The context has to allow relative column paths; otherwise, and
+/// for a path without a leading dot, this is an error.
+pub fn expand_relative_column_path<'a, 'b>(
+    token_nodes: &'b mut TokensIterator<'a>,
+    context: &ExpandContext,
+) -> Result<hir::Expression, ParseError> {
+    let (leading_dot, path) = expand_column_path_with_leading_dot(token_nodes, context)?;
+    let span = path.tag.span;
+
+    let dot = match leading_dot {
+        Some(dot) => dot,
+        None => {
+            return Err(ParseError::mismatch(
+                "relative column path",
+                "column path".spanned(span),
+            ))
+        }
+    };
 
-#[cfg(not(coloring_in_tokens))]
-impl FallibleColorSyntax for ColumnPathShape {
-    type Info = ();
-    type Input = ();
+    if path.item.is_empty() {
+        return Err(ParseError::mismatch("column", "dot".spanned(dot)));
+    }
 
-    fn color_syntax<'a, 'b>(
-        &self,
-        _input: &(),
-        token_nodes: &'b mut TokensIterator<'a>,
-        context: &ExpandContext,
-        shapes: &mut Vec<Spanned<FlatShape>>,
-    ) -> Result<(), ShellError> {
-        // If there's not even one member shape, fail
-        color_fallible_syntax(&MemberShape, token_nodes, context, shapes)?;
+    let tail = path
+        .item
+        .iter()
+        .map(|member| member.to_interned_path_member(context))
+        .collect();
+
+    Ok(hir::Expression::path(
+        hir::Expression::it_variable(dot, dot),
+        tail,
+        span,
+    ))
+}
 
-        loop {
-            let checkpoint = token_nodes.checkpoint();
+/// Expand a column path from tokens that were produced elsewhere, like by a host that
+/// tokenizes its input separately. The spans in the tokens must point into the source
+/// that `context` was created with, since bare members are read by slicing it, and the
+/// spans in the result are relative to that same source. Every token must be part of
+/// the path, except for trailing whitespace.
+pub fn parse_column_path_from_tokens(
+    tokens: &[TokenNode],
+    context: &ExpandContext,
+) -> Result<Tagged<ColumnPathMembers>, ParseError> {
+    let span = match (tokens.first(), tokens.last()) {
+        (Some(first), Some(last)) => first.span().until(last.span()),
+        _ => Span::unknown(),
+    };
+
+    let mut token_nodes = TokensIterator::all(tokens, span);
+    let path = expand_column_path(&mut token_nodes, context)?;
+
+    if !token_nodes.at_end_possible_ws() {
+        let peeked = token_nodes.peek_non_ws();
+        return Err(peeked.type_error("end of column path"));
+    }
 
-            match color_fallible_syntax_with(
-                &ColorableDotShape,
-                &FlatShape::Dot,
-                checkpoint.iterator,
-                context,
-                shapes,
-            ) {
-                Err(_) => {
-                    // we already saw at least one member shape, so return successfully
-                    return Ok(());
-                }
+    Ok(path)
+}
 
-                Ok(_) => {
-                    match color_fallible_syntax(&MemberShape, checkpoint.iterator, context, shapes)
-                    {
-                        Err(_) => {
-                            // we saw a dot but not a member (but we saw at least one member),
-                            // so don't commit the dot but return successfully
-                            return Ok(());
-                        }
+/// Expand an optional `...`, like the splat in `data...` or the spread in
+/// `(...$defaults)`, returning the span of the three dots. The dots must be adjacent;
+/// anything else, like the two dots in `data..name`, is left for the caller to deal
+/// with.
+fn expand_ellipsis(token_nodes: &mut TokensIterator<'_>, context: &ExpandContext) -> Option<Span> {
+    let checkpoint = token_nodes.checkpoint(context);
 
-                        Ok(_) => {
-                            // we saw a dot and a member, so commit it and continue on
-                            checkpoint.commit();
-                        }
+    let first = expand_syntax(&DotShape, checkpoint.iterator, context).ok()?;
+    let mut end = first;
+
+    for _ in 0..2 {
+        let dot = expand_syntax(&DotShape, checkpoint.iterator, context).ok()?;
+
+        if dot.start() != end.end() {
+            return None;
+        }
+
+        end = dot;
+    }
+
+    checkpoint.commit();
+    Some(first.until(end))
+}
+
+/// Expand an optional bracketed index right after a member or an expression, like the
+/// `[0]` in `a[0]`, the `["a b"]` in `$record["a b"]` or the range `[0..3]` in
+/// `name[0..3]`. The result is spanned by the brackets. If the next token isn't a square
+/// group, nothing is consumed and this returns `Ok(None)`. Once a group is seen,
+/// anything in it that isn't an integer, a string or a range is an error.
+fn expand_member_index(
+    token_nodes: &mut TokensIterator<'_>,
+    context: &ExpandContext,
+) -> Result<Option<Spanned<Member>>, ParseError> {
+    let mut peeked = token_nodes.peek_any();
+
+    let (children, span) = match peeked.node {
+        Some(TokenNode::Delimited(Spanned {
+            item:
+                DelimitedNode {
+                    delimiter: Delimiter::Square,
+                    children,
+                    ..
+                },
+            span,
+        })) => (children, *span),
+        _ => return Ok(None),
+    };
+
+    let index = match expand_index_key(children, span, context)? {
+        Some(key) => key,
+        None => Member::Range(
+            expand_delimited_range(children, span, context)?.into_expr(span),
+            span,
+        ),
+    };
+
+    peeked.commit();
+    Ok(Some(index.spanned(span)))
+}
+
+/// Expand the inside of a bracketed index if it's a lone integer or string, returning
+/// `Ok(None)` if it's something else
+fn expand_index_key(
+    children: &[TokenNode],
+    span: Span,
+    context: &ExpandContext,
+) -> Result<Option<Member>, ParseError> {
+    let mut tokens = TokensIterator::new(children, span, false);
+
+    match expand_syntax(&IntMemberShape, &mut tokens, context) {
+        Ok(Member::Int(int, _)) if tokens.at_end_possible_ws() => {
+            return Ok(Some(Member::Int(int, span)))
+        }
+        _ => {}
+    }
+
+    let mut tokens = TokensIterator::new(children, span, false);
+
+    let node = match StringShape.test(&mut tokens, context) {
+        None => return Ok(None),
+        Some(peeked) => peeked.not_eof("index")?.commit(),
+    };
+
+    let (outer, inner) = node.as_string().unwrap();
+
+    if !tokens.at_end_possible_ws() {
+        return Ok(None);
+    }
+
+    quoted_member_name(outer, inner, context.source)?;
+    Ok(Some(Member::String(outer, inner)))
+}
+
+/// Lower an index from `expand_member_index` into a path member that spans its brackets
+fn index_path_member(index: &Spanned<Member>, context: &ExpandContext) -> PathMember {
+    let mut member = index.item.to_interned_path_member(context);
+    member.span = index.span;
+    member
+}
+
+/// Expand an optional ` as <type>` suffix after a member. If the next tokens aren't
+/// an `as` keyword, nothing is consumed and this returns `Ok(None)`. Once `as` is seen,
+/// a missing or unknown type name is an error.
+fn expand_member_cast(
+    token_nodes: &mut TokensIterator<'_>,
+    context: &ExpandContext,
+) -> Result<Option<Spanned<TypeName>>, ParseError> {
+    let suffix = expand_type_suffix(token_nodes, context, "as")?;
+
+    Ok(suffix.map(|(_, type_name)| type_name))
+}
+
+/// Expand an optional ` <keyword> <type>` suffix, returning the span of the keyword and
+/// the type name. If the next tokens aren't the keyword, nothing is consumed and this
+/// returns `Ok(None)`. Once the keyword is seen, a missing or unknown type name is an
+/// error.
+fn expand_type_suffix(
+    token_nodes: &mut TokensIterator<'_>,
+    context: &ExpandContext,
+    keyword: &str,
+) -> Result<Option<(Span, Spanned<TypeName>)>, ParseError> {
+    let checkpoint = token_nodes.checkpoint(context);
+
+    if expand_syntax(&WhitespaceShape, checkpoint.iterator, context).is_err() {
+        return Ok(None);
+    }
+
+    let keyword = match expand_syntax(&BareShape, checkpoint.iterator, context) {
+        Ok(found) if found.item == keyword => found.span,
+        _ => return Ok(None),
+    };
+
+    expand_syntax(&WhitespaceShape, checkpoint.iterator, context)?;
+    let type_name = expand_syntax(&TypeNameShape, checkpoint.iterator, context)?;
+    checkpoint.commit();
+
+    Ok(Some((keyword, type_name)))
+}
+
+/// Expand an optional ` if <predicate>` suffix after a member. If the next tokens
+/// aren't an `if` keyword, nothing is consumed and this returns `Ok(None)`. Once
+/// `if` is seen, a missing predicate is an error.
+fn expand_member_condition(
+    token_nodes: &mut TokensIterator<'_>,
+    context: &ExpandContext,
+) -> Result<Option<hir::Expression>, ParseError> {
+    let checkpoint = token_nodes.checkpoint(context);
+
+    if expand_syntax(&WhitespaceShape, checkpoint.iterator, context).is_err() {
+        return Ok(None);
+    }
+
+    match expand_syntax(&BareShape, checkpoint.iterator, context) {
+        Ok(keyword) if keyword.item == "if" => {}
+        _ => return Ok(None),
+    }
+
+    let predicate = expand_expr(&spaced(NotExpressionShape), checkpoint.iterator, context)?;
+    checkpoint.commit();
+
+    Ok(Some(predicate))
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct ColumnPathShape;
+
+#[cfg(not(coloring_in_tokens))]
+impl FallibleColorSyntax for ColumnPathShape {
+    type Info = ();
+    type Input = ();
+
+    fn color_syntax<'a, 'b>(
+        &self,
+        _input: &(),
+        token_nodes: &'b mut TokensIterator<'a>,
+        context: &ExpandContext,
+        shapes: &mut Vec<Spanned<FlatShape>>,
+    ) -> Result<(), ShellError> {
+        color_column_path(token_nodes, context, shapes)
+    }
+}
+
+#[cfg(coloring_in_tokens)]
+impl FallibleColorSyntax for ColumnPathShape {
+    type Info = ();
+    type Input = ();
+
+    fn name(&self) -> &'static str {
+        "ColumnPathShape"
+    }
+
+    fn color_syntax<'a, 'b>(
+        &self,
+        _input: &(),
+        token_nodes: &'b mut TokensIterator<'a>,
+        context: &ExpandContext,
+    ) -> Result<(), ShellError> {
+        color_column_path(token_nodes, context, &mut TokenShapes)
+    }
+}
+
+fn color_column_path(
+    token_nodes: &mut TokensIterator<'_>,
+    context: &ExpandContext,
+    shapes: &mut impl ColorCollector,
+) -> Result<(), ShellError> {
+    // If there's not even one member shape, fail
+    shapes.color(&MemberShape, token_nodes, context)?;
+
+    loop {
+        let checkpoint = token_nodes.checkpoint(context);
+
+        match shapes.color_with(
+            &ColorableDotShape,
+            &FlatShape::Dot,
+            checkpoint.iterator,
+            context,
+        ) {
+            Err(_) => {
+                // we already saw at least one member shape, so return successfully
+                return Ok(());
+            }
+
+            Ok(_) => {
+                match shapes.color(&MemberShape, checkpoint.iterator, context) {
+                    Err(_) => {
+                        // we saw a dot but not a member (but we saw at least one member),
+                        // so don't commit the dot but return successfully
+                        return Ok(());
+                    }
+
+                    Ok(_) => {
+                        // we saw a dot and a member, so commit it and continue on
+                        checkpoint.commit();
                     }
                 }
             }
@@ -743,152 +2011,804 @@ impl FallibleColorSyntax for ColumnPathShape {
     }
 }
 
+impl FormatDebug for Tagged<ColumnPathMembers> {
+    fn fmt_debug(&self, f: &mut DebugFormatter, source: &str) -> fmt::Result {
+        write!(f, "[ ")?;
+        write!(
+            f,
+            "{}",
+            self.item
+                .iter()
+                .map(|member| member.debug(source))
+                .join(" ")
+        )?;
+        write!(f, " ]")
+    }
+}
+
+impl Tagged<ColumnPathMembers> {
+    /// Render the path as it appears in the source. If it has more than `max_members`
+    /// members, only the first and last few are rendered, with `…` in between.
+    pub fn render_truncated(&self, source: &Text, max_members: usize) -> String {
+        let members: Vec<&str> = self
+            .item
+            .iter()
+            .map(|member| member.span().slice(source))
+            .collect();
+
+        if members.len() <= max_members {
+            return members.join(".");
+        }
+
+        let head = (max_members + 1) / 2;
+        let tail = max_members / 2;
+
+        let mut rendered: Vec<&str> = members[..head].to_vec();
+        rendered.push("…");
+        rendered.extend(&members[members.len() - tail..]);
+
+        rendered.join(".")
+    }
+
+    /// Call `f` with each member of the path, along with the members that come
+    /// before it
+    pub fn walk(&self, mut f: impl FnMut(&[Member], &Member)) {
+        for (i, member) in self.item.iter().enumerate() {
+            f(&self.item[..i], member);
+        }
+    }
+
+    /// The spans of bare members that look like numbers, like `0x10` or `10`, which a
+    /// reader could take as either a key or an index
+    pub fn has_ambiguous_members(&self, source: &Text) -> Vec<Span> {
+        self.item
+            .iter()
+            .filter_map(|member| match member {
+                Member::Bare(span) if looks_numeric(span.slice(source)) => Some(*span),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The distinct kinds of member that appear in the path
+    pub fn kinds_used(&self) -> HashSet<MemberKind> {
+        self.item.iter().map(Member::kind).collect()
+    }
+
+    /// The shape of the path, with each member replaced by its kind's placeholder, so
+    /// `a.0."x"` and `b.1."y"` both have the signature `B.I.S`
+    pub fn signature(&self) -> String {
+        let placeholders: Vec<&str> = self
+            .item
+            .iter()
+            .map(|member| member.kind().placeholder())
+            .collect();
+
+        placeholders.join(".")
+    }
+
+    /// Complete the last member of a partially typed path, like the `na` in `$record.na`,
+    /// from the context's known columns. If the path ends with a dot, like `$record.`, or
+    /// has no members yet, every known column is a completion.
+    pub fn member_completions(&self, context: &ExpandContext) -> Vec<String> {
+        let known = context.known_columns.unwrap_or(&[]);
+        let end = self.tag.span.end();
+
+        let trailing_dot = self.tag.span.slice(context.source).ends_with('.')
+            || context
+                .source
+                .get(end..)
+                .map_or(false, |rest| rest.starts_with('.'));
+
+        let partial = match self.item.last() {
+            _ if trailing_dot => "",
+            None => "",
+            Some(Member::Bare(span)) => span.slice(context.source),
+            Some(_) => return vec![],
+        };
+
+        known
+            .iter()
+            .filter(|column| column.starts_with(partial))
+            .cloned()
+            .collect()
+    }
+
+    /// Render the path as readable source text, like `a.b."c d".0`. Unlike
+    /// `render_truncated`, string members are rendered by name, so they're only quoted
+    /// when they need to be, and integer members are rendered without padding.
+    pub fn display_path(&self, source: &Text) -> String {
+        let members: Vec<String> = self
+            .item
+            .iter()
+            .map(|member| match member {
+                Member::String(outer, inner) => {
+                    display_member_name(&unescaped_or_raw(*outer, *inner, source))
+                }
+                Member::Int(int, _) => int.to_string(),
+                other => other.span().slice(source).to_string(),
+            })
+            .collect();
+
+        members.join(".")
+    }
+}
+
+/// A member name as it would be written in a path, quoted if it couldn't be written bare.
+/// Double quotes only escape backslashes, so a name with a `"` in it is wrapped in
+/// backticks instead.
+fn display_member_name(name: &str) -> String {
+    let needs_quotes = name.is_empty()
+        || name
+            .chars()
+            .any(|c| c.is_whitespace() || c == '.' || c == '"' || c == '`' || c == '\\');
+
+    if !needs_quotes {
+        name.to_string()
+    } else if name.contains('"') && !name.contains('`') {
+        format!("`{}`", name)
+    } else {
+        let escaped = name.replace('\\', "\\\\").replace('"', "\\u{22}");
+        format!("\"{}\"", escaped)
+    }
+}
+
+/// Whether a word starts like a number does
+fn looks_numeric(text: &str) -> bool {
+    match text.chars().next() {
+        Some(c) => c.is_ascii_digit(),
+        None => false,
+    }
+}
+
+impl ExpandSyntax for ColumnPathShape {
+    type Output = Tagged<ColumnPathMembers>;
+
+    fn name(&self) -> &'static str {
+        "column path"
+    }
+
+    fn expand_syntax<'a, 'b>(
+        &self,
+        token_nodes: &'b mut TokensIterator<'a>,
+        context: &ExpandContext,
+    ) -> Result<Self::Output, ParseError> {
+        Ok(expand_column_path(token_nodes, context)?)
+    }
+}
+
+impl TestSyntax for ColumnPathShape {
+    fn test<'a, 'b>(
+        &self,
+        token_nodes: &'b mut TokensIterator<'a>,
+        context: &ExpandContext,
+    ) -> Option<Peeked<'a, 'b>> {
+        // the checkpoint is never committed, so the iterator is back where it started, and
+        // the warnings raised while looking ahead are dropped
+        let is_path = {
+            let checkpoint = token_nodes.checkpoint(context);
+            expand_column_path(checkpoint.iterator, context).is_ok()
+        };
+
+        if is_path {
+            Some(token_nodes.peek_any())
+        } else {
+            None
+        }
+    }
+}
+
+/// A `[index]` right after an expression, like the `[0]` in `$list[0]` or the
+/// `["a b"]` in `$record["a b"]`. It's expanded by `expand_member_index`, like an index
+/// right after a member. A `[` after whitespace starts a list instead, so it isn't an
+/// index.
+#[derive(Debug, Copy, Clone)]
+pub struct IndexShape;
+
+#[cfg(not(coloring_in_tokens))]
+impl FallibleColorSyntax for IndexShape {
+    type Info = ();
+    type Input = ();
+
+    fn color_syntax<'a, 'b>(
+        &self,
+        _input: &(),
+        token_nodes: &'b mut TokensIterator<'a>,
+        context: &ExpandContext,
+        shapes: &mut Vec<Spanned<FlatShape>>,
+    ) -> Result<(), ShellError> {
+        token_nodes.atomic(|token_nodes| {
+            let atom = expand_atom(token_nodes, "index", context, ExpansionRule::new())?;
+
+            match atom.item {
+                AtomicToken::SquareDelimited { nodes, spans } => {
+                    color_delimited_square(spans, nodes, atom.span, context, shapes);
+                    Ok(())
+                }
+                other => Err(ShellError::type_error(
+                    "index",
+                    other.type_name().spanned(atom.span),
+                )),
+            }
+        })
+    }
+}
+
+#[cfg(coloring_in_tokens)]
+impl FallibleColorSyntax for IndexShape {
+    type Info = ();
+    type Input = ();
+
+    fn name(&self) -> &'static str {
+        "IndexShape"
+    }
+
+    fn color_syntax<'a, 'b>(
+        &self,
+        _input: &(),
+        token_nodes: &'b mut TokensIterator<'a>,
+        context: &ExpandContext,
+    ) -> Result<(), ShellError> {
+        token_nodes.atomic(|token_nodes| {
+            let atom = expand_atom(token_nodes, "index", context, ExpansionRule::new())?;
+
+            match atom.item {
+                AtomicToken::SquareDelimited { nodes, spans } => {
+                    token_nodes.child((&nodes[..]).spanned(atom.span), |tokens| {
+                        color_delimited_square(spans, tokens, atom.span, context);
+                    });
+
+                    Ok(())
+                }
+                other => Err(ShellError::type_error(
+                    "index",
+                    other.type_name().spanned(atom.span),
+                )),
+            }
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct MemberShape;
+
+impl MemberShape {
+    /// Whether the next token is a bare word, string or integer, which could start a
+    /// member. This is much cheaper than expanding the member. Nothing is consumed.
+    pub fn peek(token_nodes: &mut TokensIterator<'_>, context: &ExpandContext) -> bool {
+        let checkpoint = token_nodes.checkpoint(context);
+        let peeked = checkpoint.iterator.peek_any();
+
+        match peeked.node {
+            Some(TokenNode::Token(Spanned { item, .. })) => match item {
+                RawToken::Bare | RawToken::String(..) | RawToken::Number(RawNumber::Int(..)) => {
+                    true
+                }
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+}
+
+#[cfg(not(coloring_in_tokens))]
+impl FallibleColorSyntax for MemberShape {
+    type Info = ();
+    type Input = ();
+
+    fn color_syntax<'a, 'b>(
+        &self,
+        _input: &(),
+        token_nodes: &'b mut TokensIterator<'a>,
+        context: &ExpandContext,
+        shapes: &mut Vec<Spanned<FlatShape>>,
+    ) -> Result<(), ShellError> {
+        color_member(token_nodes, context, shapes)
+    }
+}
+
 #[cfg(coloring_in_tokens)]
-impl FallibleColorSyntax for ColumnPathShape {
+impl FallibleColorSyntax for MemberShape {
     type Info = ();
     type Input = ();
 
     fn name(&self) -> &'static str {
-        "ColumnPathShape"
+        "MemberShape"
+    }
+
+    fn color_syntax<'a, 'b>(
+        &self,
+        _input: &(),
+        token_nodes: &'b mut TokensIterator<'a>,
+        context: &ExpandContext,
+    ) -> Result<(), ShellError> {
+        color_member(token_nodes, context, &mut TokenShapes)
+    }
+}
+
+fn color_member(
+    token_nodes: &mut TokensIterator<'_>,
+    context: &ExpandContext,
+    shapes: &mut impl ColorCollector,
+) -> Result<(), ShellError> {
+    color_member_head(token_nodes, context, shapes)?;
+
+    // The optional marker is part of the member, so it's colored along with it
+    if let Some(span) = expand_optional_marker(token_nodes, context) {
+        shapes.push(token_nodes, FlatShape::QuestionMark.spanned(span));
+    }
+
+    Ok(())
+}
+
+fn color_member_head(
+    token_nodes: &mut TokensIterator<'_>,
+    context: &ExpandContext,
+    shapes: &mut impl ColorCollector,
+) -> Result<(), ShellError> {
+    if let Some(span) = expand_record_key_member(token_nodes, context) {
+        shapes.push(token_nodes, FlatShape::BareMember.spanned(span));
+        return Ok(());
+    }
+
+    if let Some(span) = expand_entries_member(token_nodes, context) {
+        shapes.push(token_nodes, FlatShape::EntriesMember.spanned(span));
+        return Ok(());
+    }
+
+    if let Some(span) = expand_wildcard_member(token_nodes, context) {
+        shapes.push(token_nodes, FlatShape::WildcardMember.spanned(span));
+        return Ok(());
+    }
+
+    if let Some(span) = expand_glob_member(token_nodes, context) {
+        shapes.push(token_nodes, FlatShape::WildcardMember.spanned(span));
+        return Ok(());
+    }
+
+    if let Some(range) = expand_range_member(token_nodes, context)? {
+        for shape in range.shapes() {
+            shapes.push(token_nodes, shape);
+        }
+
+        return Ok(());
     }
 
-    fn color_syntax<'a, 'b>(
-        &self,
-        _input: &(),
-        token_nodes: &'b mut TokensIterator<'a>,
-        context: &ExpandContext,
-    ) -> Result<(), ShellError> {
-        // If there's not even one member shape, fail
-        color_fallible_syntax(&MemberShape, token_nodes, context)?;
+    if let Ok(int) = expand_syntax(&IntMemberShape, token_nodes, context) {
+        shapes.push(token_nodes, FlatShape::IntMember.spanned(int.span()));
+        return Ok(());
+    }
+
+    if let Some(span) = expand_decimal_member(token_nodes) {
+        shapes.push(token_nodes, FlatShape::BareMember.spanned(span));
+        return Ok(());
+    }
+
+    if let Some(span) = expand_bare_member(token_nodes, context) {
+        shapes.push(token_nodes, FlatShape::BareMember.spanned(span));
+        return Ok(());
+    }
+
+    // Look for a string token. If we don't find one, fail
+    shapes.color_with(&StringShape, &FlatShape::StringMember, token_nodes, context)
+}
+
+/// Lower a range member. Integer literal bounds are known right away, and any other
+/// bounds are evaluated along with the path.
+fn range_path_member(expr: &hir::Expression, span: Span) -> PathMember {
+    let range = match &expr.item {
+        hir::RawExpression::Range(range) => range,
+        _ => return PathMember::computed(expr.clone(), span),
+    };
+
+    match literal_range_bounds(range) {
+        Some((from, to)) => PathMember::range(from, to, *range.inclusive(), span),
+        None => PathMember::computed_range(range.clone(), span),
+    }
+}
+
+fn literal_range_bounds(range: &hir::Range) -> Option<(BigInt, Option<BigInt>)> {
+    let from = int_literal(range.from().as_ref()?)?;
+    let to = match range.to() {
+        Some(to) => Some(int_literal(to)?),
+        None => None,
+    };
+
+    Some((from, to))
+}
+
+fn int_literal(expr: &hir::Expression) -> Option<BigInt> {
+    match &expr.item {
+        hir::RawExpression::Literal(hir::Literal::Number(Number::Int(int))) => Some(int.clone()),
+        _ => None,
+    }
+}
+
+/// Expand a range in member position, like the `2..5`, `2..=5` or `2..` in `$rows.2..5`.
+/// If the next tokens aren't a range with a lower bound, nothing is consumed and this
+/// returns `Ok(None)`.
+fn expand_range_member(
+    token_nodes: &mut TokensIterator<'_>,
+    context: &ExpandContext,
+) -> Result<Option<RangeExpression>, ParseError> {
+    let checkpoint = token_nodes.checkpoint(context);
+
+    match expand_range(checkpoint.iterator, context)? {
+        Some(range) if range.has_lower_bound() => {
+            checkpoint.commit();
+            Ok(Some(range))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// In strict-record mode, expand an integer member as a column name, returning its span.
+/// Otherwise, or if the next token isn't an integer, nothing is consumed.
+fn expand_record_key_member(
+    token_nodes: &mut TokensIterator<'_>,
+    context: &ExpandContext,
+) -> Option<Span> {
+    if !context.strict_records {
+        return None;
+    }
+
+    match expand_syntax(&IntMemberShape, token_nodes, context) {
+        Ok(int) => Some(int.span()),
+        Err(_) => None,
+    }
+}
+
+/// Expand the `=` of an inclusive range, which the lexer produces as its own bare word
+pub(crate) fn expand_range_equals(
+    token_nodes: &mut TokensIterator<'_>,
+    context: &ExpandContext,
+) -> Option<Span> {
+    let mut peeked = token_nodes.peek_any();
+
+    let span = match peeked.node {
+        Some(TokenNode::Token(Spanned {
+            item: RawToken::Bare,
+            span,
+        })) if span.slice(context.source) == "=" => *span,
+        _ => return None,
+    };
+
+    peeked.commit();
+    Some(span)
+}
+
+/// Expand a `~` member, which addresses the key-value pairs of a row rather than a
+/// column named `~`, returning its span. If the next token isn't a `~`, nothing is
+/// consumed.
+fn expand_entries_member(
+    token_nodes: &mut TokensIterator<'_>,
+    context: &ExpandContext,
+) -> Option<Span> {
+    let mut peeked = token_nodes.peek_any();
+
+    let span = match peeked.node {
+        Some(TokenNode::Token(Spanned {
+            item: RawToken::Bare,
+            span,
+        })) if span.slice(context.source) == "~" => *span,
+        _ => return None,
+    };
+
+    peeked.commit();
+    Some(span)
+}
+
+/// Expand a `*` member, which addresses every row of a table, returning its span. The
+/// lexer only reads a lone `*` as a member right after a dot, so globs elsewhere are
+/// unaffected. If the next token isn't a lone `*`, nothing is consumed.
+fn expand_wildcard_member(
+    token_nodes: &mut TokensIterator<'_>,
+    context: &ExpandContext,
+) -> Option<Span> {
+    let mut peeked = token_nodes.peek_any();
+
+    let span = match peeked.node {
+        Some(TokenNode::Token(Spanned {
+            item: RawToken::GlobPattern,
+            span,
+        })) if span.slice(context.source) == "*" => *span,
+        _ => return None,
+    };
+
+    peeked.commit();
+    Some(span)
+}
+
+/// Expand a glob member, like `user_*` or `it?m`, returning its span. The lexer reads a
+/// word with a `*` in it as a glob pattern, and a word with a `?` in the middle as a bare
+/// word. A `?` at the end of a member is its optional marker, so `item?` isn't a glob,
+/// and a lone `*` or `?` after a dot means something else too. If the next token isn't a
+/// glob, nothing is consumed.
+fn expand_glob_member(
+    token_nodes: &mut TokensIterator<'_>,
+    context: &ExpandContext,
+) -> Option<Span> {
+    let mut peeked = token_nodes.peek_any();
+
+    let span = match peeked.node {
+        Some(TokenNode::Token(Spanned {
+            item: RawToken::GlobPattern,
+            span,
+        }))
+        | Some(TokenNode::Token(Spanned {
+            item: RawToken::Bare,
+            span,
+        })) => *span,
+        _ => return None,
+    };
+
+    let text = span.slice(context.source);
+    let is_glob_char = |c: char| c == '*' || c == '?';
+
+    if !text.contains(is_glob_char) || text.chars().all(is_glob_char) {
+        return None;
+    }
 
-        loop {
-            let checkpoint = token_nodes.checkpoint();
+    peeked.commit();
+    Some(span)
+}
 
-            match color_fallible_syntax_with(
-                &ColorableDotShape,
-                &FlatShape::Dot,
-                checkpoint.iterator,
-                context,
-            ) {
-                Err(_) => {
-                    // we already saw at least one member shape, so return successfully
-                    return Ok(());
-                }
+/// Expand a bare member, returning its span. A bare word that ends in a backslash
+/// escapes the dot right after it, so `a\.b` is a single member named `a.b`. If the next
+/// token isn't a bare word, nothing is consumed.
+fn expand_bare_member(
+    token_nodes: &mut TokensIterator<'_>,
+    context: &ExpandContext,
+) -> Option<Span> {
+    let mut span = BareShape.test(token_nodes, context)?.commit()?.span();
 
-                Ok(_) => {
-                    match color_fallible_syntax(&MemberShape, checkpoint.iterator, context) {
-                        Err(_) => {
-                            // we saw a dot but not a member (but we saw at least one member),
-                            // so don't commit the dot but return successfully
-                            return Ok(());
-                        }
+    while span.slice(context.source).ends_with('\\') {
+        let mut checkpoint = token_nodes.checkpoint(context);
 
-                        Ok(_) => {
-                            // we saw a dot and a member, so commit it and continue on
-                            checkpoint.commit();
-                        }
-                    }
-                }
+        match expand_escaped_dot(checkpoint.iterator, span) {
+            Some(word) => {
+                checkpoint.commit();
+                span = span.until(word);
             }
+            None => break,
         }
     }
+
+    Some(span)
 }
 
-impl FormatDebug for Tagged<Vec<Member>> {
-    fn fmt_debug(&self, f: &mut DebugFormatter, source: &str) -> fmt::Result {
-        self.item.fmt_debug(f, source)
-    }
+/// Expand a dot right after `span` and the word right after the dot, returning the
+/// word's span
+fn expand_escaped_dot(token_nodes: &mut TokensIterator<'_>, span: Span) -> Option<Span> {
+    let mut peeked = token_nodes.peek_any();
+
+    let dot = match peeked.node {
+        Some(TokenNode::Token(Spanned {
+            item: RawToken::Operator(Operator::Dot),
+            span: dot,
+        })) if dot.start() == span.end() => *dot,
+        _ => return None,
+    };
+
+    peeked.commit();
+    let mut peeked = token_nodes.peek_any();
+
+    let word = match peeked.node {
+        Some(TokenNode::Token(Spanned {
+            item: RawToken::Bare,
+            span: word,
+        }))
+        | Some(TokenNode::Token(Spanned {
+            item: RawToken::Number(_),
+            span: word,
+        })) if word.start() == dot.end() => *word,
+        _ => return None,
+    };
+
+    peeked.commit();
+    Some(word)
 }
 
-impl ExpandSyntax for ColumnPathShape {
-    type Output = Tagged<Vec<Member>>;
+/// Expand a decimal in member position, returning its span. The lexer reads the `1.5`
+/// in `$data.1.5` as a single number, so it names the column `"1.5"` rather than the
+/// integer members `1` and `5`. If the next token isn't a decimal, nothing is consumed.
+fn expand_decimal_member(token_nodes: &mut TokensIterator<'_>) -> Option<Span> {
+    let mut peeked = token_nodes.peek_any();
+
+    let span = match peeked.node {
+        Some(TokenNode::Token(Spanned {
+            item: RawToken::Number(RawNumber::Decimal(_)),
+            span,
+        })) => *span,
+        _ => return None,
+    };
+
+    peeked.commit();
+    Some(span)
+}
 
-    fn name(&self) -> &'static str {
-        "column path"
+/// Expand a `?` right after a member, returning its span. If the next token isn't a
+/// `?`, nothing is consumed.
+fn expand_optional_marker(
+    token_nodes: &mut TokensIterator<'_>,
+    context: &ExpandContext,
+) -> Option<Span> {
+    let mut peeked = token_nodes.peek_any();
+
+    let span = match peeked.node {
+        Some(TokenNode::Token(Spanned {
+            item: RawToken::Bare,
+            span,
+        })) if span.slice(context.source) == "?" => *span,
+        _ => return None,
+    };
+
+    peeked.commit();
+    Some(span)
+}
+
+/// The contents of a delimited group in member position
+enum MemberGroup {
+    Expression(hir::Expression),
+    /// An expression preceded by `...`, like `(...$defaults)`
+    Spread(hir::Expression),
+    /// Expressions separated by `|`, like `(left.name | right.name)`
+    Alternatives(Vec<hir::Expression>),
+}
+
+/// Expand a delimited expression in member position, like the computed member
+/// `(a ?? b ?? "x")` or the filter `[.active]`. If the next token isn't a group with
+/// the given delimiter, nothing is consumed and this returns `Ok(None)`. Once a group
+/// is seen, anything in it that isn't a single expression optionally preceded by a
+/// `...` spread, or expressions separated by `|`, is an error, as is nesting groups
+/// deeper than the context's limit.
+fn expand_member_group(
+    token_nodes: &mut TokensIterator<'_>,
+    context: &ExpandContext,
+    delimiter: Delimiter,
+    expected_end: &'static str,
+) -> Result<Option<(MemberGroup, Span)>, ParseError> {
+    let mut peeked = token_nodes.peek_any();
+
+    let (children, span, open) = match peeked.node {
+        Some(TokenNode::Delimited(Spanned {
+            item:
+                DelimitedNode {
+                    delimiter: found,
+                    children,
+                    spans,
+                },
+            span,
+        })) if *found == delimiter => (children, *span, spans.0),
+        _ => return Ok(None),
+    };
+
+    let depth = context.member_nesting.get();
+
+    if depth >= context.member_nesting_limit {
+        context.member_nesting_error.set(Some(open));
+        return Err(ParseError::too_deep(open));
     }
 
-    fn expand_syntax<'a, 'b>(
-        &self,
-        token_nodes: &'b mut TokensIterator<'a>,
-        context: &ExpandContext,
-    ) -> Result<Self::Output, ParseError> {
-        Ok(expand_column_path(token_nodes, context)?)
+    context.member_nesting.set(depth + 1);
+    let group = expand_member_group_contents(children, span, context, expected_end);
+    context.member_nesting.set(depth);
+
+    // The shapes between a group that's nested too deeply and this one may have
+    // swallowed its error, so check for it explicitly
+    if let Some(too_deep) = context.member_nesting_error.get() {
+        if depth == 0 {
+            context.member_nesting_error.set(None);
+        }
+
+        return Err(ParseError::too_deep(too_deep));
     }
+
+    let group = group?;
+
+    peeked.commit();
+    Ok(Some((group, span)))
 }
 
-#[derive(Debug, Copy, Clone)]
-pub struct MemberShape;
+fn expand_member_group_contents(
+    children: &[TokenNode],
+    span: Span,
+    context: &ExpandContext,
+    expected_end: &'static str,
+) -> Result<MemberGroup, ParseError> {
+    let mut tokens = TokensIterator::new(children, span, false);
+
+    // whitespace just inside the delimiters is allowed
+    let _ = expand_syntax(&WhitespaceShape, &mut tokens, context);
+    let spread = expand_ellipsis(&mut tokens, context);
+    let expr = expand_member_alternative(&mut tokens, context, span)?;
+    let mut alternatives = vec![];
+
+    while let Some(pipe) = expand_pipe_token(&mut tokens, context) {
+        if spread.is_some() {
+            return Err(ParseError::mismatch(expected_end, "pipe".spanned(pipe)));
+        }
 
-#[cfg(not(coloring_in_tokens))]
-impl FallibleColorSyntax for MemberShape {
-    type Info = ();
-    type Input = ();
+        alternatives.push(expand_member_alternative(&mut tokens, context, pipe)?);
+    }
 
-    fn color_syntax<'a, 'b>(
-        &self,
-        _input: &(),
-        token_nodes: &'b mut TokensIterator<'a>,
-        context: &ExpandContext,
-        shapes: &mut Vec<Spanned<FlatShape>>,
-    ) -> Result<(), ShellError> {
-        let bare = color_fallible_syntax_with(
-            &BareShape,
-            &FlatShape::BareMember,
-            token_nodes,
-            context,
-            shapes,
-        );
+    if !tokens.at_end_possible_ws() {
+        return Err(tokens.peek_non_ws().type_error(expected_end));
+    }
 
-        match bare {
-            Ok(_) => return Ok(()),
-            Err(_) => {
-                // If we don't have a bare word, we'll look for a string
-            }
+    match spread {
+        None if alternatives.is_empty() => Ok(MemberGroup::Expression(expr)),
+        None => {
+            alternatives.insert(0, expr);
+            Ok(MemberGroup::Alternatives(alternatives))
         }
+        Some(_) => Ok(MemberGroup::Spread(expr)),
+    }
+}
 
-        // Look for a string token. If we don't find one, fail
-        color_fallible_syntax_with(
-            &StringShape,
-            &FlatShape::StringMember,
-            token_nodes,
-            context,
-            shapes,
-        )
+/// Expand one of the `|`-separated expressions in a member group. An alternative
+/// can't be empty, like the one after the `|` in `(left.name | )`, so if there's
+/// nothing before the next `|` or the end of the group, the error points at `before`,
+/// the `|` or group that the alternative should have followed.
+fn expand_member_alternative(
+    tokens: &mut TokensIterator<'_>,
+    context: &ExpandContext,
+    before: Span,
+) -> Result<hir::Expression, ParseError> {
+    if tokens.at_end_possible_ws() || at_pipe_token(tokens, context) {
+        return Err(ParseError::mismatch(
+            "member alternative",
+            "nothing".spanned(before),
+        ));
     }
+
+    let _ = expand_syntax(&WhitespaceShape, tokens, context);
+    expand_expr(&NotExpressionShape, tokens, context)
 }
 
-#[cfg(coloring_in_tokens)]
-impl FallibleColorSyntax for MemberShape {
-    type Info = ();
-    type Input = ();
+/// A column number like `#3`, which addresses a column by its position
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct ColumnNumberShape;
+
+impl ExpandSyntax for ColumnNumberShape {
+    type Output = Member;
 
     fn name(&self) -> &'static str {
-        "MemberShape"
+        "column number"
     }
 
-    fn color_syntax<'a, 'b>(
+    fn expand_syntax<'a, 'b>(
         &self,
-        _input: &(),
         token_nodes: &'b mut TokensIterator<'a>,
         context: &ExpandContext,
-    ) -> Result<(), ShellError> {
-        let bare =
-            color_fallible_syntax_with(&BareShape, &FlatShape::BareMember, token_nodes, context);
-
-        match bare {
-            Ok(_) => return Ok(()),
-            Err(_) => {
-                // If we don't have a bare word, we'll look for a string
+    ) -> Result<Self::Output, ParseError> {
+        parse_single_node(token_nodes, "column number", |token, token_span, err| {
+            let text = token_span.slice(context.source);
+
+            match token {
+                RawToken::Bare
+                    if text.len() > 1
+                        && text.starts_with('#')
+                        && text[1..].chars().all(|c| c.is_ascii_digit()) =>
+                {
+                    Ok(Member::ColumnNumber(
+                        BigInt::from_str(&text[1..]).unwrap(),
+                        token_span,
+                    ))
+                }
+                _ => Err(err.error()),
             }
-        }
-
-        // Look for a string token. If we don't find one, fail
-        color_fallible_syntax_with(&StringShape, &FlatShape::StringMember, token_nodes, context)
+        })
     }
 }
 
+/// An integer member, like the `2` in `$list.2` or the `-1` in `$list.-1`.
+///
+/// A negative index is told apart from subtraction by the lexer: a `-` right after
+/// a dot is the sign of the number, so `$list.-1` is a single `-1` token, while the
+/// `-` in `$list - 1` is surrounded by whitespace and is lexed as the operator.
 #[derive(Debug, Copy, Clone)]
-struct IntMemberShape;
+pub(crate) struct IntMemberShape;
 
 impl ExpandSyntax for IntMemberShape {
     type Output = Member;
@@ -902,7 +2822,7 @@ impl ExpandSyntax for IntMemberShape {
         token_nodes: &'b mut TokensIterator<'a>,
         context: &ExpandContext,
     ) -> Result<Self::Output, ParseError> {
-        token_nodes.atomic_parse(|token_nodes| {
+        token_nodes.atomic_parse(context, |token_nodes| {
             let next = expand_atom(
                 token_nodes,
                 "integer member",
@@ -913,17 +2833,20 @@ impl ExpandSyntax for IntMemberShape {
             match next.item {
                 AtomicToken::Number {
                     number: RawNumber::Int(int),
-                } => Ok(Member::Int(
-                    BigInt::from_str(int.slice(context.source)).unwrap(),
-                    int,
-                )),
+                } => match parse_int(int.slice(context.source)) {
+                    Some(number) => Ok(Member::Int(number, int)),
+                    None => Err(ParseError::mismatch(
+                        "integer member",
+                        "number".spanned(int),
+                    )),
+                },
 
                 AtomicToken::Word { text } => {
-                    let int = BigInt::from_str(text.slice(context.source));
+                    let int = parse_int(text.slice(context.source));
 
                     match int {
-                        Ok(int) => return Ok(Member::Int(int, text)),
-                        Err(_) => Err(ParseError::mismatch("integer member", "word".spanned(text))),
+                        Some(int) => return Ok(Member::Int(int, text)),
+                        None => Err(ParseError::mismatch("integer member", "word".spanned(text))),
                     }
                 }
 
@@ -948,37 +2871,204 @@ impl ExpandSyntax for MemberShape {
         token_nodes: &mut TokensIterator<'_>,
         context: &ExpandContext,
     ) -> Result<Member, ParseError> {
-        if let Ok(int) = expand_syntax(&IntMemberShape, token_nodes, context) {
-            return Ok(int);
-        }
+        let member = expand_member(token_nodes, context)?;
+        check_deprecated_member(&member, context);
 
-        let bare = BareShape.test(token_nodes, context);
-        if let Some(peeked) = bare {
-            let node = peeked.not_eof("column")?.commit();
-            return Ok(Member::Bare(node.span()));
-        }
+        Ok(member)
+    }
+}
+
+fn expand_member(
+    token_nodes: &mut TokensIterator<'_>,
+    context: &ExpandContext,
+) -> Result<Member, ParseError> {
+    if let Some(span) = expand_record_key_member(token_nodes, context) {
+        let member = Member::Bare(span);
+        check_known_column(&member, context);
+        check_reserved_member(&member, context);
+        return Ok(member);
+    }
+
+    if let Some(range) = expand_range_member(token_nodes, context)? {
+        let span = range.span();
+        return Ok(Member::Range(range.into_expr(span), span));
+    }
+
+    if let Ok(int) = expand_syntax(&IntMemberShape, token_nodes, context) {
+        return Ok(int);
+    }
+
+    if let Ok(column) = expand_syntax(&ColumnNumberShape, token_nodes, context) {
+        return Ok(column);
+    }
+
+    if let Some((group, span)) = expand_member_group(
+        token_nodes,
+        context,
+        Delimiter::Paren,
+        "end of computed member",
+    )? {
+        return Ok(match group {
+            MemberGroup::Expression(expr) => Member::Computed(expr, span),
+            MemberGroup::Spread(expr) => Member::Spread(expr, span),
+            MemberGroup::Alternatives(exprs) => Member::Alternatives(exprs, span),
+        });
+    }
+
+    if let Some((group, span)) =
+        expand_member_group(token_nodes, context, Delimiter::Square, "end of filter")?
+    {
+        return match group {
+            MemberGroup::Expression(expr) => Ok(Member::Filter(expr, span)),
+            MemberGroup::Spread(_) => Err(ParseError::mismatch("filter", "spread".spanned(span))),
+            MemberGroup::Alternatives(_) => {
+                Err(ParseError::mismatch("filter", "alternatives".spanned(span)))
+            }
+        };
+    }
 
-        /* KATZ */
-        /* let number = NumberShape.test(token_nodes, context);
+    if let Some(span) = expand_entries_member(token_nodes, context) {
+        return Ok(Member::Entries(span));
+    }
+
+    if let Some(span) = expand_wildcard_member(token_nodes, context) {
+        return Ok(Member::Wildcard(span));
+    }
+
+    if let Some(span) = expand_glob_member(token_nodes, context) {
+        return Ok(Member::Glob(span));
+    }
+
+    if let Some(span) = expand_bare_member(token_nodes, context) {
+        let member = Member::Bare(span);
+        check_known_column(&member, context);
+        check_reserved_member(&member, context);
+        return Ok(member);
+    }
+
+    if let Some(span) = expand_decimal_member(token_nodes) {
+        let member = Member::Bare(span);
+        check_known_column(&member, context);
+        check_reserved_member(&member, context);
+        return Ok(member);
+    }
+
+    let string = StringShape.test(token_nodes, context);
 
-        if let Some(peeked) = number {
-            let node = peeked.not_eof("column")?.commit();
-            let (n, span) = node.as_number().unwrap();
+    if let Some(peeked) = string {
+        let node = peeked.not_eof("column")?.commit();
+        let (outer, inner) = node.as_string().unwrap();
+        quoted_member_name(outer, inner, context.source)?;
 
-            return Ok(Member::Number(n, span))
-        }*/
+        let member = Member::String(outer, inner);
+        check_known_column(&member, context);
+        check_reserved_member(&member, context);
+
+        return Ok(member);
+    }
 
-        let string = StringShape.test(token_nodes, context);
+    let peeked = token_nodes.peek_any();
 
-        if let Some(peeked) = string {
-            let node = peeked.not_eof("column")?.commit();
-            let (outer, inner) = node.as_string().unwrap();
+    // if something close to a known column is here instead, suggest that column
+    if let (Some(TokenNode::Token(token)), Some(known)) = (peeked.node, context.known_columns) {
+        let text = token.span.slice(context.source);
 
-            return Ok(Member::String(outer, inner));
+        if let Some(suggestion) = closest_column(known, text) {
+            return Err(ParseError::column_mismatch(
+                text.spanned(token.span),
+                suggestion,
+            ));
         }
+    }
+
+    Err(peeked.type_error("column"))
+}
+
+/// Warn about a member whose form the context treats as deprecated
+fn check_deprecated_member(member: &Member, context: &ExpandContext) {
+    let deprecated = match context.deprecated_members {
+        None => return,
+        Some(deprecated) => deprecated,
+    };
+
+    if deprecated.contains(&member.kind()) {
+        context.warn(ParseWarning::deprecated_member(
+            member.type_name().spanned(member.span()),
+        ));
+    }
+}
+
+/// Warn about a member whose name is one of the context's reserved member names
+fn check_reserved_member(member: &Member, context: &ExpandContext) {
+    let reserved = match context.reserved_members {
+        None => return,
+        Some(reserved) => reserved,
+    };
+
+    let name = match member {
+        Member::String(_, inner) => inner.slice(context.source).to_string(),
+        Member::Bare(span) => bare_member_name(*span, context.source),
+        _ => return,
+    };
+
+    if reserved.iter().any(|reserved| *reserved == name) {
+        context.warn(ParseWarning::reserved_member(name.spanned(member.span())));
+    }
+}
+
+/// If the context knows which columns exist, warn about a member that isn't one of them,
+/// suggesting the closest known column if there's one that's close enough
+fn check_known_column(member: &Member, context: &ExpandContext) {
+    let known = match context.known_columns {
+        None => return,
+        Some(known) => known,
+    };
+
+    let name = match member {
+        Member::String(_, inner) => inner.slice(context.source).to_string(),
+        Member::Bare(span) => bare_member_name(*span, context.source),
+        _ => return,
+    };
+
+    if known.iter().any(|column| *column == name) {
+        return;
+    }
+
+    let suggestion = closest_column(known, &name);
 
-        Err(token_nodes.peek_any().type_error("column"))
+    context.warn(ParseWarning::unknown_column(
+        name.spanned(member.span()),
+        suggestion,
+    ));
+}
+
+/// The known column closest to `name`, if there's one that's close enough
+fn closest_column(known: &[String], name: &str) -> Option<String> {
+    known
+        .iter()
+        .map(|column| (column_distance(column, name), column))
+        .filter(|(distance, _)| *distance <= std::cmp::max(1, name.len() / 3))
+        .min()
+        .map(|(_, column)| column.clone())
+}
+
+/// The edit distance between a known column and a member name. Swapping two adjacent
+/// letters, like `nmae` for `name`, is a common typo, so it counts as a single edit.
+fn column_distance(column: &str, name: &str) -> usize {
+    let left: Vec<char> = column.chars().collect();
+    let right: Vec<char> = name.chars().collect();
+
+    if left.len() == right.len() {
+        let differences: Vec<usize> = (0..left.len()).filter(|&i| left[i] != right[i]).collect();
+
+        if let [first, second] = differences[..] {
+            if second == first + 1 && left[first] == right[second] && left[second] == right[first] {
+                return 1;
+            }
+        }
     }
+
+    natural::distance::levenshtein_distance(column, name)
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -999,17 +3089,7 @@ impl FallibleColorSyntax for ColorableDotShape {
         _context: &ExpandContext,
         shapes: &mut Vec<Spanned<FlatShape>>,
     ) -> Result<(), ShellError> {
-        let peeked = token_nodes.peek_any().not_eof("dot")?;
-
-        match peeked.node {
-            node if node.is_dot() => {
-                peeked.commit();
-                shapes.push((*input).spanned(node.span()));
-                Ok(())
-            }
-
-            other => Err(ShellError::type_error("dot", other.spanned_type_name())),
-        }
+        color_dot(input, token_nodes, shapes)
     }
 }
 
@@ -1028,20 +3108,26 @@ impl FallibleColorSyntax for ColorableDotShape {
         token_nodes: &'b mut TokensIterator<'a>,
         _context: &ExpandContext,
     ) -> Result<(), ShellError> {
-        let peeked = token_nodes.peek_any().not_eof("dot")?;
-
-        match peeked.node {
-            node if node.is_dot() => {
-                peeked.commit();
-                token_nodes.color_shape((*input).spanned(node.span()));
-                Ok(())
-            }
+        color_dot(input, token_nodes, &mut TokenShapes)
+    }
+}
 
-            other => Err(ShellError::type_error(
-                "dot",
-                other.type_name().spanned(other.span()),
-            )),
+fn color_dot(
+    input: &FlatShape,
+    token_nodes: &mut TokensIterator<'_>,
+    shapes: &mut impl ColorCollector,
+) -> Result<(), ShellError> {
+    let peeked = token_nodes.peek_any().not_eof("dot")?;
+
+    match peeked.node {
+        node if node.is_dot() => {
+            let span = node.span();
+            peeked.commit();
+            shapes.push(token_nodes, (*input).spanned(span));
+            Ok(())
         }
+
+        other => Err(ShellError::type_error("dot", other.spanned_type_name())),
     }
 }
 
@@ -1083,9 +3169,84 @@ impl ExpandSyntax for DotShape {
     }
 }
 
+/// A lone `?`, which the tokenizer produces as a glob pattern
+#[derive(Debug, Copy, Clone)]
+pub struct QuestionMarkShape;
+
+impl ExpandSyntax for QuestionMarkShape {
+    type Output = Span;
+
+    fn name(&self) -> &'static str {
+        "question mark"
+    }
+
+    fn expand_syntax<'a, 'b>(
+        &self,
+        token_nodes: &'b mut TokensIterator<'a>,
+        context: &ExpandContext,
+    ) -> Result<Self::Output, ParseError> {
+        parse_single_node(
+            token_nodes,
+            "question mark",
+            |token, token_span, _| match token {
+                RawToken::GlobPattern | RawToken::Bare
+                    if token_span.slice(context.source) == "?" =>
+                {
+                    Ok(token_span)
+                }
+                _ => Err(ParseError::mismatch(
+                    "question mark",
+                    token.type_name().spanned(token_span),
+                )),
+            },
+        )
+    }
+}
+
+/// A `?` or `!` marker right after a member, which the tokenizer produces as a bare word
+#[derive(Debug, Copy, Clone)]
+pub struct MemberMarkerShape;
+
+impl ExpandSyntax for MemberMarkerShape {
+    type Output = Spanned<MemberMarker>;
+
+    fn name(&self) -> &'static str {
+        "member marker"
+    }
+
+    fn expand_syntax<'a, 'b>(
+        &self,
+        token_nodes: &'b mut TokensIterator<'a>,
+        context: &ExpandContext,
+    ) -> Result<Self::Output, ParseError> {
+        parse_single_node(token_nodes, "member marker", |token, token_span, _| match (
+            token,
+            token_span.slice(context.source),
+        ) {
+            (RawToken::Bare, "?") => Ok(MemberMarker::Optional.spanned(token_span)),
+            (RawToken::Bare, "!") => Ok(MemberMarker::Required.spanned(token_span)),
+            _ => Err(ParseError::mismatch(
+                "member marker",
+                token.type_name().spanned(token_span),
+            )),
+        })
+    }
+}
+
 #[derive(Debug, Copy, Clone)]
 pub struct InfixShape;
 
+impl InfixShape {
+    /// Whether the next tokens are whitespace followed by an infix operator (`.` isn't
+    /// one). Nothing is consumed.
+    pub fn peek(token_nodes: &mut TokensIterator<'_>, context: &ExpandContext) -> bool {
+        let checkpoint = token_nodes.checkpoint(context);
+
+        expand_syntax(&WhitespaceShape, checkpoint.iterator, context).is_ok()
+            && expand_syntax(&InfixInnerShape, checkpoint.iterator, context).is_ok()
+    }
+}
+
 #[cfg(not(coloring_in_tokens))]
 impl FallibleColorSyntax for InfixShape {
     type Info = ();
@@ -1096,38 +3257,9 @@ impl FallibleColorSyntax for InfixShape {
         _input: &(),
         token_nodes: &'b mut TokensIterator<'a>,
         context: &ExpandContext,
-        outer_shapes: &mut Vec<Spanned<FlatShape>>,
+        shapes: &mut Vec<Spanned<FlatShape>>,
     ) -> Result<(), ShellError> {
-        let checkpoint = token_nodes.checkpoint();
-        let mut shapes = vec![];
-
-        // An infix operator must be prefixed by whitespace. If no whitespace was found, fail
-        color_fallible_syntax(&WhitespaceShape, checkpoint.iterator, context, &mut shapes)?;
-
-        // Parse the next TokenNode after the whitespace
-        parse_single_node(
-            checkpoint.iterator,
-            "infix operator",
-            |token, token_span, err| {
-                match token {
-                    // If it's an operator (and not `.`), it's a match
-                    RawToken::Operator(operator) if operator != Operator::Dot => {
-                        shapes.push(FlatShape::Operator.spanned(token_span));
-                        Ok(())
-                    }
-
-                    // Otherwise, it's not a match
-                    _ => Err(err.error()),
-                }
-            },
-        )?;
-
-        // An infix operator must be followed by whitespace. If no whitespace was found, fail
-        color_fallible_syntax(&WhitespaceShape, checkpoint.iterator, context, &mut shapes)?;
-
-        outer_shapes.extend(shapes);
-        checkpoint.commit();
-        Ok(())
+        color_infix(token_nodes, context, shapes)
     }
 }
 
@@ -1146,39 +3278,39 @@ impl FallibleColorSyntax for InfixShape {
         token_nodes: &'b mut TokensIterator<'a>,
         context: &ExpandContext,
     ) -> Result<(), ShellError> {
-        let checkpoint = token_nodes.checkpoint();
+        color_infix(token_nodes, context, &mut TokenShapes)
+    }
+}
 
+fn color_infix(
+    token_nodes: &mut TokensIterator<'_>,
+    context: &ExpandContext,
+    shapes: &mut impl ColorCollector,
+) -> Result<(), ShellError> {
+    shapes.atomic(token_nodes, |token_nodes, shapes| {
         // An infix operator must be prefixed by whitespace. If no whitespace was found, fail
-        color_fallible_syntax(&WhitespaceShape, checkpoint.iterator, context)?;
+        shapes.color(&WhitespaceShape, token_nodes, context)?;
 
         // Parse the next TokenNode after the whitespace
-        let operator_span = parse_single_node(
-            checkpoint.iterator,
-            "infix operator",
-            |token, token_span, _| {
-                match token {
-                    // If it's an operator (and not `.`), it's a match
-                    RawToken::Operator(operator) if operator != Operator::Dot => Ok(token_span),
-
-                    // Otherwise, it's not a match
-                    _ => Err(ParseError::mismatch(
-                        "infix operator",
-                        token.type_name().spanned(token_span),
-                    )),
+        let operator =
+            parse_single_node(token_nodes, "infix operator", |token, token_span, err| {
+                match infix_operator(token, token_span, context) {
+                    Some(operator) => Ok(operator.spanned(token_span)),
+
+                    None => Err(err.error()),
                 }
-            },
-        )?;
+            })?;
 
-        checkpoint
-            .iterator
-            .color_shape(FlatShape::Operator.spanned(operator_span));
+        shapes.push(
+            token_nodes,
+            FlatShape::operator(operator.item).spanned(operator.span),
+        );
 
         // An infix operator must be followed by whitespace. If no whitespace was found, fail
-        color_fallible_syntax(&WhitespaceShape, checkpoint.iterator, context)?;
+        shapes.color(&WhitespaceShape, token_nodes, context)?;
 
-        checkpoint.commit();
         Ok(())
-    }
+    })
 }
 
 impl FormatDebug for Spanned<(Span, Spanned<Operator>, Span)> {
@@ -1199,7 +3331,7 @@ impl ExpandSyntax for InfixShape {
         token_nodes: &'b mut TokensIterator<'a>,
         context: &ExpandContext,
     ) -> Result<Self::Output, ParseError> {
-        let mut checkpoint = token_nodes.checkpoint();
+        let mut checkpoint = token_nodes.checkpoint(context);
 
         // An infix operator must be prefixed by whitespace
         let start = expand_syntax(&WhitespaceShape, checkpoint.iterator, context)?;
@@ -1207,8 +3339,28 @@ impl ExpandSyntax for InfixShape {
         // Parse the next TokenNode after the whitespace
         let operator = expand_syntax(&InfixInnerShape, &mut checkpoint.iterator, context)?;
 
-        // An infix operator must be followed by whitespace
-        let end = expand_syntax(&WhitespaceShape, checkpoint.iterator, context)?;
+        // An infix operator must be followed by whitespace. If something else follows, point
+        // at its first character.
+        let end = match expand_syntax(&WhitespaceShape, checkpoint.iterator, context) {
+            Ok(end) => end,
+            Err(err) => match checkpoint.iterator.peek_any().node {
+                None => return Err(err),
+                Some(node) => {
+                    let next = node.span();
+                    let width = next
+                        .slice(context.source)
+                        .chars()
+                        .next()
+                        .map(|c| c.len_utf8())
+                        .unwrap_or(0);
+
+                    return Err(ParseError::infix_without_whitespace(
+                        operator.span,
+                        Span::new(next.start(), next.start() + width),
+                    ));
+                }
+            },
+        };
 
         checkpoint.commit();
 
@@ -1235,18 +3387,41 @@ impl ExpandSyntax for InfixInnerShape {
     fn expand_syntax<'a, 'b>(
         &self,
         token_nodes: &'b mut TokensIterator<'a>,
-        _context: &ExpandContext,
+        context: &ExpandContext,
     ) -> Result<Self::Output, ParseError> {
-        parse_single_node(token_nodes, "infix operator", |token, token_span, err| {
-            Ok(match token {
-                // If it's an operator (and not `.`), it's a match
-                RawToken::Operator(operator) if operator != Operator::Dot => {
-                    operator.spanned(token_span)
-                }
+        match token_nodes.peek_operator() {
+            Some(operator) if operator.item != Operator::Dot => {
+                token_nodes.peek_non_ws().commit();
+                return Ok(operator);
+            }
+            _ => {}
+        }
 
-                // Otherwise, it's not a match
-                _ => return Err(err.error()),
-            })
+        // `*`, a lone `-` and word operators aren't lexed as operators, so look at the token itself
+        parse_single_node(token_nodes, "infix operator", |token, token_span, err| {
+            match infix_operator(token, token_span, context) {
+                Some(operator) => Ok(operator.spanned(token_span)),
+                None => Err(err.error()),
+            }
         })
     }
 }
+
+/// The infix operator that a token stands for, if any. `.` isn't an infix operator, and a
+/// lone `*` lexes as a glob, so it's taken to mean multiplication here. The word operators
+/// `in` and `not-in` lex as bare words, so they're only operators in infix position, and
+/// so does a lone `-`, which stays a plain argument anywhere else (like `cd -`).
+fn infix_operator(token: RawToken, span: Span, context: &ExpandContext) -> Option<Operator> {
+    match token {
+        RawToken::Operator(Operator::Dot) => None,
+        RawToken::Operator(operator) => Some(operator),
+        RawToken::GlobPattern if span.slice(context.source) == "*" => Some(Operator::Multiply),
+        RawToken::Bare => match span.slice(context.source) {
+            "in" => Some(Operator::In),
+            "not-in" => Some(Operator::NotIn),
+            "-" => Some(Operator::Minus),
+            _ => None,
+        },
+        _ => None,
+    }
+}
@@ -8,12 +8,20 @@ pub enum FlatShape {
     ItVariable,
     Variable,
     Operator,
+    ComparisonOperator,
+    LogicalOperator,
     Dot,
     InternalCommand,
     ExternalCommand,
     ExternalWord,
     BareMember,
     StringMember,
+    EntriesMember,
+    WildcardMember,
+    /// An integer index, like the `0` in `$list.0`
+    IntMember,
+    /// The optional marker after a member, like the `?` in `$x.name?`
+    QuestionMark,
     String,
     Path,
     Word,
@@ -25,10 +33,34 @@ pub enum FlatShape {
     Decimal,
     Whitespace,
     Error,
-    Size { number: Span, unit: Span },
+    Size {
+        number: Span,
+        unit: Span,
+    },
 }
 
 impl FlatShape {
+    /// The shape for an infix operator, so comparisons and logical connectives can be
+    /// styled apart from arithmetic
+    pub fn operator(operator: Operator) -> FlatShape {
+        match operator {
+            Operator::Equal
+            | Operator::NotEqual
+            | Operator::LessThan
+            | Operator::GreaterThan
+            | Operator::LessThanOrEqual
+            | Operator::GreaterThanOrEqual
+            | Operator::Compare
+            | Operator::RegexMatch
+            | Operator::NotRegexMatch
+            | Operator::In
+            | Operator::NotIn => FlatShape::ComparisonOperator,
+            Operator::And | Operator::Or | Operator::Not => FlatShape::LogicalOperator,
+            Operator::Dot => FlatShape::Dot,
+            _ => FlatShape::Operator,
+        }
+    }
+
     pub fn from(token: &TokenNode, source: &Text, shapes: &mut Vec<Spanned<FlatShape>>) -> () {
         match token {
             TokenNode::Token(token) => match token.item {
@@ -38,10 +70,9 @@ impl FlatShape {
                 RawToken::Number(RawNumber::Decimal(_)) => {
                     shapes.push(FlatShape::Decimal.spanned(token.span))
                 }
-                RawToken::Operator(Operator::Dot) => {
-                    shapes.push(FlatShape::Dot.spanned(token.span))
+                RawToken::Operator(operator) => {
+                    shapes.push(FlatShape::operator(operator).spanned(token.span))
                 }
-                RawToken::Operator(_) => shapes.push(FlatShape::Operator.spanned(token.span)),
                 RawToken::String(_) => shapes.push(FlatShape::String.spanned(token.span)),
                 RawToken::Variable(v) if v.slice(source) == "it" => {
                     shapes.push(FlatShape::ItVariable.spanned(token.span))
@@ -95,3 +126,79 @@ impl FlatShape {
         }
     }
 }
+
+/// The token types a `SemanticToken` can refer to, in legend order. An LSP server
+/// advertises this list, and each token's `token_type` is an index into it.
+pub const SEMANTIC_TOKEN_TYPES: &[&str] = &[
+    "variable",
+    "operator",
+    "property",
+    "string",
+    "number",
+    "function",
+    "parameter",
+];
+
+/// The token modifiers a `SemanticToken` can carry, in legend order. Each token's
+/// `modifiers` is a bitset over this list.
+pub const SEMANTIC_TOKEN_MODIFIERS: &[&str] = &["defaultLibrary"];
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct SemanticToken {
+    pub token_type: u32,
+    pub modifiers: u32,
+    pub span: Span,
+}
+
+impl SemanticToken {
+    pub fn from_shapes(shapes: &[Spanned<FlatShape>]) -> Vec<SemanticToken> {
+        shapes
+            .iter()
+            .filter_map(|shape| shape.item.semantic_token(shape.span))
+            .collect()
+    }
+}
+
+impl FlatShape {
+    /// The LSP semantic token for a shape, or `None` for shapes that have no semantic
+    /// meaning, like whitespace and errors
+    pub fn semantic_token(&self, span: Span) -> Option<SemanticToken> {
+        let (kind, modifiers) = match self {
+            FlatShape::ItVariable => ("variable", 1),
+            FlatShape::Variable => ("variable", 0),
+            FlatShape::OpenDelimiter(_)
+            | FlatShape::CloseDelimiter(_)
+            | FlatShape::Operator
+            | FlatShape::ComparisonOperator
+            | FlatShape::LogicalOperator
+            | FlatShape::Dot
+            | FlatShape::QuestionMark
+            | FlatShape::Pipe => ("operator", 0),
+            FlatShape::BareMember
+            | FlatShape::StringMember
+            | FlatShape::EntriesMember
+            | FlatShape::WildcardMember
+            | FlatShape::IntMember => ("property", 0),
+            FlatShape::String
+            | FlatShape::Path
+            | FlatShape::Word
+            | FlatShape::ExternalWord
+            | FlatShape::GlobPattern => ("string", 0),
+            FlatShape::Int | FlatShape::Decimal | FlatShape::Size { .. } => ("number", 0),
+            FlatShape::InternalCommand | FlatShape::ExternalCommand => ("function", 0),
+            FlatShape::Flag | FlatShape::ShorthandFlag => ("parameter", 0),
+            FlatShape::Whitespace | FlatShape::Error => return None,
+        };
+
+        let token_type = SEMANTIC_TOKEN_TYPES
+            .iter()
+            .position(|name| *name == kind)
+            .expect("semantic token type is in the legend") as u32;
+
+        Some(SemanticToken {
+            token_type,
+            modifiers,
+            span,
+        })
+    }
+}
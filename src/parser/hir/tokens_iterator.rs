@@ -1,15 +1,17 @@
 pub(crate) mod debug;
 
 use self::debug::{ColorTracer, ExpandTracer};
-use crate::errors::ShellError;
+use crate::errors::{ParseWarning, ShellError};
+use crate::parser::hir::syntax_shape::ExpandContext;
 #[cfg(coloring_in_tokens)]
 use crate::parser::hir::syntax_shape::FlatShape;
 use crate::parser::hir::Expression;
-use crate::parser::TokenNode;
+use crate::parser::{Operator, RawToken, TokenNode};
 use crate::prelude::*;
 use crate::{Span, Spanned, SpannedItem};
 #[allow(unused)]
 use getset::{Getters, MutGetters};
+use std::cell::RefCell;
 
 cfg_if::cfg_if! {
     if #[cfg(coloring_in_tokens)] {
@@ -55,6 +57,8 @@ pub struct Checkpoint<'content, 'me> {
     seen: indexmap::IndexSet<usize>,
     #[cfg(coloring_in_tokens)]
     shape_start: usize,
+    /// The warnings being collected, and how many there were when the checkpoint was made
+    warnings: Option<(&'me RefCell<Vec<ParseWarning>>, usize)>,
     committed: bool,
 }
 
@@ -73,6 +77,12 @@ impl<'content, 'me> std::ops::Drop for Checkpoint<'content, 'me> {
             state.seen = self.seen.clone();
             #[cfg(coloring_in_tokens)]
             state.shapes.truncate(self.shape_start);
+
+            // warnings raised by tokens that are being rolled back will be raised again,
+            // or not at all, if the tokens are expanded differently
+            if let Some((warnings, len)) = self.warnings {
+                warnings.borrow_mut().truncate(len);
+            }
         }
     }
 }
@@ -411,14 +421,19 @@ impl<'content> TokensIterator<'content> {
     }
 
     /// Use a checkpoint when you need to peek more than one token ahead, but can't be sure
-    /// that you'll succeed.
-    pub fn checkpoint<'me>(&'me mut self) -> Checkpoint<'content, 'me> {
+    /// that you'll succeed. If the checkpoint is rolled back, so are any warnings that
+    /// were raised on `context` after it was made.
+    pub fn checkpoint<'me, 'context: 'me>(
+        &'me mut self,
+        context: &ExpandContext<'context>,
+    ) -> Checkpoint<'content, 'me> {
         let state = &mut self.state;
 
         let index = state.index;
         #[cfg(coloring_in_tokens)]
         let shape_start = state.shapes.len();
         let seen = state.seen.clone();
+        let warnings = context.warnings_checkpoint();
 
         Checkpoint {
             iterator: self,
@@ -427,6 +442,7 @@ impl<'content> TokensIterator<'content> {
             committed: false,
             #[cfg(coloring_in_tokens)]
             shape_start,
+            warnings,
         }
     }
 
@@ -450,6 +466,7 @@ impl<'content> TokensIterator<'content> {
             committed: false,
             #[cfg(coloring_in_tokens)]
             shape_start,
+            warnings: None,
         };
 
         let value = block(checkpoint.iterator)?;
@@ -458,12 +475,14 @@ impl<'content> TokensIterator<'content> {
         return Ok(value);
     }
 
-    /// Use a checkpoint when you need to peek more than one token ahead, but can't be sure
-    /// that you'll succeed.
-    pub fn atomic_parse<'me, T>(
+    /// Like `atomic`, but if `block` fails, the tokens it consumed and the shapes it colored
+    /// before failing are kept, so a half-typed expression like `$x.` still colors as far
+    /// as it got. The error is still returned. The step that failed is expected to clean up
+    /// after itself.
+    pub fn best_effort<'me, T>(
         &'me mut self,
-        block: impl FnOnce(&mut TokensIterator<'content>) -> Result<T, ParseError>,
-    ) -> Result<T, ParseError> {
+        block: impl FnOnce(&mut TokensIterator<'content>) -> Result<T, ShellError>,
+    ) -> Result<T, ShellError> {
         let state = &mut self.state;
 
         let index = state.index;
@@ -478,8 +497,25 @@ impl<'content> TokensIterator<'content> {
             committed: false,
             #[cfg(coloring_in_tokens)]
             shape_start,
+            warnings: None,
         };
 
+        let result = block(checkpoint.iterator);
+
+        checkpoint.commit();
+        result
+    }
+
+    /// Use a checkpoint when you need to peek more than one token ahead, but can't be sure
+    /// that you'll succeed. Like `checkpoint`, this rolls back warnings raised on `context`
+    /// if `block` fails.
+    pub fn atomic_parse<'me, T>(
+        &'me mut self,
+        context: &ExpandContext,
+        block: impl FnOnce(&mut TokensIterator<'content>) -> Result<T, ParseError>,
+    ) -> Result<T, ParseError> {
+        let checkpoint = self.checkpoint(context);
+
         let value = block(checkpoint.iterator)?;
 
         checkpoint.commit();
@@ -505,6 +541,7 @@ impl<'content> TokensIterator<'content> {
             seen,
             committed: false,
             shape_start: 0,
+            warnings: None,
         };
 
         let value = block(checkpoint.iterator);
@@ -641,6 +678,18 @@ impl<'content> TokensIterator<'content> {
         }
     }
 
+    /// Peek the next token, not including whitespace, if it's an operator. Nothing is
+    /// consumed, so there's nothing to commit or roll back.
+    pub fn peek_operator(&self) -> Option<Spanned<Operator>> {
+        match peek(self, true)? {
+            TokenNode::Token(Spanned {
+                item: RawToken::Operator(operator),
+                span,
+            }) => Some(operator.spanned(*span)),
+            _ => None,
+        }
+    }
+
     fn commit(&mut self, from: usize, to: usize) {
         for index in from..to {
             self.state.seen.insert(index);
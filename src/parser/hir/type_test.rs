@@ -0,0 +1,33 @@
+use crate::parser::hir::syntax_shape::TypeName;
+use crate::parser::hir::Expression;
+use crate::prelude::*;
+
+use derive_new::new;
+use getset::Getters;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(
+    Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Getters, Serialize, Deserialize, new,
+)]
+#[get = "pub(crate)"]
+pub struct TypeTest {
+    expr: Expression,
+    type_name: Spanned<TypeName>,
+}
+
+impl fmt::Display for TypeTest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({} is {})", self.expr, self.type_name.as_str())
+    }
+}
+
+impl FormatDebug for TypeTest {
+    fn fmt_debug(&self, f: &mut DebugFormatter, source: &str) -> fmt::Result {
+        write!(f, "{}", self.expr.debug(source))?;
+        write!(f, " is ")?;
+        write!(f, "{}", self.type_name.debug(source))?;
+
+        Ok(())
+    }
+}
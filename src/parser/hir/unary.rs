@@ -0,0 +1,31 @@
+use crate::parser::{hir::Expression, Operator};
+use crate::prelude::*;
+
+use derive_new::new;
+use getset::Getters;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(
+    Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Getters, Serialize, Deserialize, new,
+)]
+#[get = "pub(crate)"]
+pub struct Unary {
+    op: Spanned<Operator>,
+    expr: Expression,
+}
+
+impl fmt::Display for Unary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({} {})", self.op.as_str(), self.expr)
+    }
+}
+
+impl FormatDebug for Unary {
+    fn fmt_debug(&self, f: &mut DebugFormatter, source: &str) -> fmt::Result {
+        write!(f, "{}", self.op.debug(source))?;
+        write!(f, "{}", self.expr.debug(source))?;
+
+        Ok(())
+    }
+}
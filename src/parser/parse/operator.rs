@@ -11,7 +11,36 @@ pub enum Operator {
     GreaterThan,
     LessThanOrEqual,
     GreaterThanOrEqual,
+    /// The three-way comparison `<=>`, which evaluates to -1, 0 or 1
+    Compare,
+    RegexMatch,
+    NotRegexMatch,
+    In,
+    NotIn,
+    Coalesce,
+    Plus,
+    /// `++`, which joins two lists or two strings
+    Append,
+    Minus,
+    Multiply,
+    Mod,
+    Pow,
+    And,
+    Or,
     Dot,
+    /// The prefix `-` in `-$x`
+    Negate,
+    /// The prefix `not` in `not $flag`
+    Not,
+}
+
+/// Which way a chain of operators with the same precedence groups
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Associativity {
+    /// `a - b - c` is `(a - b) - c`
+    Left,
+    /// `a ** b ** c` is `a ** (b ** c)`
+    Right,
 }
 
 impl FormatDebug for Operator {
@@ -33,7 +62,57 @@ impl Operator {
             Operator::GreaterThan => ">",
             Operator::LessThanOrEqual => "<=",
             Operator::GreaterThanOrEqual => ">=",
+            Operator::Compare => "<=>",
+            Operator::RegexMatch => "=~",
+            Operator::NotRegexMatch => "!~",
+            Operator::In => "in",
+            Operator::NotIn => "not-in",
+            Operator::Coalesce => "??",
+            Operator::Plus => "+",
+            Operator::Append => "++",
+            Operator::Minus => "-",
+            Operator::Multiply => "*",
+            Operator::Mod => "%",
+            Operator::Pow => "**",
+            Operator::And => "&&",
+            Operator::Or => "||",
             Operator::Dot => ".",
+            Operator::Negate => "-",
+            Operator::Not => "not",
+        }
+    }
+
+    /// How tightly the operator binds in a chain of infix operators. Higher binds tighter.
+    pub fn precedence(&self) -> usize {
+        match *self {
+            Operator::Dot | Operator::Negate => 8,
+            Operator::Pow => 7,
+            Operator::Multiply | Operator::Mod => 6,
+            Operator::Plus | Operator::Append | Operator::Minus => 5,
+            Operator::Coalesce => 4,
+            Operator::Equal
+            | Operator::NotEqual
+            | Operator::LessThan
+            | Operator::GreaterThan
+            | Operator::LessThanOrEqual
+            | Operator::GreaterThanOrEqual
+            | Operator::Compare
+            | Operator::RegexMatch
+            | Operator::NotRegexMatch
+            | Operator::In
+            | Operator::NotIn => 3,
+            // `not $a == $b` negates the comparison
+            Operator::Not => 2,
+            Operator::And => 1,
+            Operator::Or => 0,
+        }
+    }
+
+    /// How a chain of this operator groups. Only `**` groups to the right.
+    pub fn associativity(&self) -> Associativity {
+        match *self {
+            Operator::Pow => Associativity::Right,
+            _ => Associativity::Left,
         }
     }
 }
@@ -54,6 +133,20 @@ impl FromStr for Operator {
             ">" => Ok(Operator::GreaterThan),
             "<=" => Ok(Operator::LessThanOrEqual),
             ">=" => Ok(Operator::GreaterThanOrEqual),
+            "<=>" => Ok(Operator::Compare),
+            "=~" => Ok(Operator::RegexMatch),
+            "!~" => Ok(Operator::NotRegexMatch),
+            "in" => Ok(Operator::In),
+            "not-in" => Ok(Operator::NotIn),
+            "??" => Ok(Operator::Coalesce),
+            "+" => Ok(Operator::Plus),
+            "++" => Ok(Operator::Append),
+            "-" => Ok(Operator::Minus),
+            "*" => Ok(Operator::Multiply),
+            "%" => Ok(Operator::Mod),
+            "**" => Ok(Operator::Pow),
+            "&&" => Ok(Operator::And),
+            "||" => Ok(Operator::Or),
             "." => Ok(Operator::Dot),
             _ => Err(()),
         }
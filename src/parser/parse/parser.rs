@@ -68,6 +68,39 @@ macro_rules! operator {
             ))
         }
     };
+
+    // An operator that has to end at a boundary, so it isn't split off the front of a
+    // longer word
+    ($(#[$attr:meta])* $name:ident : $($token:literal)|+ , bounded) => {
+        $(#[$attr])*
+        #[tracable_parser]
+        pub fn $name(input: NomSpan) -> IResult<NomSpan, TokenNode> {
+            let start = input.offset;
+            let (input, tag) = operator!(@tag $($token)|+)(input)?;
+
+            if !is_boundary(input.fragment.chars().next()) {
+                return Err(nom::Err::Error(nom::error::make_error(
+                    input,
+                    nom::error::ErrorKind::Tag,
+                )));
+            }
+
+            let end = input.offset;
+
+            Ok((
+                input,
+                TokenTreeBuilder::spanned_op(tag.fragment, Span::new(start, end)),
+            ))
+        }
+    };
+
+    (@tag $token:literal) => {
+        tag($token)
+    };
+
+    (@tag $($token:literal)|+) => {
+        alt(($(tag($token)),+))
+    };
 }
 
 operator! { gt:  >  }
@@ -78,6 +111,24 @@ operator! { eq:  == }
 operator! { neq: != }
 operator! { dot: . }
 
+/// `<=>`, which has to be tried before `<=` and `<`
+#[tracable_parser]
+pub fn compare(input: NomSpan) -> IResult<NomSpan, TokenNode> {
+    let start = input.offset;
+    let (input, tag) = tag("<=>")(input)?;
+    let end = input.offset;
+
+    Ok((
+        input,
+        TokenTreeBuilder::spanned_op(tag.fragment, Span::new(start, end)),
+    ))
+}
+
+operator! {
+    /// `??` on its own, so that globs like `??.txt` still lex as globs
+    coalesce: "??", bounded
+}
+
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Ord, PartialOrd, Serialize, Deserialize)]
 pub enum Number {
     Int(BigInt),
@@ -185,7 +236,16 @@ pub fn raw_number(input: NomSpan) -> IResult<NomSpan, Spanned<RawNumber>> {
     let anchoral = input;
     let start = input.offset;
     let (input, neg) = opt(tag("-"))(input)?;
-    let (input, head) = digit1(input)?;
+
+    if let Ok((input, _)) = radix_int(input) {
+        let next = input.fragment.chars().next();
+
+        if is_boundary(next) || next == Some('.') {
+            return Ok((input, RawNumber::int(Span::new(start, input.offset))));
+        }
+    }
+
+    let (input, head) = separated_digits(input)?;
 
     match input.fragment.chars().next() {
         None => return Ok((input, RawNumber::int(Span::new(start, input.offset)))),
@@ -226,9 +286,69 @@ pub fn raw_number(input: NomSpan) -> IResult<NomSpan, Spanned<RawNumber>> {
     }
 }
 
+/// The rest of an integer with a radix prefix, like `0xff`, `0o17` or `0b1010`. The prefix
+/// must be followed by at least one digit of its radix, and digits may be grouped by single
+/// underscores.
+fn radix_int(input: NomSpan) -> IResult<NomSpan, NomSpan> {
+    let (input, radix) = alt((
+        value(16, tag("0x")),
+        value(8, tag("0o")),
+        value(2, tag("0b")),
+    ))(input)?;
+
+    let digit = move |c: char| c.is_digit(radix);
+
+    recognize(pair(
+        take_while1(digit),
+        many0(preceded(char('_'), take_while1(digit))),
+    ))(input)
+}
+
+/// Decimal digits, optionally grouped by single underscores, like `1_000_000`
+fn separated_digits(input: NomSpan) -> IResult<NomSpan, NomSpan> {
+    recognize(pair(digit1, many0(preceded(char('_'), digit1))))(input)
+}
+
+operator! {
+    /// `=~` or `!~` on its own
+    regex_match: "=~" | "!~", bounded
+}
+
+operator! {
+    /// `&&` on its own
+    and: "&&", bounded
+}
+
+operator! {
+    /// `||` on its own, which is lexed before a single `|` can split the pipeline
+    or: "||", bounded
+}
+
+operator! {
+    /// `+` on its own, so that words like `+%Y` aren't split up
+    plus: "+", bounded
+}
+
+operator! {
+    /// `++` on its own, which appends lists or strings. It's tried before `+`.
+    append: "++", bounded
+}
+
+operator! {
+    /// `**` on its own, so that globs like `**/*.rs` still lex as globs
+    pow: "**", bounded
+}
+
+operator! {
+    /// `%` on its own, so that words like `%Y` aren't split up
+    modulo: "%", bounded
+}
+
 #[tracable_parser]
 pub fn operator(input: NomSpan) -> IResult<NomSpan, TokenNode> {
-    let (input, operator) = alt((gte, lte, neq, gt, lt, eq))(input)?;
+    let (input, operator) = alt((
+        gte, compare, lte, neq, gt, lt, eq, coalesce, modulo, pow, append, plus, and, or,
+    ))(input)?;
 
     Ok((input, operator))
 }
@@ -264,9 +384,84 @@ pub fn sq_string(input: NomSpan) -> IResult<NomSpan, TokenNode> {
     ))
 }
 
+/// A backtick-quoted string. Nothing inside the backticks is special, so it's mostly
+/// useful for column names that aren't valid bare words, like `` $it.`first name` ``.
+#[tracable_parser]
+pub fn bt_string(input: NomSpan) -> IResult<NomSpan, TokenNode> {
+    let start = input.offset;
+    let (input, _) = char('`')(input)?;
+    let start1 = input.offset;
+    let (input, _) = many0(none_of("`"))(input)?;
+    let end1 = input.offset;
+    let (input, _) = char('`')(input)?;
+    let end = input.offset;
+
+    Ok((
+        input,
+        TokenTreeBuilder::spanned_string(Span::new(start1, end1), Span::new(start, end)),
+    ))
+}
+
+/// An interpolated string, like `$"Hello ($name)"`. The text around the parenthesized
+/// expressions is lexed as string segments, and `\\` and `\(` are a literal backslash and
+/// paren. A `(` that doesn't start a complete parenthesized expression is left in a segment
+/// for the parser to report.
+#[tracable_parser]
+pub fn interpolated_string(input: NomSpan) -> IResult<NomSpan, TokenNode> {
+    let start = input.offset;
+    let (mut input, _) = tag("$\"")(input)?;
+    let open = Span::new(start, input.offset);
+    let mut children = vec![];
+
+    loop {
+        let segment_start = input.offset;
+        let (rest, _) = many0(alt((tag("\\\\"), tag("\\("), is_not("\"(\\"), tag("\\"))))(input)?;
+        input = rest;
+
+        if input.offset > segment_start {
+            let segment = Span::new(segment_start, input.offset);
+            children.push(TokenTreeBuilder::spanned_string(segment, segment));
+        }
+
+        match input.fragment.chars().next() {
+            Some('"') => break,
+            Some('(') => match delimited_paren(input) {
+                Ok((rest, paren)) => {
+                    input = rest;
+                    children.push(paren);
+                }
+                Err(_) => {
+                    let (rest, paren) = tag("(")(input)?;
+                    input = rest;
+                    children.push(TokenTreeBuilder::spanned_string(paren, paren));
+                }
+            },
+            _ => {
+                return Err(nom::Err::Error(nom::error::make_error(
+                    input,
+                    nom::error::ErrorKind::Char,
+                )))
+            }
+        }
+    }
+
+    let close_start = input.offset;
+    let (input, _) = char('"')(input)?;
+    let close = Span::new(close_start, input.offset);
+
+    Ok((
+        input,
+        TokenTreeBuilder::spanned_interpolation(
+            children,
+            (open, close),
+            Span::new(start, input.offset),
+        ),
+    ))
+}
+
 #[tracable_parser]
 pub fn string(input: NomSpan) -> IResult<NomSpan, TokenNode> {
-    alt((sq_string, dq_string))(input)
+    alt((sq_string, dq_string, bt_string))(input)
 }
 
 #[tracable_parser]
@@ -393,7 +588,16 @@ pub fn shorthand(input: NomSpan) -> IResult<NomSpan, TokenNode> {
 
 #[tracable_parser]
 pub fn leaf(input: NomSpan) -> IResult<NomSpan, TokenNode> {
-    let (input, node) = alt((number, string, operator, flag, shorthand, var, external))(input)?;
+    let (input, node) = alt((
+        number,
+        string,
+        operator,
+        flag,
+        shorthand,
+        interpolated_string,
+        var,
+        external,
+    ))(input)?;
 
     Ok((input, node))
 }
@@ -403,7 +607,11 @@ pub fn token_list(input: NomSpan) -> IResult<NomSpan, Spanned<Vec<TokenNode>>> {
     let start = input.offset;
     let (input, first) = node(input)?;
 
-    let (input, mut list) = many0(pair(alt((whitespace, dot)), node))(input)?;
+    let (input, mut list) = many0(alt((
+        pair(dot, member_node),
+        pair(whitespace, node),
+        index_suffix,
+    )))(input)?;
 
     let end = input.offset;
 
@@ -413,6 +621,16 @@ pub fn token_list(input: NomSpan) -> IResult<NomSpan, Spanned<Vec<TokenNode>>> {
     ))
 }
 
+/// A `[ ... ]` right after another node, like the `[0]` in `$list[0]`. It's lexed the same
+/// way a list is, and the same way as the index in a `suffixed_member`; the parser decides
+/// that it's an index.
+#[tracable_parser]
+pub fn index_suffix(input: NomSpan) -> IResult<NomSpan, (TokenNode, Vec<TokenNode>)> {
+    let (input, square) = delimited_square(input)?;
+
+    Ok((input, (square, vec![])))
+}
+
 #[tracable_parser]
 pub fn spaced_token_list(input: NomSpan) -> IResult<NomSpan, Spanned<Vec<TokenNode>>> {
     let start = input.offset;
@@ -460,13 +678,47 @@ pub fn whitespace(input: NomSpan) -> IResult<NomSpan, TokenNode> {
     Ok((input, TokenTreeBuilder::spanned_ws(Span::new(left, right))))
 }
 
+/// The tokens in a parenthesized group, which can separate alternatives with `|`, like
+/// `(left.name | right.name)`, or pipe an expression into a pipeline, like
+/// `($it.users | each { ... })`. A top-level `|` separates a pipeline's commands instead,
+/// so a `|` is only lexed, as a bare word, inside parentheses.
+#[tracable_parser]
+pub fn piped_token_list(input: NomSpan) -> IResult<NomSpan, Spanned<Vec<TokenNode>>> {
+    let start = input.offset;
+    let (input, head) = spaced_token_list(input)?;
+    let (input, tail) = many0(pair(pipe_word, spaced_token_list))(input)?;
+    let end = input.offset;
+
+    let mut out = head.item;
+
+    for (pipe, items) in tail {
+        out.push(pipe);
+        out.extend(items.item);
+    }
+
+    Ok((input, out.spanned(Span::new(start, end))))
+}
+
+/// A `|` inside parentheses, lexed as a bare word
+#[tracable_parser]
+pub fn pipe_word(input: NomSpan) -> IResult<NomSpan, TokenNode> {
+    let start = input.offset;
+    let (input, _) = char('|')(input)?;
+    let end = input.offset;
+
+    Ok((input, TokenTreeBuilder::spanned_bare(Span::new(start, end))))
+}
+
 pub fn delimited(
     input: NomSpan,
     delimiter: Delimiter,
 ) -> IResult<NomSpan, (Span, Span, Spanned<Vec<TokenNode>>)> {
     let left = input.offset;
     let (input, open_span) = tag(delimiter.open())(input)?;
-    let (input, inner_items) = opt(spaced_token_list)(input)?;
+    let (input, inner_items) = match delimiter {
+        Delimiter::Paren => opt(piped_token_list)(input)?,
+        _ => opt(spaced_token_list)(input)?,
+    };
     let (input, close_span) = tag(delimiter.close())(input)?;
     let right = input.offset;
 
@@ -575,19 +827,237 @@ pub fn node1(input: NomSpan) -> IResult<NomSpan, TokenNode> {
     alt((leaf, bare, pattern, external_word, delimited_paren))(input)
 }
 
+/// A node right after a dot, which can also be a column number
+#[tracable_parser]
+pub fn member_node(input: NomSpan) -> IResult<NomSpan, Vec<TokenNode>> {
+    alt((
+        range_member,
+        to_list(wildcard_member),
+        to_list(column_number),
+        suffixed_member,
+        node,
+    ))(input)
+}
+
+/// A lone `*` right after a dot, like the one in `$table.*.name`, lexed as a glob
+/// pattern. A `*` anywhere else is lexed as it always was.
+#[tracable_parser]
+pub fn wildcard_member(input: NomSpan) -> IResult<NomSpan, TokenNode> {
+    let start = input.offset;
+    let (input, _) = char('*')(input)?;
+    let end = input.offset;
+
+    let next_char = input.fragment.chars().nth(0);
+
+    if !is_boundary(next_char) && next_char != Some('.') {
+        return Err(nom::Err::Error(nom::error::make_error(
+            input,
+            nom::error::ErrorKind::Char,
+        )));
+    }
+
+    Ok((
+        input,
+        TokenTreeBuilder::spanned_pattern(Span::new(start, end)),
+    ))
+}
+
+/// A range right after a dot, like the `2..5`, `2..=5` or `2..` in `$rows.2..5`. Its
+/// integer bounds and the `=` of an inclusive range are lexed as separate bare words.
+#[tracable_parser]
+pub fn range_member(input: NomSpan) -> IResult<NomSpan, Vec<TokenNode>> {
+    let (input, from) = range_bound(input)?;
+    let (input, (first, second)) = pair(dot, dot)(input)?;
+
+    let equals_start = input.offset;
+    let (input, equals) = opt(char('='))(input)?;
+    let equals =
+        equals.map(|_| TokenTreeBuilder::spanned_bare(Span::new(equals_start, equals_start + 1)));
+
+    let (input, to) = opt(range_bound)(input)?;
+
+    let next_char = input.fragment.chars().nth(0);
+
+    if (equals.is_some() && to.is_none()) || (!is_boundary(next_char) && next_char != Some('.')) {
+        return Err(nom::Err::Error(nom::error::make_error(
+            input,
+            nom::error::ErrorKind::OneOf,
+        )));
+    }
+
+    let mut result = vec![from, first, second];
+    result.extend(equals);
+    result.extend(to);
+
+    Ok((input, result))
+}
+
+/// A range in expression position, like `1..10`, `1..=10`, `$start..$end`, `..5` or `5..`.
+/// It's lexed like a range member, but either bound may be missing. A lone `..` is still a
+/// bare path. It's tried before the other nodes, so the `$start` in `$start..$end` isn't
+/// lexed as a variable on its own.
+#[tracable_parser]
+pub fn range_expression(input: NomSpan) -> IResult<NomSpan, Vec<TokenNode>> {
+    let (input, from) = opt(range_bound)(input)?;
+    let (input, (first, second)) = pair(dot, dot)(input)?;
+
+    let equals_start = input.offset;
+    let (input, equals) = opt(char('='))(input)?;
+    let equals =
+        equals.map(|_| TokenTreeBuilder::spanned_bare(Span::new(equals_start, equals_start + 1)));
+
+    let (input, to) = opt(range_bound)(input)?;
+
+    let next_char = input.fragment.chars().nth(0);
+
+    if (from.is_none() && to.is_none())
+        || (equals.is_some() && to.is_none())
+        || !is_boundary(next_char)
+    {
+        return Err(nom::Err::Error(nom::error::make_error(
+            input,
+            nom::error::ErrorKind::OneOf,
+        )));
+    }
+
+    let mut result: Vec<TokenNode> = from.into_iter().collect();
+    result.push(first);
+    result.push(second);
+    result.extend(equals);
+    result.extend(to);
+
+    Ok((input, result))
+}
+
+/// A bound of a range: a variable, or an integer lexed as a bare word
+fn range_bound(input: NomSpan) -> IResult<NomSpan, TokenNode> {
+    alt((var, int_range_bound))(input)
+}
+
+fn int_range_bound(input: NomSpan) -> IResult<NomSpan, TokenNode> {
+    let start = input.offset;
+    let (input, _) = opt(char('-'))(input)?;
+    let (input, _) = digit1(input)?;
+    let end = input.offset;
+
+    Ok((input, TokenTreeBuilder::spanned_bare(Span::new(start, end))))
+}
+
+/// A member followed right away by a `?` or `!` marker, a bracketed index, or both,
+/// like the `a?` and `b!` in `$it.a?.b!.c`, `name[0..3]` or `a[0]?`. Markers are lexed as
+/// separate bare words, so they aren't part of the member's name.
+#[tracable_parser]
+pub fn suffixed_member(input: NomSpan) -> IResult<NomSpan, Vec<TokenNode>> {
+    let start = input.offset;
+    let (input, _) = take_while1(is_start_bare_char)(input)?;
+    let (input, _) = take_while(|c| c != '?' && is_bare_char(c))(input)?;
+    let mut result = vec![TokenTreeBuilder::spanned_bare(Span::new(
+        start,
+        input.offset,
+    ))];
+
+    let (input, before_index) = opt(member_marker)(input)?;
+    let (input, index) = opt(delimited_square)(input)?;
+    let (input, after_index) = opt(member_marker)(input)?;
+
+    let next_char = input.fragment.chars().nth(0);
+
+    if (before_index.is_none() && index.is_none())
+        || (!is_boundary(next_char) && next_char != Some('.'))
+    {
+        return Err(nom::Err::Error(nom::error::make_error(
+            input,
+            nom::error::ErrorKind::OneOf,
+        )));
+    }
+
+    result.extend(before_index);
+    result.extend(index);
+    result.extend(after_index);
+
+    Ok((input, result))
+}
+
+/// A `?` or `!` marker right after a member, lexed as a bare word
+#[tracable_parser]
+pub fn member_marker(input: NomSpan) -> IResult<NomSpan, TokenNode> {
+    let start = input.offset;
+    let (input, _) = one_of("?!")(input)?;
+    let end = input.offset;
+
+    Ok((input, TokenTreeBuilder::spanned_bare(Span::new(start, end))))
+}
+
+/// A column number like `#3`, lexed as a bare word. It's only lexed right after a dot,
+/// so `#` anywhere else is unaffected.
+#[tracable_parser]
+pub fn column_number(input: NomSpan) -> IResult<NomSpan, TokenNode> {
+    let start = input.offset;
+    let (input, _) = char('#')(input)?;
+    let (input, _) = digit1(input)?;
+    let end = input.offset;
+
+    Ok((input, TokenTreeBuilder::spanned_bare(Span::new(start, end))))
+}
+
 #[tracable_parser]
 pub fn node(input: NomSpan) -> IResult<NomSpan, Vec<TokenNode>> {
     alt((
+        range_expression,
         to_list(leaf),
+        negation,
         bare_path,
         pattern_path,
         to_list(external_word),
         to_list(delimited_paren),
         to_list(delimited_brace),
         to_list(delimited_square),
+        spread,
     ))(input)
 }
 
+/// A `-` right before a variable or a parenthesized expression, like `-$x` or `-(1 + 2)`.
+/// A `-` right before a digit is already lexed as the sign of a number.
+#[tracable_parser]
+pub fn negation(input: NomSpan) -> IResult<NomSpan, Vec<TokenNode>> {
+    let start = input.offset;
+    let (input, tag) = tag("-")(input)?;
+
+    match input.fragment.chars().next() {
+        Some('$') | Some('(') => {}
+        _ => {
+            return Err(nom::Err::Error(nom::error::make_error(
+                input,
+                nom::error::ErrorKind::Tag,
+            )))
+        }
+    }
+
+    let end = input.offset;
+    let (input, next) = node(input)?;
+
+    let mut result = vec![TokenTreeBuilder::spanned_op(
+        tag.fragment,
+        Span::new(start, end),
+    )];
+    result.extend(next);
+
+    Ok((input, result))
+}
+
+/// A `...` right before a node, like the spread in `(...$defaults)`. A `...` before a
+/// bare word is already lexed by `bare_path`, so this only picks up the rest.
+#[tracable_parser]
+pub fn spread(input: NomSpan) -> IResult<NomSpan, Vec<TokenNode>> {
+    let (input, (first, second, third)) = tuple((dot, dot, dot))(input)?;
+    let (input, next) = node(input)?;
+
+    let mut result = vec![first, second, third];
+    result.extend(next);
+
+    Ok((input, result))
+}
+
 fn to_list(
     parser: impl Fn(NomSpan) -> IResult<NomSpan, TokenNode>,
 ) -> impl Fn(NomSpan) -> IResult<NomSpan, Vec<TokenNode>> {
@@ -696,6 +1166,8 @@ fn is_bare_char(c: char) -> bool {
     match c {
         '+' => true,
         _ if c.is_alphanumeric() => true,
+        // combining marks, like the accent in a decomposed `é`
+        _ if unicode_xid::UnicodeXID::is_xid_continue(c) => true,
         '\\' => true,
         '/' => true,
         '_' => true,
@@ -800,115 +1272,337 @@ mod tests {
     }
 
     #[test]
-    fn test_operator() {
+    fn test_integer_separators() {
         equal_tokens! {
             <nodes>
-            ">" -> b::token_list(vec![b::op(">")])
+            "1_000" -> b::token_list(vec![b::int_literal("1_000")])
         }
 
         equal_tokens! {
             <nodes>
-            ">=" -> b::token_list(vec![b::op(">=")])
+            "0xff_ff" -> b::token_list(vec![b::int_literal("0xff_ff")])
         }
 
         equal_tokens! {
             <nodes>
-            "<" -> b::token_list(vec![b::op("<")])
+            "_1" -> b::token_list(vec![b::bare("_1")])
         }
 
         equal_tokens! {
             <nodes>
-            "<=" -> b::token_list(vec![b::op("<=")])
+            "1__0" -> b::token_list(vec![b::bare("1__0")])
         }
 
         equal_tokens! {
             <nodes>
-            "==" -> b::token_list(vec![b::op("==")])
+            "1_" -> b::token_list(vec![b::bare("1_")])
         }
+    }
 
+    #[test]
+    fn test_radix_integer() {
         equal_tokens! {
             <nodes>
-            "!=" -> b::token_list(vec![b::op("!=")])
+            "0xff" -> b::token_list(vec![b::int_literal("0xff")])
         }
-    }
 
-    #[test]
-    fn test_string() {
         equal_tokens! {
             <nodes>
-            r#""hello world""# -> b::token_list(vec![b::string("hello world")])
+            "0o17" -> b::token_list(vec![b::int_literal("0o17")])
         }
 
         equal_tokens! {
             <nodes>
-            r#"'hello world'"# -> b::token_list(vec![b::string("hello world")])
+            "-0b1010" -> b::token_list(vec![b::int_literal("-0b1010")])
         }
-    }
 
-    #[test]
-    fn test_bare() {
         equal_tokens! {
             <nodes>
-            "hello" -> b::token_list(vec![b::bare("hello")])
+            "$bytes.0xff" -> b::token_list(vec![b::var("bytes"), b::op("."), b::int_literal("0xff")])
         }
-    }
 
-    #[test]
-    fn test_unit_sizes() {
         equal_tokens! {
             <nodes>
-            "450MB" -> b::token_list(vec![b::bare("450MB")])
+            "0x" -> b::token_list(vec![b::bare("0x")])
+        }
+
+        equal_tokens! {
+            <nodes>
+            "0xzz" -> b::token_list(vec![b::bare("0xzz")])
         }
     }
+
     #[test]
-    fn test_simple_path() {
+    fn test_interpolated_string() {
         equal_tokens! {
             <nodes>
-            "chrome.exe" -> b::token_list(vec![b::bare("chrome"), b::op(Operator::Dot), b::bare("exe")])
+            r#"$"a ($x) b""# -> b::token_list(vec![b::interpolation(vec![
+                b::segment("a "),
+                b::parens(vec![b::var("x")]),
+                b::segment(" b")
+            ])])
         }
 
         equal_tokens! {
             <nodes>
-            ".azure" -> b::token_list(vec![b::op(Operator::Dot), b::bare("azure")])
+            r#"$"a \(b""# -> b::token_list(vec![b::interpolation(vec![b::segment(r"a \(b")])])
         }
 
         equal_tokens! {
             <nodes>
-            r"C:\windows\system.dll" -> b::token_list(vec![b::bare(r"C:\windows\system"), b::op(Operator::Dot), b::bare("dll")])
+            r#"$"a \\($x)""# -> b::token_list(vec![b::interpolation(vec![
+                b::segment(r"a \\"),
+                b::parens(vec![b::var("x")])
+            ])])
         }
 
         equal_tokens! {
             <nodes>
-            r"C:\Code\-testing\my_tests.js" -> b::token_list(vec![b::bare(r"C:\Code\-testing\my_tests"), b::op(Operator::Dot), b::bare("js")])
+            r#"$"a ($x b""# -> b::token_list(vec![b::interpolation(vec![
+                b::segment("a "),
+                b::segment("("),
+                b::segment("$x b")
+            ])])
         }
     }
 
     #[test]
-    fn test_flag() {
+    fn test_operator() {
         equal_tokens! {
             <nodes>
-            "--amigos" -> b::token_list(vec![b::flag("arepas")])
+            ">" -> b::token_list(vec![b::op(">")])
         }
 
         equal_tokens! {
             <nodes>
-            "--all-amigos" -> b::token_list(vec![b::flag("all-amigos")])
+            ">=" -> b::token_list(vec![b::op(">=")])
         }
-    }
 
-    #[test]
-    fn test_shorthand_flag() {
         equal_tokens! {
             <nodes>
-            "-katz" -> b::token_list(vec![b::shorthand("katz")])
+            "<" -> b::token_list(vec![b::op("<")])
         }
-    }
 
-    #[test]
-    fn test_variable() {
         equal_tokens! {
             <nodes>
-            "$it" -> b::token_list(vec![b::var("it")])
+            "<=" -> b::token_list(vec![b::op("<=")])
+        }
+
+        equal_tokens! {
+            <nodes>
+            "<=>" -> b::token_list(vec![b::op("<=>")])
+        }
+
+        equal_tokens! {
+            <nodes>
+            "$a <=> $b" -> b::token_list(vec![b::var("a"), b::sp(), b::op("<=>"), b::sp(), b::var("b")])
+        }
+
+        equal_tokens! {
+            <nodes>
+            "$a <= $b" -> b::token_list(vec![b::var("a"), b::sp(), b::op("<="), b::sp(), b::var("b")])
+        }
+
+        equal_tokens! {
+            <nodes>
+            "$a ++ $b" -> b::token_list(vec![b::var("a"), b::sp(), b::op("++"), b::sp(), b::var("b")])
+        }
+
+        equal_tokens! {
+            <nodes>
+            "$a + $b" -> b::token_list(vec![b::var("a"), b::sp(), b::op("+"), b::sp(), b::var("b")])
+        }
+
+        equal_tokens! {
+            <nodes>
+            "==" -> b::token_list(vec![b::op("==")])
+        }
+
+        equal_tokens! {
+            <nodes>
+            "!=" -> b::token_list(vec![b::op("!=")])
+        }
+
+        equal_tokens! {
+            <nodes>
+            "=~" -> b::token_list(vec![b::op("=~")])
+        }
+
+        equal_tokens! {
+            <nodes>
+            "!~" -> b::token_list(vec![b::op("!~")])
+        }
+
+        equal_tokens! {
+            <nodes>
+            "??" -> b::token_list(vec![b::op("??")])
+        }
+
+        equal_tokens! {
+            <nodes>
+            "%" -> b::token_list(vec![b::op("%")])
+        }
+
+        equal_tokens! {
+            <nodes>
+            "**" -> b::token_list(vec![b::op("**")])
+        }
+
+        equal_tokens! {
+            <nodes>
+            "2**3" -> b::token_list(vec![b::pattern("2**3")])
+        }
+
+        equal_tokens! {
+            <nodes>
+            "+" -> b::token_list(vec![b::op("+")])
+        }
+
+        equal_tokens! {
+            <nodes>
+            "-" -> b::token_list(vec![b::bare("-")])
+        }
+
+        equal_tokens! {
+            <nodes>
+            "&&" -> b::token_list(vec![b::op("&&")])
+        }
+
+        equal_tokens! {
+            <nodes>
+            "||" -> b::token_list(vec![b::op("||")])
+        }
+    }
+
+    #[test]
+    fn test_signed_operand() {
+        equal_tokens! {
+            <nodes>
+            "1 - 2" -> b::token_list(vec![b::int(1), b::sp(), b::bare("-"), b::sp(), b::int(2)])
+        }
+
+        equal_tokens! {
+            <nodes>
+            "1 + -2" -> b::token_list(vec![b::int(1), b::sp(), b::op("+"), b::sp(), b::int(-2)])
+        }
+
+        equal_tokens! {
+            <nodes>
+            "1 - -2" -> b::token_list(vec![b::int(1), b::sp(), b::bare("-"), b::sp(), b::int(-2)])
+        }
+    }
+
+    #[test]
+    fn test_negative_member_and_subtraction() {
+        equal_tokens! {
+            <nodes>
+            "$x.-1" -> b::token_list(vec![b::var("x"), b::op("."), b::int(-1)])
+        }
+
+        equal_tokens! {
+            <nodes>
+            "$x - 1" -> b::token_list(vec![b::var("x"), b::sp(), b::bare("-"), b::sp(), b::int(1)])
+        }
+    }
+
+    #[test]
+    fn test_negation() {
+        equal_tokens! {
+            <nodes>
+            "-$x" -> b::token_list(vec![b::op("-"), b::var("x")])
+        }
+
+        equal_tokens! {
+            <nodes>
+            "-(1 + 2)" -> b::token_list(vec![
+                b::op("-"),
+                b::parens(vec![b::int(1), b::sp(), b::op("+"), b::sp(), b::int(2)])
+            ])
+        }
+    }
+
+    #[test]
+    fn test_string() {
+        equal_tokens! {
+            <nodes>
+            r#""hello world""# -> b::token_list(vec![b::string("hello world")])
+        }
+
+        equal_tokens! {
+            <nodes>
+            r#"'hello world'"# -> b::token_list(vec![b::string("hello world")])
+        }
+
+        equal_tokens! {
+            <nodes>
+            "`hello world`" -> b::token_list(vec![b::string("hello world")])
+        }
+    }
+
+    #[test]
+    fn test_bare() {
+        equal_tokens! {
+            <nodes>
+            "hello" -> b::token_list(vec![b::bare("hello")])
+        }
+    }
+
+    #[test]
+    fn test_unit_sizes() {
+        equal_tokens! {
+            <nodes>
+            "450MB" -> b::token_list(vec![b::bare("450MB")])
+        }
+    }
+    #[test]
+    fn test_simple_path() {
+        equal_tokens! {
+            <nodes>
+            "chrome.exe" -> b::token_list(vec![b::bare("chrome"), b::op(Operator::Dot), b::bare("exe")])
+        }
+
+        equal_tokens! {
+            <nodes>
+            ".azure" -> b::token_list(vec![b::op(Operator::Dot), b::bare("azure")])
+        }
+
+        equal_tokens! {
+            <nodes>
+            r"C:\windows\system.dll" -> b::token_list(vec![b::bare(r"C:\windows\system"), b::op(Operator::Dot), b::bare("dll")])
+        }
+
+        equal_tokens! {
+            <nodes>
+            r"C:\Code\-testing\my_tests.js" -> b::token_list(vec![b::bare(r"C:\Code\-testing\my_tests"), b::op(Operator::Dot), b::bare("js")])
+        }
+    }
+
+    #[test]
+    fn test_flag() {
+        equal_tokens! {
+            <nodes>
+            "--amigos" -> b::token_list(vec![b::flag("arepas")])
+        }
+
+        equal_tokens! {
+            <nodes>
+            "--all-amigos" -> b::token_list(vec![b::flag("all-amigos")])
+        }
+    }
+
+    #[test]
+    fn test_shorthand_flag() {
+        equal_tokens! {
+            <nodes>
+            "-katz" -> b::token_list(vec![b::shorthand("katz")])
+        }
+    }
+
+    #[test]
+    fn test_variable() {
+        equal_tokens! {
+            <nodes>
+            "$it" -> b::token_list(vec![b::var("it")])
         }
 
         equal_tokens! {
@@ -997,6 +1691,11 @@ mod tests {
             "$it.0" -> b::token_list(vec![b::var("it"), b::op("."), b::int(0)])
         }
 
+        equal_tokens! {
+            <nodes>
+            "$it.#3" -> b::token_list(vec![b::var("it"), b::op("."), b::bare("#3")])
+        }
+
         equal_tokens! {
             <nodes>
             "$head.part1.part2" -> b::token_list(vec![b::var("head"), b::op("."), b::bare("part1"), b::op("."), b::bare("part2")])
@@ -1013,6 +1712,281 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_unicode_member() {
+        equal_tokens! {
+            <nodes>
+            "$record.名前" -> b::token_list(vec![b::var("record"), b::op("."), b::bare("名前")])
+        }
+
+        equal_tokens! {
+            <nodes>
+            "$row.café" -> b::token_list(vec![b::var("row"), b::op("."), b::bare("café")])
+        }
+
+        // a decomposed `é`, an `e` followed by a combining accent
+        equal_tokens! {
+            <nodes>
+            "$row.cafe\u{301}" -> b::token_list(vec![b::var("row"), b::op("."), b::bare("cafe\u{301}")])
+        }
+
+        equal_tokens! {
+            <nodes>
+            "$a.名前 + 1" -> b::token_list(vec![b::var("a"), b::op("."), b::bare("名前"), b::sp(), b::op("+"), b::sp(), b::int(1)])
+        }
+    }
+
+    #[test]
+    fn test_indexed_member() {
+        equal_tokens! {
+            <nodes>
+            "$it.name[0..3]" -> b::token_list(vec![
+                b::var("it"),
+                b::op("."),
+                b::bare("name"),
+                b::square(vec![b::bare("0"), b::op("."), b::op("."), b::bare("3")])
+            ])
+        }
+    }
+
+    #[test]
+    fn test_range_member() {
+        equal_tokens! {
+            <nodes>
+            "$rows.2..=5" -> b::token_list(vec![
+                b::var("rows"),
+                b::op("."),
+                b::bare("2"),
+                b::op("."),
+                b::op("."),
+                b::bare("="),
+                b::bare("5")
+            ])
+        }
+
+        equal_tokens! {
+            <nodes>
+            "$rows.2..-1.name" -> b::token_list(vec![
+                b::var("rows"),
+                b::op("."),
+                b::bare("2"),
+                b::op("."),
+                b::op("."),
+                b::bare("-1"),
+                b::op("."),
+                b::bare("name")
+            ])
+        }
+    }
+
+    #[test]
+    fn test_range_expression() {
+        equal_tokens! {
+            <nodes>
+            "1..=10" -> b::token_list(vec![
+                b::bare("1"),
+                b::op("."),
+                b::op("."),
+                b::bare("="),
+                b::bare("10")
+            ])
+        }
+
+        equal_tokens! {
+            <nodes>
+            "..5" -> b::token_list(vec![b::op("."), b::op("."), b::bare("5")])
+        }
+
+        equal_tokens! {
+            <nodes>
+            "5.." -> b::token_list(vec![b::bare("5"), b::op("."), b::op(".")])
+        }
+
+        equal_tokens! {
+            <nodes>
+            ".." -> b::token_list(vec![b::op("."), b::op(".")])
+        }
+
+        equal_tokens! {
+            <nodes>
+            "$a..$b" -> b::token_list(vec![b::var("a"), b::op("."), b::op("."), b::var("b")])
+        }
+
+        equal_tokens! {
+            <nodes>
+            "[$a..=5]" -> b::token_list(vec![b::square(vec![
+                b::var("a"),
+                b::op("."),
+                b::op("."),
+                b::bare("="),
+                b::bare("5")
+            ])])
+        }
+    }
+
+    #[test]
+    fn test_index_suffix() {
+        equal_tokens! {
+            <nodes>
+            "$list[0]" -> b::token_list(vec![b::var("list"), b::square(vec![b::int(0)])])
+        }
+
+        equal_tokens! {
+            <nodes>
+            "$list [0]" -> b::token_list(vec![b::var("list"), b::sp(), b::square(vec![b::int(0)])])
+        }
+    }
+
+    #[test]
+    fn test_wildcard_member() {
+        equal_tokens! {
+            <nodes>
+            "$table.*.name" -> b::token_list(vec![
+                b::var("table"),
+                b::op("."),
+                b::pattern("*"),
+                b::op("."),
+                b::bare("name")
+            ])
+        }
+
+        equal_tokens! {
+            <nodes>
+            "cp * /dev/null" -> b::token_list(vec![b::bare("cp"), b::sp(), b::pattern("*"), b::sp(), b::bare("/dev/null")])
+        }
+    }
+
+    #[test]
+    fn test_marked_index() {
+        equal_tokens! {
+            <nodes>
+            "$it.a?[0]" -> b::token_list(vec![
+                b::var("it"),
+                b::op("."),
+                b::bare("a"),
+                b::bare("?"),
+                b::square(vec![b::int(0)])
+            ])
+        }
+
+        equal_tokens! {
+            <nodes>
+            "$it.a[0]?" -> b::token_list(vec![
+                b::var("it"),
+                b::op("."),
+                b::bare("a"),
+                b::square(vec![b::int(0)]),
+                b::bare("?")
+            ])
+        }
+    }
+
+    #[test]
+    fn test_member_markers() {
+        equal_tokens! {
+            <nodes>
+            "$it.a?.b!.c" -> b::token_list(vec![
+                b::var("it"),
+                b::op("."),
+                b::bare("a"),
+                b::bare("?"),
+                b::op("."),
+                b::bare("b"),
+                b::bare("!"),
+                b::op("."),
+                b::bare("c")
+            ])
+        }
+    }
+
+    #[test]
+    fn test_escaped_dot_member() {
+        equal_tokens! {
+            <nodes>
+            r"$record.a\.b" -> b::token_list(vec![b::var("record"), b::op("."), b::bare(r"a\"), b::op("."), b::bare("b")])
+        }
+    }
+
+    #[test]
+    fn test_glob_member() {
+        equal_tokens! {
+            <nodes>
+            "$table.user_*" -> b::token_list(vec![b::var("table"), b::op("."), b::pattern("user_*")])
+        }
+
+        equal_tokens! {
+            <nodes>
+            "$table.it?m" -> b::token_list(vec![b::var("table"), b::op("."), b::bare("it?m")])
+        }
+
+        // a `?` at the end is the member's optional marker
+        equal_tokens! {
+            <nodes>
+            "$table.item?" -> b::token_list(vec![b::var("table"), b::op("."), b::bare("item"), b::bare("?")])
+        }
+    }
+
+    #[test]
+    fn test_spread_member_group() {
+        equal_tokens! {
+            <nodes>
+            "$it.(...$defaults)" -> b::token_list(vec![
+                b::var("it"),
+                b::op("."),
+                b::parens(vec![b::op("."), b::op("."), b::op("."), b::var("defaults")])
+            ])
+        }
+    }
+
+    #[test]
+    fn test_piped_group() {
+        equal_tokens! {
+            <nodes>
+            "($it.users | each)" -> b::token_list(vec![
+                b::parens(vec![
+                    b::var("it"),
+                    b::op("."),
+                    b::bare("users"),
+                    b::sp(),
+                    b::bare("|"),
+                    b::sp(),
+                    b::bare("each"),
+                ])
+            ])
+        }
+    }
+
+    #[test]
+    fn test_unclosed_member_group() {
+        assert!(crate::parser::parse("$it.(a ?? b").is_err());
+    }
+
+    #[test]
+    fn test_backtick_member() {
+        equal_tokens! {
+            <nodes>
+            "$record.`weird.name`" -> b::token_list(vec![
+                b::var("record"),
+                b::op("."),
+                b::backtick_string("weird.name")
+            ])
+        }
+
+        equal_tokens! {
+            <nodes>
+            "$record.`weird name`" -> b::token_list(vec![
+                b::var("record"),
+                b::op("."),
+                b::backtick_string("weird name")
+            ])
+        }
+    }
+
+    #[test]
+    fn test_unterminated_backtick_member() {
+        assert!(crate::parser::parse("$record.`weird.name").is_err());
+    }
+
     #[test]
     fn test_nested_path() {
         equal_tokens! {
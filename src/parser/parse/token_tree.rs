@@ -64,6 +64,7 @@ impl fmt::Debug for DebugTokenNode<'_> {
                         Delimiter::Brace => "{",
                         Delimiter::Paren => "(",
                         Delimiter::Square => "[",
+                        Delimiter::Interpolation => "$\"",
                     }
                 )?;
 
@@ -78,6 +79,7 @@ impl fmt::Debug for DebugTokenNode<'_> {
                         Delimiter::Brace => "}",
                         Delimiter::Paren => ")",
                         Delimiter::Square => "]",
+                        Delimiter::Interpolation => "\"",
                     }
                 )
             }
@@ -302,6 +304,7 @@ impl DelimitedNode {
             Delimiter::Brace => "braced expression",
             Delimiter::Paren => "parenthesized expression",
             Delimiter::Square => "array literal or index operator",
+            Delimiter::Interpolation => "string interpolation",
         }
     }
 }
@@ -311,6 +314,8 @@ pub enum Delimiter {
     Paren,
     Brace,
     Square,
+    /// `$"..."`, whose children are string segments and parenthesized expressions
+    Interpolation,
 }
 
 impl Delimiter {
@@ -319,6 +324,7 @@ impl Delimiter {
             Delimiter::Paren => "(",
             Delimiter::Brace => "{",
             Delimiter::Square => "[",
+            Delimiter::Interpolation => "$\"",
         }
     }
 
@@ -327,6 +333,7 @@ impl Delimiter {
             Delimiter::Paren => ")",
             Delimiter::Brace => "}",
             Delimiter::Square => "]",
+            Delimiter::Interpolation => "\"",
         }
     }
 }
@@ -133,6 +133,22 @@ impl TokenTreeBuilder {
         })
     }
 
+    pub fn backtick_string(input: impl Into<String>) -> CurriedToken {
+        let input = input.into();
+
+        Box::new(move |b| {
+            let (start, _) = b.consume("`");
+            let (inner_start, inner_end) = b.consume(&input);
+            let (_, end) = b.consume("`");
+            b.pos = end;
+
+            TokenTreeBuilder::spanned_string(
+                Span::new(inner_start, inner_end),
+                Span::new(start, end),
+            )
+        })
+    }
+
     pub fn spanned_string(input: impl Into<Span>, span: impl Into<Span>) -> TokenNode {
         TokenNode::Token(RawToken::String(input.into()).spanned(span.into()))
     }
@@ -215,6 +231,21 @@ impl TokenTreeBuilder {
         })
     }
 
+    /// An integer token spelled exactly as written, like `0xff`
+    pub fn int_literal(input: impl Into<String>) -> CurriedToken {
+        let input = input.into();
+
+        Box::new(move |b| {
+            let (start, end) = b.consume(&input);
+            b.pos = end;
+
+            TokenTreeBuilder::spanned_number(
+                RawNumber::Int(Span::new(start, end)),
+                Span::new(start, end),
+            )
+        })
+    }
+
     pub fn decimal(input: impl Into<BigDecimal>) -> CurriedToken {
         let decimal = input.into();
 
@@ -383,6 +414,41 @@ impl TokenTreeBuilder {
         )
     }
 
+    pub fn interpolation(input: Vec<CurriedToken>) -> CurriedToken {
+        Box::new(move |b| {
+            let open = b.consume_span("$\"");
+            let mut tokens = vec![];
+            for item in input {
+                tokens.push(item(b));
+            }
+            let close = b.consume_span("\"");
+
+            TokenTreeBuilder::spanned_interpolation(tokens, (open, close), open.until(close))
+        })
+    }
+
+    pub fn spanned_interpolation(
+        input: impl Into<Vec<TokenNode>>,
+        spans: (Span, Span),
+        span: impl Into<Span>,
+    ) -> TokenNode {
+        TokenNode::Delimited(
+            DelimitedNode::new(Delimiter::Interpolation, spans, input.into()).spanned(span.into()),
+        )
+    }
+
+    /// A literal segment of an interpolated string, which isn't quoted on its own
+    pub fn segment(input: impl Into<String>) -> CurriedToken {
+        let input = input.into();
+
+        Box::new(move |b| {
+            let (start, end) = b.consume(&input);
+            b.pos = end;
+
+            TokenTreeBuilder::spanned_string(Span::new(start, end), Span::new(start, end))
+        })
+    }
+
     pub fn sp() -> CurriedToken {
         Box::new(|b| {
             let (start, end) = b.consume(" ");
@@ -1,6 +1,7 @@
 use crate::parser::Operator;
 use crate::prelude::*;
 use crate::Text;
+use num_traits::Num;
 use std::fmt;
 use std::str::FromStr;
 
@@ -61,14 +62,62 @@ impl RawNumber {
 
     pub(crate) fn to_number(self, source: &Text) -> Number {
         match self {
-            RawNumber::Int(tag) => Number::Int(BigInt::from_str(tag.slice(source)).unwrap()),
+            RawNumber::Int(tag) => Number::Int(parse_int(tag.slice(source)).unwrap()),
             RawNumber::Decimal(tag) => {
-                Number::Decimal(BigDecimal::from_str(tag.slice(source)).unwrap())
+                Number::Decimal(BigDecimal::from_str(&tag.slice(source).replace('_', "")).unwrap())
             }
         }
     }
 }
 
+/// Parse the text of an integer, which may have a `0x`, `0o` or `0b` radix prefix and `_`
+/// separators between its digits, like `1_000_000`. A prefix that isn't followed by digits
+/// of its radix, like `0x` or `0xzz`, isn't an integer, and neither are leading, trailing
+/// or doubled separators.
+pub(crate) fn parse_int(text: &str) -> Option<BigInt> {
+    let (negative, unsigned) = if text.starts_with('-') {
+        (true, &text[1..])
+    } else if text.starts_with('+') {
+        (false, &text[1..])
+    } else {
+        (false, text)
+    };
+
+    let (radix, digits) = if unsigned.starts_with("0x") {
+        (16, &unsigned[2..])
+    } else if unsigned.starts_with("0o") {
+        (8, &unsigned[2..])
+    } else if unsigned.starts_with("0b") {
+        (2, &unsigned[2..])
+    } else {
+        (10, unsigned)
+    };
+
+    let digits = without_separators(digits, radix)?;
+    let int = BigInt::from_str_radix(&digits, radix).ok()?;
+
+    match negative {
+        true => Some(-int),
+        false => Some(int),
+    }
+}
+
+/// The digits of an integer without their `_` separators, as long as every separator sits
+/// between two digits
+fn without_separators(digits: &str, radix: u32) -> Option<String> {
+    if digits.starts_with('_') || digits.ends_with('_') || digits.contains("__") {
+        return None;
+    }
+
+    let digits: String = digits.chars().filter(|c| *c != '_').collect();
+
+    if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+        return None;
+    }
+
+    Some(digits)
+}
+
 pub type Token = Spanned<RawToken>;
 
 impl Token {
@@ -13,6 +13,9 @@ pub enum Unit {
     Gigabyte,
     Terabyte,
     Petabyte,
+    Kibibyte,
+    Mebibyte,
+    Gibibyte,
 
     // Duration units
     Second,
@@ -46,6 +49,9 @@ impl Unit {
             Unit::Gigabyte => "GB",
             Unit::Terabyte => "TB",
             Unit::Petabyte => "PB",
+            Unit::Kibibyte => "KiB",
+            Unit::Mebibyte => "MiB",
+            Unit::Gibibyte => "GiB",
             Unit::Second => "s",
             Unit::Minute => "m",
             Unit::Hour => "h",
@@ -61,11 +67,14 @@ impl Unit {
 
         match self {
             Unit::Byte => Value::number(size),
-            Unit::Kilobyte => Value::number(size * 1024),
-            Unit::Megabyte => Value::number(size * 1024 * 1024),
-            Unit::Gigabyte => Value::number(size * 1024 * 1024 * 1024),
-            Unit::Terabyte => Value::number(size * 1024 * 1024 * 1024 * 1024),
-            Unit::Petabyte => Value::number(size * 1024 * 1024 * 1024 * 1024 * 1024),
+            Unit::Kilobyte => Value::number(size * 1000),
+            Unit::Megabyte => Value::number(size * 1000 * 1000),
+            Unit::Gigabyte => Value::number(size * 1000 * 1000 * 1000),
+            Unit::Terabyte => Value::number(size * 1000 * 1000 * 1000 * 1000),
+            Unit::Petabyte => Value::number(size * 1000 * 1000 * 1000 * 1000 * 1000),
+            Unit::Kibibyte => Value::number(size * 1024),
+            Unit::Mebibyte => Value::number(size * 1024 * 1024),
+            Unit::Gibibyte => Value::number(size * 1024 * 1024 * 1024),
             Unit::Second => Value::duration(convert_number_to_u64(&size)),
             Unit::Minute => Value::duration(60 * convert_number_to_u64(&size)),
             Unit::Hour => Value::duration(60 * 60 * convert_number_to_u64(&size)),
@@ -87,11 +96,14 @@ impl FromStr for Unit {
             "GB" | "gb" | "Gb" => Ok(Unit::Gigabyte),
             "TB" | "tb" | "Tb" => Ok(Unit::Terabyte),
             "PB" | "pb" | "Pb" => Ok(Unit::Petabyte),
-            "s" => Ok(Unit::Second),
-            "m" => Ok(Unit::Minute),
-            "h" => Ok(Unit::Hour),
-            "d" => Ok(Unit::Day),
-            "w" => Ok(Unit::Week),
+            "KiB" | "kib" => Ok(Unit::Kibibyte),
+            "MiB" | "mib" => Ok(Unit::Mebibyte),
+            "GiB" | "gib" => Ok(Unit::Gibibyte),
+            "s" | "sec" => Ok(Unit::Second),
+            "m" | "min" => Ok(Unit::Minute),
+            "h" | "hr" => Ok(Unit::Hour),
+            "d" | "day" => Ok(Unit::Day),
+            "w" | "wk" => Ok(Unit::Week),
             "M" => Ok(Unit::Month),
             "y" => Ok(Unit::Year),
             _ => Err(()),
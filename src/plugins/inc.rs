@@ -245,7 +245,7 @@ mod tests {
         fn with_parameter(&mut self, name: &str) -> &mut Self {
             let fields: Vec<PathMember> = name
                 .split(".")
-                .map(|s| RawPathMember::String(s.to_string()).spanned_unknown())
+                .map(|s| RawPathMember::String(s.into()).spanned_unknown())
                 .collect();
 
             self.positionals
@@ -336,8 +336,8 @@ mod tests {
                 .field
                 .map(|f| f.iter().map(|f| f.item.clone()).collect()),
             Some(vec![
-                RawPathMember::String("package".to_string()),
-                RawPathMember::String("version".to_string())
+                RawPathMember::String("package".into()),
+                RawPathMember::String("version".into())
             ])
         );
     }
@@ -382,8 +382,8 @@ mod tests {
                 .field
                 .map(|f| f.iter().cloned().map(|f| f.item).collect()),
             Some(vec![
-                RawPathMember::String("package".to_string()),
-                RawPathMember::String("description".to_string())
+                RawPathMember::String("package".into()),
+                RawPathMember::String("description".into())
             ])
         )
     }
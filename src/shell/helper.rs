@@ -146,12 +146,18 @@ fn paint_flat_shape(flat_shape: &Spanned<FlatShape>, line: &str) -> String {
         FlatShape::ItVariable => Color::Purple.bold(),
         FlatShape::Variable => Color::Purple.normal(),
         FlatShape::Operator => Color::Yellow.normal(),
+        FlatShape::ComparisonOperator => Color::Yellow.bold(),
+        FlatShape::LogicalOperator => Color::Purple.normal(),
         FlatShape::Dot => Color::White.normal(),
         FlatShape::InternalCommand => Color::Cyan.bold(),
         FlatShape::ExternalCommand => Color::Cyan.normal(),
         FlatShape::ExternalWord => Color::Black.bold(),
         FlatShape::BareMember => Color::Yellow.bold(),
         FlatShape::StringMember => Color::Yellow.bold(),
+        FlatShape::EntriesMember => Color::Yellow.italic(),
+        FlatShape::WildcardMember => Color::Yellow.italic(),
+        FlatShape::IntMember => Color::Purple.bold(),
+        FlatShape::QuestionMark => Color::White.normal(),
         FlatShape::String => Color::Green.normal(),
         FlatShape::Path => Color::Cyan.normal(),
         FlatShape::GlobPattern => Color::Cyan.bold(),
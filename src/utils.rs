@@ -8,8 +8,22 @@ use std::path::{Component, Path, PathBuf};
 
 pub fn did_you_mean(obj_source: &Value, field_tried: &PathMember) -> Option<Vec<(usize, String)>> {
     let field_tried = match &field_tried.item {
-        RawPathMember::String(string) => string.clone(),
+        RawPathMember::String(string) => string.to_string(),
         RawPathMember::Int(int) => format!("{}", int),
+        RawPathMember::SmallInt(int) => format!("{}", int),
+        RawPathMember::Entries
+        | RawPathMember::Wildcard
+        | RawPathMember::Glob(_)
+        | RawPathMember::ColumnNumber(_)
+        | RawPathMember::Splat(_)
+        | RawPathMember::Computed(_)
+        | RawPathMember::Filter(_)
+        | RawPathMember::Spread(_)
+        | RawPathMember::Alternatives(_)
+        | RawPathMember::ComputedRange(_)
+        | RawPathMember::Conditional(..)
+        | RawPathMember::Cast(..)
+        | RawPathMember::Range { .. } => return None,
     };
 
     let possibilities = obj_source.data_descriptors();